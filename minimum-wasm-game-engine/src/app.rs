@@ -0,0 +1,89 @@
+//! 应用和插件
+//!
+//! [`App`] 持有世界和调度, 后端负责窗口和主循环: 每帧把输入和窗口大小写进资源,
+//! 再调用 [`App::update`]. 同一套插件可以跑在 macroquad、winit 或无窗口的后端上.
+
+use crate::{
+    CommandBuffer, Input, Resource, Schedule, Stage, SystemConfig, Time, WindowSize, World,
+};
+
+/// 给应用添加系统和资源的扩展点
+pub trait Plugin {
+    fn build(&self, app: &mut App);
+}
+
+impl<F: Fn(&mut App)> Plugin for F {
+    fn build(&self, app: &mut App) {
+        self(app);
+    }
+}
+
+/// 驱动应用主循环的后端
+pub trait Backend {
+    fn run(self, app: App);
+}
+
+/// 插入这个资源后后端在当前帧结束时退出
+#[derive(Debug, Default, Clone, Copy)]
+pub struct AppExit;
+
+pub struct App {
+    pub world: World,
+    pub schedule: Schedule,
+}
+
+impl Default for App {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl App {
+    /// 带有 [`Input`]、[`Time`] 和 [`WindowSize`] 资源的空应用
+    pub fn new() -> Self {
+        let mut world = World::new();
+        world.insert_resource(Input::default());
+        world.insert_resource(Time::default());
+        world.insert_resource(WindowSize::default());
+        Self {
+            world,
+            schedule: Schedule::new(),
+        }
+    }
+
+    pub fn add_plugin(&mut self, plugin: impl Plugin) -> &mut Self {
+        plugin.build(self);
+        self
+    }
+
+    pub fn insert_resource<R: Resource>(&mut self, resource: R) -> &mut Self {
+        self.world.insert_resource(resource);
+        self
+    }
+
+    /// 见 [`Schedule::add_system`]
+    pub fn add_system(
+        &mut self,
+        stage: Stage,
+        name: &'static str,
+        system: impl FnMut(&World, &mut CommandBuffer) + Send + 'static,
+    ) -> &mut SystemConfig {
+        self.schedule.add_system(stage, name, system)
+    }
+
+    /// 执行一帧, 后端在写入这一帧的输入之后调用
+    pub fn update(&mut self, delta: f32) {
+        self.world.resource_mut::<Time>().advance(delta);
+        self.schedule.run(&mut self.world, delta);
+        self.world.resource_mut::<Input>().end_frame();
+    }
+
+    /// 是否有系统请求退出
+    pub fn should_exit(&self) -> bool {
+        self.world.contains_resource::<AppExit>()
+    }
+
+    pub fn run(self, backend: impl Backend) {
+        backend.run(self);
+    }
+}
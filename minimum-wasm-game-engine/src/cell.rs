@@ -0,0 +1,208 @@
+//! 用原子计数做借用检查的 `RefCell`
+//!
+//! 和 `std::cell::RefCell` 一样在借用冲突时 panic, 但可以在线程间共享,
+//! 并行执行的系统因此可以同时借用不同的组件列. 没有线程的 wasm32 上原子操作就是普通读写.
+
+use std::cell::UnsafeCell;
+use std::fmt;
+use std::marker::PhantomData;
+use std::mem;
+use std::ops::{Deref, DerefMut};
+use std::ptr::NonNull;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// 借用计数为这个值时表示正在被可变借用
+const WRITING: usize = usize::MAX;
+
+pub(crate) struct AtomicRefCell<T: ?Sized> {
+    borrow: AtomicUsize,
+    value: UnsafeCell<T>,
+}
+
+// 和 `RwLock` 相同的要求: 共享借用可以跨线程, 可变借用可以把值交给别的线程
+unsafe impl<T: ?Sized + Send> Send for AtomicRefCell<T> {}
+unsafe impl<T: ?Sized + Send + Sync> Sync for AtomicRefCell<T> {}
+
+impl<T> AtomicRefCell<T> {
+    pub(crate) fn new(value: T) -> Self {
+        Self {
+            borrow: AtomicUsize::new(0),
+            value: UnsafeCell::new(value),
+        }
+    }
+
+    pub(crate) fn into_inner(self) -> T {
+        self.value.into_inner()
+    }
+}
+
+impl<T: ?Sized> AtomicRefCell<T> {
+    /// 共享借用, 正在被可变借用时 panic
+    #[track_caller]
+    pub(crate) fn borrow(&self) -> Ref<'_, T> {
+        let mut current = self.borrow.load(Ordering::Relaxed);
+        loop {
+            assert!(current < WRITING - 1, "already mutably borrowed");
+            match self.borrow.compare_exchange_weak(
+                current,
+                current + 1,
+                Ordering::Acquire,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => break,
+                Err(actual) => current = actual,
+            }
+        }
+        Ref {
+            // 计数不为 0 时没有可变借用
+            value: unsafe { &*self.value.get() },
+            borrow: &self.borrow,
+        }
+    }
+
+    /// 可变借用, 已经被借用时 panic
+    #[track_caller]
+    pub(crate) fn borrow_mut(&self) -> RefMut<'_, T> {
+        if self
+            .borrow
+            .compare_exchange(0, WRITING, Ordering::Acquire, Ordering::Relaxed)
+            .is_err()
+        {
+            panic!("already borrowed");
+        }
+        RefMut {
+            // 计数为 WRITING 时只有这一个借用
+            value: unsafe { NonNull::new_unchecked(self.value.get()) },
+            borrow: &self.borrow,
+            _marker: PhantomData,
+        }
+    }
+
+    pub(crate) fn get_mut(&mut self) -> &mut T {
+        self.value.get_mut()
+    }
+}
+
+/// [`World`](crate::World) 中组件或资源的共享借用
+pub struct Ref<'a, T: ?Sized> {
+    value: &'a T,
+    borrow: &'a AtomicUsize,
+}
+
+impl<'a, T: ?Sized> Ref<'a, T> {
+    /// 借用其中的一部分
+    pub fn map<U: ?Sized>(orig: Self, f: impl FnOnce(&T) -> &U) -> Ref<'a, U> {
+        let value = f(orig.value);
+        let borrow = orig.borrow;
+        mem::forget(orig);
+        Ref { value, borrow }
+    }
+}
+
+impl<T: ?Sized> Deref for Ref<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.value
+    }
+}
+
+impl<T: ?Sized> Drop for Ref<'_, T> {
+    fn drop(&mut self) {
+        self.borrow.fetch_sub(1, Ordering::Release);
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for Ref<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.value.fmt(f)
+    }
+}
+
+/// [`World`](crate::World) 中组件或资源的可变借用
+pub struct RefMut<'a, T: ?Sized> {
+    value: NonNull<T>,
+    borrow: &'a AtomicUsize,
+    _marker: PhantomData<&'a mut T>,
+}
+
+impl<'a, T: ?Sized> RefMut<'a, T> {
+    /// 借用其中的一部分
+    pub fn map<U: ?Sized>(mut orig: Self, f: impl FnOnce(&mut T) -> &mut U) -> RefMut<'a, U> {
+        let value = NonNull::from(f(unsafe { orig.value.as_mut() }));
+        let borrow = orig.borrow;
+        mem::forget(orig);
+        RefMut {
+            value,
+            borrow,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T: ?Sized> Deref for RefMut<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { self.value.as_ref() }
+    }
+}
+
+impl<T: ?Sized> DerefMut for RefMut<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { self.value.as_mut() }
+    }
+}
+
+impl<T: ?Sized> Drop for RefMut<'_, T> {
+    fn drop(&mut self) {
+        self.borrow.store(0, Ordering::Release);
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for RefMut<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn borrows_are_released_on_drop() {
+        let cell = AtomicRefCell::new(vec![1, 2]);
+        {
+            let a = cell.borrow();
+            let b = Ref::map(cell.borrow(), |v| &v[1]);
+            assert_eq!((a[0], *b), (1, 2));
+        }
+        *RefMut::map(cell.borrow_mut(), |v| &mut v[0]) = 10;
+        assert_eq!(*cell.borrow(), vec![10, 2]);
+    }
+
+    #[test]
+    #[should_panic(expected = "already borrowed")]
+    fn mutable_borrow_conflicts_with_shared() {
+        let cell = AtomicRefCell::new(0);
+        let _shared = cell.borrow();
+        cell.borrow_mut();
+    }
+
+    #[test]
+    fn shared_borrows_from_many_threads() {
+        let cell = AtomicRefCell::new(5);
+        std::thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| {
+                    for _ in 0..1000 {
+                        assert_eq!(*cell.borrow(), 5);
+                    }
+                });
+            }
+        });
+        *cell.borrow_mut() += 1;
+        assert_eq!(*cell.borrow(), 6);
+    }
+}
@@ -0,0 +1,106 @@
+//! 命令缓冲
+//!
+//! 查询期间不能修改世界的结构, 创建和销毁先记录下来, 在同步点用
+//! [`CommandBuffer::apply`] 按记录的顺序执行. 创建的实体立即预留, 可以在应用之前使用.
+
+use crate::{Bundle, Component, Entity, World};
+
+type Command = Box<dyn FnOnce(&mut World) + Send>;
+
+#[derive(Default)]
+pub struct CommandBuffer {
+    commands: Vec<Command>,
+}
+
+impl CommandBuffer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.commands.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    /// 预留实体, 应用时再添加组件
+    pub fn spawn(&mut self, world: &World, bundle: impl Bundle) -> Entity {
+        let entity = world.reserve_entity();
+        self.push(move |world| world.insert_bundle(entity, bundle));
+        entity
+    }
+
+    /// 应用时实体已经不存在则忽略
+    pub fn despawn(&mut self, entity: Entity) {
+        self.push(move |world| {
+            world.despawn(entity);
+        });
+    }
+
+    /// 应用时实体已经不存在则忽略
+    pub fn insert<T: Component>(&mut self, entity: Entity, component: T) {
+        self.push(move |world| {
+            if world.is_alive(entity) {
+                world.insert(entity, component);
+            }
+        });
+    }
+
+    pub fn remove<T: Component>(&mut self, entity: Entity) {
+        self.push(move |world| {
+            world.remove::<T>(entity);
+        });
+    }
+
+    /// 记录任意修改
+    pub fn push(&mut self, command: impl FnOnce(&mut World) + Send + 'static) {
+        self.commands.push(Box::new(command));
+    }
+
+    /// 按顺序执行所有命令并清空缓冲
+    pub fn apply(&mut self, world: &mut World) {
+        world.flush();
+        for command in self.commands.drain(..) {
+            command(world);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Bullet(u32);
+    struct Dead;
+
+    #[test]
+    fn commands_run_at_the_sync_point() {
+        let mut world = World::new();
+        let shooter = world.spawn((Bullet(0),));
+        let mut commands = CommandBuffer::new();
+
+        world
+            .query::<(Entity, &Bullet)>()
+            .for_each(|(entity, bullet)| {
+                commands.spawn(&world, (Bullet(bullet.0 + 1),));
+                commands.insert(entity, Dead);
+            });
+        let spawned = commands.spawn(&world, (Bullet(7),));
+        assert!(!world.is_alive(spawned));
+        assert_eq!(world.query::<&Bullet>().count(), 1);
+
+        commands.apply(&mut world);
+        assert!(commands.is_empty());
+        assert_eq!(world.get::<Bullet>(spawned).unwrap().0, 7);
+        assert_eq!(world.query::<&Bullet>().count(), 3);
+        assert!(world.has::<Dead>(shooter));
+
+        commands.despawn(shooter);
+        commands.insert(shooter, Dead);
+        commands.apply(&mut world);
+        assert!(!world.is_alive(shooter));
+        assert_eq!(world.query::<&Dead>().count(), 0);
+    }
+}
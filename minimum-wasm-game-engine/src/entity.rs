@@ -0,0 +1,34 @@
+//! 实体
+
+use std::fmt;
+
+use pool::{Generation, Handle, RawHandle};
+
+/// 世代句柄表示的实体, 实体被销毁后旧的句柄不会指向复用同一索引的新实体
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Entity(pub(crate) Handle<()>);
+
+impl Entity {
+    pub fn index(&self) -> u32 {
+        self.0.index()
+    }
+
+    pub fn generation(&self) -> Generation {
+        self.0.generation()
+    }
+
+    /// 无类型的句柄, 方便交给脚本保存
+    pub fn raw(self) -> RawHandle {
+        self.0.raw()
+    }
+
+    pub fn from_raw(raw: RawHandle) -> Self {
+        Self(Handle::from_raw(raw))
+    }
+}
+
+impl fmt::Debug for Entity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Entity({}v{})", self.index(), self.generation())
+    }
+}
@@ -0,0 +1,116 @@
+//! 无窗口后端
+//!
+//! 以固定的帧时长执行若干帧, 输入由脚本在每帧开始前写入, 用于测试和服务器.
+
+use crate::{App, Backend, Input, WindowSize};
+
+type InputScript = Box<dyn FnMut(u64, &mut Input)>;
+
+pub struct Headless {
+    frames: u64,
+    delta: f32,
+    size: WindowSize,
+    script: Option<InputScript>,
+}
+
+impl Headless {
+    /// 执行 `frames` 帧, 每帧 1/60 秒
+    pub fn new(frames: u64) -> Self {
+        Self {
+            frames,
+            delta: 1.0 / 60.0,
+            size: WindowSize::default(),
+            script: None,
+        }
+    }
+
+    pub fn with_delta(mut self, delta: f32) -> Self {
+        self.delta = delta;
+        self
+    }
+
+    pub fn with_size(mut self, width: f32, height: f32) -> Self {
+        self.size = WindowSize {
+            width,
+            height,
+            scale_factor: 1.0,
+        };
+        self
+    }
+
+    /// 每帧开始前用帧号 (从 0 开始) 调用, 模拟按键和鼠标
+    pub fn with_input(mut self, script: impl FnMut(u64, &mut Input) + 'static) -> Self {
+        self.script = Some(Box::new(script));
+        self
+    }
+
+    /// 在借用的应用上执行, 执行完后还可以检查世界的状态
+    pub fn run_on(mut self, app: &mut App) {
+        app.world.insert_resource(self.size);
+        for frame in 0..self.frames {
+            if let Some(script) = &mut self.script {
+                script(frame, &mut app.world.resource_mut::<Input>());
+            }
+            app.update(self.delta);
+            if app.should_exit() {
+                break;
+            }
+        }
+    }
+}
+
+impl Backend for Headless {
+    fn run(self, mut app: App) {
+        self.run_on(&mut app);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AppExit, Key, Stage, Time, World};
+
+    struct Jumps(u32);
+
+    fn jump_plugin(app: &mut App) {
+        app.insert_resource(Jumps(0));
+        app.add_system(Stage::Update, "jump", |world: &World, _| {
+            if world.resource::<Input>().key_pressed(Key::Space) {
+                world.resource_mut::<Jumps>().0 += 1;
+            }
+        });
+    }
+
+    #[test]
+    fn scripted_input_drives_plugins() {
+        let mut app = App::new();
+        app.add_plugin(jump_plugin);
+        Headless::new(10)
+            .with_size(320.0, 180.0)
+            .with_input(|frame, input| match frame {
+                2 | 6 => input.press_key(Key::Space),
+                3 | 7 => input.release_key(Key::Space),
+                _ => {}
+            })
+            .run_on(&mut app);
+
+        assert_eq!(app.world.resource::<Jumps>().0, 2);
+        assert_eq!(app.world.resource::<Time>().frame(), 10);
+        assert!((app.world.resource::<Time>().elapsed() - 10.0 / 60.0).abs() < 1e-6);
+        assert_eq!(app.world.resource::<WindowSize>().width, 320.0);
+    }
+
+    #[test]
+    fn exit_stops_the_loop() {
+        let mut app = App::new();
+        app.add_system(Stage::PostUpdate, "quit", |world, commands| {
+            if world.resource::<Time>().frame() == 3 {
+                commands.push(|world| {
+                    world.insert_resource(AppExit);
+                });
+            }
+        });
+        Headless::new(100).run_on(&mut app);
+        assert_eq!(app.world.resource::<Time>().frame(), 3);
+    }
+}
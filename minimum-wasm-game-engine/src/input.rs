@@ -0,0 +1,214 @@
+//! 输入资源
+//!
+//! 后端在每帧开始前写入按键和鼠标的状态, 系统通过 `world.resource::<Input>()` 读取.
+//! “刚按下”和“刚松开”只在一帧内有效, 帧结束时清空.
+
+use std::collections::HashSet;
+
+/// 引擎使用的按键, 和具体后端无关
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Key {
+    A,
+    B,
+    C,
+    D,
+    E,
+    F,
+    G,
+    H,
+    I,
+    J,
+    K,
+    L,
+    M,
+    N,
+    O,
+    P,
+    Q,
+    R,
+    S,
+    T,
+    U,
+    V,
+    W,
+    X,
+    Y,
+    Z,
+    Key0,
+    Key1,
+    Key2,
+    Key3,
+    Key4,
+    Key5,
+    Key6,
+    Key7,
+    Key8,
+    Key9,
+    Left,
+    Right,
+    Up,
+    Down,
+    Space,
+    Enter,
+    Escape,
+    Tab,
+    Backspace,
+    LeftShift,
+    RightShift,
+    LeftControl,
+    RightControl,
+    LeftAlt,
+    RightAlt,
+    F1,
+    F2,
+    F3,
+    F4,
+    F5,
+    F6,
+    F7,
+    F8,
+    F9,
+    F10,
+    F11,
+    F12,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MouseButton {
+    Left,
+    Right,
+    Middle,
+}
+
+/// 当前帧的键盘和鼠标状态
+#[derive(Debug, Default, Clone)]
+pub struct Input {
+    keys: ButtonState<Key>,
+    mouse: ButtonState<MouseButton>,
+    /// 逻辑像素坐标, 原点在窗口左上角
+    mouse_position: (f32, f32),
+}
+
+#[derive(Debug, Clone)]
+struct ButtonState<T> {
+    down: HashSet<T>,
+    pressed: HashSet<T>,
+    released: HashSet<T>,
+}
+
+impl<T> Default for ButtonState<T> {
+    fn default() -> Self {
+        Self {
+            down: HashSet::new(),
+            pressed: HashSet::new(),
+            released: HashSet::new(),
+        }
+    }
+}
+
+impl<T: Copy + Eq + std::hash::Hash> ButtonState<T> {
+    fn press(&mut self, button: T) {
+        // 系统的按键重复不算新的按下
+        if self.down.insert(button) {
+            self.pressed.insert(button);
+        }
+    }
+
+    fn release(&mut self, button: T) {
+        if self.down.remove(&button) {
+            self.released.insert(button);
+        }
+    }
+
+    fn end_frame(&mut self) {
+        self.pressed.clear();
+        self.released.clear();
+    }
+}
+
+impl Input {
+    /// 是否按住
+    pub fn key_down(&self, key: Key) -> bool {
+        self.keys.down.contains(&key)
+    }
+
+    /// 是否在这一帧按下
+    pub fn key_pressed(&self, key: Key) -> bool {
+        self.keys.pressed.contains(&key)
+    }
+
+    /// 是否在这一帧松开
+    pub fn key_released(&self, key: Key) -> bool {
+        self.keys.released.contains(&key)
+    }
+
+    pub fn mouse_down(&self, button: MouseButton) -> bool {
+        self.mouse.down.contains(&button)
+    }
+
+    pub fn mouse_pressed(&self, button: MouseButton) -> bool {
+        self.mouse.pressed.contains(&button)
+    }
+
+    pub fn mouse_released(&self, button: MouseButton) -> bool {
+        self.mouse.released.contains(&button)
+    }
+
+    pub fn mouse_position(&self) -> (f32, f32) {
+        self.mouse_position
+    }
+
+    // 以下由后端调用
+
+    pub fn press_key(&mut self, key: Key) {
+        self.keys.press(key);
+    }
+
+    pub fn release_key(&mut self, key: Key) {
+        self.keys.release(key);
+    }
+
+    pub fn press_mouse(&mut self, button: MouseButton) {
+        self.mouse.press(button);
+    }
+
+    pub fn release_mouse(&mut self, button: MouseButton) {
+        self.mouse.release(button);
+    }
+
+    pub fn set_mouse_position(&mut self, position: (f32, f32)) {
+        self.mouse_position = position;
+    }
+
+    /// 清空只在一帧内有效的状态, 由 [`App::update`](crate::App::update) 在帧结束时调用
+    pub fn end_frame(&mut self) {
+        self.keys.end_frame();
+        self.mouse.end_frame();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pressed_and_released_last_one_frame() {
+        let mut input = Input::default();
+        input.press_key(Key::Space);
+        assert!(input.key_down(Key::Space) && input.key_pressed(Key::Space));
+        input.end_frame();
+
+        // 按键重复
+        input.press_key(Key::Space);
+        assert!(input.key_down(Key::Space) && !input.key_pressed(Key::Space));
+        input.release_key(Key::Space);
+        assert!(!input.key_down(Key::Space) && input.key_released(Key::Space));
+        input.end_frame();
+        assert!(!input.key_released(Key::Space));
+
+        input.press_mouse(MouseButton::Left);
+        input.release_mouse(MouseButton::Left);
+        assert!(input.mouse_pressed(MouseButton::Left) && input.mouse_released(MouseButton::Left));
+        assert!(!input.mouse_down(MouseButton::Left));
+    }
+}
@@ -1,14 +1,46 @@
-pub fn add(left: u64, right: u64) -> u64 {
-    left + right
-}
+//! 最小的 wasm 游戏引擎
+//!
+//! 目前提供 ECS: 实体是 [`pool`] 中的世代句柄, 组件按类型存放在稀疏集中,
+//! 通过 [`World::query`] 查询, 用 [`With`] 和 [`Without`] 过滤.
+//! 全局数据作为资源按类型存放在 [`World`] 中, 查询期间的创建和销毁交给 [`CommandBuffer`].
+//! 组件和资源的借用在运行时检查, 冲突时 panic.
+//!
+//! 系统注册到 [`Schedule`] 的各个阶段中执行, 原生平台上不冲突的系统可以并行.
+//!
+//! [`App`] 用插件组织系统, 由后端驱动主循环: 无窗口的 [`Headless`] 总是可用,
+//! `macroquad` 和 `winit` 特性分别开启对应的后端. 所有后端都提供
+//! [`Input`]、[`Time`] 和 [`WindowSize`] 资源.
+//!
+//! [`math`] 提供向量、矩阵、2D 变换、形状相交测试、随机数和缓动曲线.
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+mod app;
+mod cell;
+mod command;
+mod entity;
+mod headless;
+mod input;
+#[cfg(feature = "macroquad")]
+mod macroquad_backend;
+pub mod math;
+mod query;
+mod schedule;
+mod storage;
+mod time;
+#[cfg(all(feature = "winit", not(target_arch = "wasm32")))]
+mod winit_backend;
+mod world;
 
-    #[test]
-    fn it_works() {
-        let result = add(2, 2);
-        assert_eq!(result, 4);
-    }
-}
+pub use app::{App, AppExit, Backend, Plugin};
+pub use cell::{Ref, RefMut};
+pub use command::CommandBuffer;
+pub use entity::Entity;
+pub use headless::Headless;
+pub use input::{Input, Key, MouseButton};
+#[cfg(feature = "macroquad")]
+pub use macroquad_backend::Macroquad;
+pub use query::{Query, QueryData, QueryFilter, With, Without};
+pub use schedule::{Executor, FixedTime, Schedule, Stage, SystemConfig, in_state, resource_exists};
+pub use time::{Time, WindowSize};
+#[cfg(all(feature = "winit", not(target_arch = "wasm32")))]
+pub use winit_backend::{Winit, WinitWindow};
+pub use world::{Bundle, Component, Resource, World};
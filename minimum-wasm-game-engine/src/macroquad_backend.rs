@@ -0,0 +1,150 @@
+//! macroquad 后端
+//!
+//! 在 macroquad 的窗口中运行应用, `Render` 阶段的系统可以直接调用 macroquad 的绘图函数.
+
+use macroquad::input::{self as mq, KeyCode};
+use macroquad::window::{self, Conf};
+
+use crate::{App, Backend, Input, Key, MouseButton, WindowSize};
+
+pub struct Macroquad {
+    conf: Conf,
+}
+
+impl Macroquad {
+    pub fn new(title: &str) -> Self {
+        Self::with_conf(Conf {
+            window_title: title.to_owned(),
+            ..Default::default()
+        })
+    }
+
+    pub fn with_conf(conf: Conf) -> Self {
+        Self { conf }
+    }
+}
+
+impl Backend for Macroquad {
+    fn run(self, mut app: App) {
+        macroquad::Window::from_config(self.conf, async move {
+            loop {
+                poll(&mut app);
+                app.update(macroquad::time::get_frame_time());
+                if app.should_exit() {
+                    break;
+                }
+                window::next_frame().await;
+            }
+        });
+    }
+}
+
+/// 把 macroquad 这一帧的输入和窗口大小写进资源
+fn poll(app: &mut App) {
+    let mut input = app.world.resource_mut::<Input>();
+    for key in mq::get_keys_pressed().into_iter().filter_map(key) {
+        input.press_key(key);
+    }
+    for key in mq::get_keys_released().into_iter().filter_map(key) {
+        input.release_key(key);
+    }
+    for (theirs, ours) in [
+        (mq::MouseButton::Left, MouseButton::Left),
+        (mq::MouseButton::Right, MouseButton::Right),
+        (mq::MouseButton::Middle, MouseButton::Middle),
+    ] {
+        if mq::is_mouse_button_pressed(theirs) {
+            input.press_mouse(ours);
+        }
+        if mq::is_mouse_button_released(theirs) {
+            input.release_mouse(ours);
+        }
+    }
+    input.set_mouse_position(mq::mouse_position());
+    drop(input);
+
+    *app.world.resource_mut::<WindowSize>() = WindowSize {
+        width: window::screen_width(),
+        height: window::screen_height(),
+        scale_factor: window::screen_dpi_scale(),
+    };
+}
+
+/// 两边的按键名字相同
+macro_rules! same_keys {
+    ($code:expr, $($name:ident),*) => {
+        match $code {
+            $(KeyCode::$name => Some(Key::$name),)*
+            _ => None,
+        }
+    };
+}
+
+fn key(code: KeyCode) -> Option<Key> {
+    same_keys!(
+        code,
+        A,
+        B,
+        C,
+        D,
+        E,
+        F,
+        G,
+        H,
+        I,
+        J,
+        K,
+        L,
+        M,
+        N,
+        O,
+        P,
+        Q,
+        R,
+        S,
+        T,
+        U,
+        V,
+        W,
+        X,
+        Y,
+        Z,
+        Key0,
+        Key1,
+        Key2,
+        Key3,
+        Key4,
+        Key5,
+        Key6,
+        Key7,
+        Key8,
+        Key9,
+        Left,
+        Right,
+        Up,
+        Down,
+        Space,
+        Enter,
+        Escape,
+        Tab,
+        Backspace,
+        LeftShift,
+        RightShift,
+        LeftControl,
+        RightControl,
+        LeftAlt,
+        RightAlt,
+        F1,
+        F2,
+        F3,
+        F4,
+        F5,
+        F6,
+        F7,
+        F8,
+        F9,
+        F10,
+        F11,
+        F12
+    )
+}
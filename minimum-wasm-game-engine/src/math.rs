@@ -0,0 +1,83 @@
+//! 数学库
+//!
+//! 向量、矩阵、四元数、2D 变换、矩形和圆的相交测试、可复现的随机数和缓动曲线.
+//! 只依赖 `core`, 三角函数和开方通过内部的 `float` 模块调用, 开启 `libm` 特性后不需要 std,
+//! 整个模块可以直接用在 `no_std` 环境中.
+//!
+//! 矩阵按列存储, 投影矩阵使用 OpenGL 的约定 (右手坐标系, 裁剪空间 z 在 -1 到 1 之间),
+//! 和 macroquad 一致.
+
+mod ease;
+mod matrix;
+mod quat;
+mod random;
+mod shape;
+mod transform;
+mod vector;
+
+pub use ease::{Ease, Lerp, Tween};
+pub use matrix::{Mat3, Mat4};
+pub use quat::Quat;
+pub use random::Rng;
+pub use shape::{Circle, Rect};
+pub use transform::Transform;
+pub use vector::{Vec2, Vec3, Vec4};
+
+/// 需要 std 或 libm 的浮点函数
+pub(crate) mod float {
+    macro_rules! float_fns {
+        ($($name:ident => $libm:ident($($arg:ident),*);)*) => {$(
+            #[cfg(feature = "libm")]
+            #[inline]
+            pub(crate) fn $name(x: f32 $(, $arg: f32)*) -> f32 {
+                libm::$libm(x $(, $arg)*)
+            }
+
+            #[cfg(not(feature = "libm"))]
+            #[inline]
+            pub(crate) fn $name(x: f32 $(, $arg: f32)*) -> f32 {
+                x.$name($($arg),*)
+            }
+        )*};
+    }
+
+    float_fns! {
+        sqrt => sqrtf();
+        sin => sinf();
+        cos => cosf();
+        tan => tanf();
+        acos => acosf();
+        atan2 => atan2f(other);
+        powf => powf(n);
+    }
+}
+
+/// 测试中比较浮点数
+#[cfg(test)]
+macro_rules! assert_near {
+    ($a:expr, $b:expr) => {
+        assert_near!($a, $b, 1e-5)
+    };
+    ($a:expr, $b:expr, $eps:expr) => {{
+        let (a, b) = ($a, $b);
+        assert!(
+            crate::math::Near::near(&a, &b, $eps),
+            "{a:?} is not near {b:?}"
+        );
+    }};
+}
+#[cfg(test)]
+pub(crate) use assert_near;
+
+/// 近似相等, 只在测试中使用
+#[cfg(test)]
+pub(crate) trait Near {
+    fn near(&self, other: &Self, eps: f32) -> bool;
+}
+
+#[cfg(test)]
+impl Near for f32 {
+    fn near(&self, other: &Self, eps: f32) -> bool {
+        (self - other).abs() <= eps
+    }
+}
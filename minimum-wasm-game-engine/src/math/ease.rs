@@ -0,0 +1,327 @@
+//! 缓动曲线和补间动画
+//!
+//! 曲线的定义和 <https://easings.net> 相同.
+
+use core::f32::consts::{PI, TAU};
+
+use super::{Quat, Vec2, Vec3, Vec4, float};
+
+/// 缓动曲线, 把 0 到 1 的进度映射为插值系数
+///
+/// `Back` 和 `Elastic` 会超出 0 到 1 的范围.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Ease {
+    #[default]
+    Linear,
+    QuadIn,
+    QuadOut,
+    QuadInOut,
+    CubicIn,
+    CubicOut,
+    CubicInOut,
+    QuartIn,
+    QuartOut,
+    QuartInOut,
+    QuintIn,
+    QuintOut,
+    QuintInOut,
+    SineIn,
+    SineOut,
+    SineInOut,
+    ExpoIn,
+    ExpoOut,
+    ExpoInOut,
+    CircIn,
+    CircOut,
+    CircInOut,
+    BackIn,
+    BackOut,
+    BackInOut,
+    ElasticIn,
+    ElasticOut,
+    ElasticInOut,
+    BounceIn,
+    BounceOut,
+    BounceInOut,
+}
+
+const BACK: f32 = 1.70158;
+const BACK_IN_OUT: f32 = BACK * 1.525;
+const ELASTIC: f32 = TAU / 3.0;
+const ELASTIC_IN_OUT: f32 = TAU / 4.5;
+
+impl Ease {
+    pub const ALL: [Self; 31] = {
+        use Ease::*;
+        [
+            Linear,
+            QuadIn,
+            QuadOut,
+            QuadInOut,
+            CubicIn,
+            CubicOut,
+            CubicInOut,
+            QuartIn,
+            QuartOut,
+            QuartInOut,
+            QuintIn,
+            QuintOut,
+            QuintInOut,
+            SineIn,
+            SineOut,
+            SineInOut,
+            ExpoIn,
+            ExpoOut,
+            ExpoInOut,
+            CircIn,
+            CircOut,
+            CircInOut,
+            BackIn,
+            BackOut,
+            BackInOut,
+            ElasticIn,
+            ElasticOut,
+            ElasticInOut,
+            BounceIn,
+            BounceOut,
+            BounceInOut,
+        ]
+    };
+
+    /// `t` 会被限制在 0 到 1 之间, 两端总是返回 0 和 1
+    pub fn apply(self, t: f32) -> f32 {
+        use Ease::*;
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Linear => t,
+            QuadIn => pow(t, 2),
+            QuadOut => 1.0 - pow(1.0 - t, 2),
+            QuadInOut => in_out(t, 2),
+            CubicIn => pow(t, 3),
+            CubicOut => 1.0 - pow(1.0 - t, 3),
+            CubicInOut => in_out(t, 3),
+            QuartIn => pow(t, 4),
+            QuartOut => 1.0 - pow(1.0 - t, 4),
+            QuartInOut => in_out(t, 4),
+            QuintIn => pow(t, 5),
+            QuintOut => 1.0 - pow(1.0 - t, 5),
+            QuintInOut => in_out(t, 5),
+            SineIn => 1.0 - float::cos(t * PI / 2.0),
+            SineOut => float::sin(t * PI / 2.0),
+            SineInOut => (1.0 - float::cos(t * PI)) / 2.0,
+            _ if t == 0.0 || t == 1.0 => t,
+            ExpoIn => exp2(10.0 * t - 10.0),
+            ExpoOut => 1.0 - exp2(-10.0 * t),
+            ExpoInOut if t < 0.5 => exp2(20.0 * t - 10.0) / 2.0,
+            ExpoInOut => (2.0 - exp2(-20.0 * t + 10.0)) / 2.0,
+            CircIn => 1.0 - float::sqrt(1.0 - t * t),
+            CircOut => float::sqrt(1.0 - pow(t - 1.0, 2)),
+            CircInOut if t < 0.5 => (1.0 - float::sqrt(1.0 - pow(2.0 * t, 2))) / 2.0,
+            CircInOut => (float::sqrt(1.0 - pow(2.0 - 2.0 * t, 2)) + 1.0) / 2.0,
+            BackIn => (BACK + 1.0) * pow(t, 3) - BACK * t * t,
+            BackOut => 1.0 + (BACK + 1.0) * pow(t - 1.0, 3) + BACK * pow(t - 1.0, 2),
+            BackInOut if t < 0.5 => {
+                pow(2.0 * t, 2) * ((BACK_IN_OUT + 1.0) * 2.0 * t - BACK_IN_OUT) / 2.0
+            }
+            BackInOut => {
+                (pow(2.0 * t - 2.0, 2) * ((BACK_IN_OUT + 1.0) * (2.0 * t - 2.0) + BACK_IN_OUT)
+                    + 2.0)
+                    / 2.0
+            }
+            ElasticIn => -exp2(10.0 * t - 10.0) * float::sin((10.0 * t - 10.75) * ELASTIC),
+            ElasticOut => exp2(-10.0 * t) * float::sin((10.0 * t - 0.75) * ELASTIC) + 1.0,
+            ElasticInOut if t < 0.5 => {
+                -(exp2(20.0 * t - 10.0) * float::sin((20.0 * t - 11.125) * ELASTIC_IN_OUT)) / 2.0
+            }
+            ElasticInOut => {
+                exp2(-20.0 * t + 10.0) * float::sin((20.0 * t - 11.125) * ELASTIC_IN_OUT) / 2.0
+                    + 1.0
+            }
+            BounceIn => 1.0 - bounce_out(1.0 - t),
+            BounceOut => bounce_out(t),
+            BounceInOut if t < 0.5 => (1.0 - bounce_out(1.0 - 2.0 * t)) / 2.0,
+            BounceInOut => (1.0 + bounce_out(2.0 * t - 1.0)) / 2.0,
+        }
+    }
+}
+
+/// `f32::powi` 需要 std
+fn pow(x: f32, n: u32) -> f32 {
+    (0..n).fold(1.0, |acc, _| acc * x)
+}
+
+fn exp2(x: f32) -> f32 {
+    float::powf(2.0, x)
+}
+
+/// 前半段是 `t^n`, 后半段和它中心对称
+fn in_out(t: f32, n: u32) -> f32 {
+    if t < 0.5 {
+        pow(2.0, n - 1) * pow(t, n)
+    } else {
+        1.0 - pow(2.0 - 2.0 * t, n) / 2.0
+    }
+}
+
+fn bounce_out(t: f32) -> f32 {
+    const N: f32 = 7.5625;
+    const D: f32 = 2.75;
+    if t < 1.0 / D {
+        N * t * t
+    } else if t < 2.0 / D {
+        let t = t - 1.5 / D;
+        N * t * t + 0.75
+    } else if t < 2.5 / D {
+        let t = t - 2.25 / D;
+        N * t * t + 0.9375
+    } else {
+        let t = t - 2.625 / D;
+        N * t * t + 0.984375
+    }
+}
+
+/// 可以插值的类型
+pub trait Lerp: Copy {
+    /// `t` 为 0 时是 `self`, 为 1 时是 `other`, 超出范围时外插
+    fn lerp(self, other: Self, t: f32) -> Self;
+}
+
+impl Lerp for f32 {
+    fn lerp(self, other: Self, t: f32) -> Self {
+        self + (other - self) * t
+    }
+}
+
+macro_rules! impl_lerp {
+    ($($ty:ty),*) => {$(
+        impl Lerp for $ty {
+            fn lerp(self, other: Self, t: f32) -> Self {
+                <$ty>::lerp(self, other, t)
+            }
+        }
+    )*};
+}
+
+impl_lerp!(Vec2, Vec3, Vec4);
+
+/// 四元数用球面插值
+impl Lerp for Quat {
+    fn lerp(self, other: Self, t: f32) -> Self {
+        self.slerp(other, t)
+    }
+}
+
+/// 在一段时间内按缓动曲线从 `from` 变化到 `to`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tween<T> {
+    pub from: T,
+    pub to: T,
+    /// 秒
+    pub duration: f32,
+    pub ease: Ease,
+    elapsed: f32,
+}
+
+impl<T: Lerp> Tween<T> {
+    pub fn new(from: T, to: T, duration: f32, ease: Ease) -> Self {
+        Self {
+            from,
+            to,
+            duration,
+            ease,
+            elapsed: 0.0,
+        }
+    }
+
+    /// 前进 `delta` 秒, 返回新的值
+    pub fn update(&mut self, delta: f32) -> T {
+        self.elapsed = (self.elapsed + delta).min(self.duration.max(0.0));
+        self.value()
+    }
+
+    pub fn value(&self) -> T {
+        self.from.lerp(self.to, self.ease.apply(self.progress()))
+    }
+
+    /// 时间上的进度, 0 到 1 之间
+    pub fn progress(&self) -> f32 {
+        if self.duration > 0.0 {
+            self.elapsed / self.duration
+        } else {
+            1.0
+        }
+    }
+
+    pub fn finished(&self) -> bool {
+        self.progress() >= 1.0
+    }
+
+    pub fn reset(&mut self) {
+        self.elapsed = 0.0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::assert_near;
+
+    #[test]
+    fn curves_match_reference_values() {
+        let cases = [
+            (Ease::Linear, 0.5),
+            (Ease::QuadIn, 0.25),
+            (Ease::QuadInOut, 0.5),
+            (Ease::CubicOut, 0.875),
+            (Ease::QuartIn, 0.0625),
+            (Ease::QuintOut, 0.96875),
+            (Ease::SineInOut, 0.5),
+            (Ease::SineIn, 0.29289323),
+            (Ease::ExpoIn, 0.03125),
+            (Ease::ExpoInOut, 0.5),
+            (Ease::CircOut, 0.8660254),
+            (Ease::BackIn, -0.0876975),
+            (Ease::BackOut, 1.0876975),
+            (Ease::ElasticOut, 1.015625),
+            (Ease::ElasticIn, -0.015625),
+            (Ease::BounceOut, 0.765625),
+            (Ease::BounceIn, 0.234375),
+        ];
+        for (ease, expected) in cases {
+            assert_near!(ease.apply(0.5), expected);
+        }
+        assert_near!(Ease::CubicInOut.apply(0.25), 0.0625);
+        assert_near!(Ease::BackInOut.apply(0.25), -0.0996818);
+        assert_near!(Ease::BounceInOut.apply(0.75), 0.8828125);
+    }
+
+    #[test]
+    fn curves_start_at_zero_and_end_at_one() {
+        for ease in Ease::ALL {
+            assert_eq!(ease.apply(0.0), 0.0, "{ease:?}");
+            assert_near!(ease.apply(1.0), 1.0);
+            assert_eq!(ease.apply(-1.0), ease.apply(0.0), "{ease:?}");
+            assert_eq!(ease.apply(2.0), ease.apply(1.0), "{ease:?}");
+            // 对称的曲线在中点是 0.5
+            if format!("{ease:?}").ends_with("InOut") {
+                assert_near!(ease.apply(0.5), 0.5);
+            }
+        }
+    }
+
+    #[test]
+    fn tween_runs_for_its_duration() {
+        let mut tween = Tween::new(Vec2::ZERO, Vec2::new(10.0, 20.0), 2.0, Ease::QuadIn);
+        assert_eq!(tween.value(), Vec2::ZERO);
+        assert_near!(tween.update(1.0), Vec2::new(2.5, 5.0));
+        assert!(!tween.finished());
+        assert_eq!(tween.update(5.0), Vec2::new(10.0, 20.0));
+        assert!(tween.finished());
+        tween.reset();
+        assert_eq!(tween.progress(), 0.0);
+
+        let instant = Tween::new(1.0, 3.0, 0.0, Ease::Linear);
+        assert!(instant.finished());
+        assert_eq!(instant.value(), 3.0);
+    }
+}
@@ -0,0 +1,472 @@
+//! 按列存储的矩阵
+
+use core::ops::Mul;
+
+use super::{Quat, Vec2, Vec3, Vec4, float};
+
+/// 3x3 矩阵, 用作 2D 的齐次变换
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Mat3 {
+    pub x_axis: Vec3,
+    pub y_axis: Vec3,
+    pub z_axis: Vec3,
+}
+
+impl Default for Mat3 {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Mat3 {
+    pub const IDENTITY: Self = Self::from_cols(Vec3::X, Vec3::Y, Vec3::Z);
+
+    pub const fn from_cols(x_axis: Vec3, y_axis: Vec3, z_axis: Vec3) -> Self {
+        Self {
+            x_axis,
+            y_axis,
+            z_axis,
+        }
+    }
+
+    pub fn from_translation(translation: Vec2) -> Self {
+        Self::from_cols(Vec3::X, Vec3::Y, translation.extend(1.0))
+    }
+
+    /// 逆时针旋转 `angle` 弧度 (y 轴向下时看起来是顺时针)
+    pub fn from_angle(angle: f32) -> Self {
+        let (sin, cos) = (float::sin(angle), float::cos(angle));
+        Self::from_cols(Vec3::new(cos, sin, 0.0), Vec3::new(-sin, cos, 0.0), Vec3::Z)
+    }
+
+    pub fn from_scale(scale: Vec2) -> Self {
+        Self::from_cols(
+            Vec3::new(scale.x, 0.0, 0.0),
+            Vec3::new(0.0, scale.y, 0.0),
+            Vec3::Z,
+        )
+    }
+
+    /// 依次缩放、旋转、平移
+    pub fn from_scale_angle_translation(scale: Vec2, angle: f32, translation: Vec2) -> Self {
+        let (sin, cos) = (float::sin(angle), float::cos(angle));
+        Self::from_cols(
+            Vec3::new(cos * scale.x, sin * scale.x, 0.0),
+            Vec3::new(-sin * scale.y, cos * scale.y, 0.0),
+            translation.extend(1.0),
+        )
+    }
+
+    pub const fn to_cols_array(&self) -> [f32; 9] {
+        let (x, y, z) = (self.x_axis, self.y_axis, self.z_axis);
+        [x.x, x.y, x.z, y.x, y.y, y.z, z.x, z.y, z.z]
+    }
+
+    /// 变换点, 包括平移
+    pub fn transform_point(&self, point: Vec2) -> Vec2 {
+        (*self * point.extend(1.0)).truncate()
+    }
+
+    /// 变换方向, 不包括平移
+    pub fn transform_vector(&self, vector: Vec2) -> Vec2 {
+        (*self * vector.extend(0.0)).truncate()
+    }
+
+    pub fn transpose(&self) -> Self {
+        let (x, y, z) = (self.x_axis, self.y_axis, self.z_axis);
+        Self::from_cols(
+            Vec3::new(x.x, y.x, z.x),
+            Vec3::new(x.y, y.y, z.y),
+            Vec3::new(x.z, y.z, z.z),
+        )
+    }
+
+    pub fn determinant(&self) -> f32 {
+        self.z_axis.dot(self.x_axis.cross(self.y_axis))
+    }
+
+    /// 行列式为 0 时不可逆
+    pub fn inverse(&self) -> Option<Self> {
+        let (x, y, z) = (self.x_axis, self.y_axis, self.z_axis);
+        let rows = Self::from_cols(y.cross(z), z.cross(x), x.cross(y));
+        let determinant = z.dot(rows.z_axis);
+        if determinant == 0.0 || !determinant.is_finite() {
+            return None;
+        }
+        let inverse = rows.transpose();
+        let scale = 1.0 / determinant;
+        Some(Self::from_cols(
+            inverse.x_axis * scale,
+            inverse.y_axis * scale,
+            inverse.z_axis * scale,
+        ))
+    }
+}
+
+impl Mul<Vec3> for Mat3 {
+    type Output = Vec3;
+
+    fn mul(self, v: Vec3) -> Vec3 {
+        self.x_axis * v.x + self.y_axis * v.y + self.z_axis * v.z
+    }
+}
+
+/// `a * b` 先应用 `b` 再应用 `a`
+impl Mul for Mat3 {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        Self::from_cols(
+            self * other.x_axis,
+            self * other.y_axis,
+            self * other.z_axis,
+        )
+    }
+}
+
+/// 4x4 矩阵, 用作 3D 变换和投影
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Mat4 {
+    pub x_axis: Vec4,
+    pub y_axis: Vec4,
+    pub z_axis: Vec4,
+    pub w_axis: Vec4,
+}
+
+impl Default for Mat4 {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Mat4 {
+    pub const IDENTITY: Self = Self::from_cols(
+        Vec4::new(1.0, 0.0, 0.0, 0.0),
+        Vec4::new(0.0, 1.0, 0.0, 0.0),
+        Vec4::new(0.0, 0.0, 1.0, 0.0),
+        Vec4::new(0.0, 0.0, 0.0, 1.0),
+    );
+
+    pub const fn from_cols(x_axis: Vec4, y_axis: Vec4, z_axis: Vec4, w_axis: Vec4) -> Self {
+        Self {
+            x_axis,
+            y_axis,
+            z_axis,
+            w_axis,
+        }
+    }
+
+    pub const fn from_cols_array_2d(cols: [[f32; 4]; 4]) -> Self {
+        Self::from_cols(
+            Vec4::from_array(cols[0]),
+            Vec4::from_array(cols[1]),
+            Vec4::from_array(cols[2]),
+            Vec4::from_array(cols[3]),
+        )
+    }
+
+    pub const fn to_cols_array_2d(&self) -> [[f32; 4]; 4] {
+        [
+            self.x_axis.to_array(),
+            self.y_axis.to_array(),
+            self.z_axis.to_array(),
+            self.w_axis.to_array(),
+        ]
+    }
+
+    /// 按列展开, 可以直接作为 uniform 上传
+    pub const fn to_cols_array(&self) -> [f32; 16] {
+        let [x, y, z, w] = self.to_cols_array_2d();
+        [
+            x[0], x[1], x[2], x[3], y[0], y[1], y[2], y[3], z[0], z[1], z[2], z[3], w[0], w[1],
+            w[2], w[3],
+        ]
+    }
+
+    pub fn from_translation(translation: Vec3) -> Self {
+        Self {
+            w_axis: translation.extend(1.0),
+            ..Self::IDENTITY
+        }
+    }
+
+    pub fn from_scale(scale: Vec3) -> Self {
+        Self::from_cols(
+            Vec4::new(scale.x, 0.0, 0.0, 0.0),
+            Vec4::new(0.0, scale.y, 0.0, 0.0),
+            Vec4::new(0.0, 0.0, scale.z, 0.0),
+            Vec4::new(0.0, 0.0, 0.0, 1.0),
+        )
+    }
+
+    /// 单位四元数表示的旋转
+    pub fn from_quat(rotation: Quat) -> Self {
+        let Quat { x, y, z, w } = rotation;
+        let (x2, y2, z2) = (x + x, y + y, z + z);
+        let (xx, xy, xz) = (x * x2, x * y2, x * z2);
+        let (yy, yz, zz) = (y * y2, y * z2, z * z2);
+        let (wx, wy, wz) = (w * x2, w * y2, w * z2);
+        Self::from_cols(
+            Vec4::new(1.0 - (yy + zz), xy + wz, xz - wy, 0.0),
+            Vec4::new(xy - wz, 1.0 - (xx + zz), yz + wx, 0.0),
+            Vec4::new(xz + wy, yz - wx, 1.0 - (xx + yy), 0.0),
+            Vec4::new(0.0, 0.0, 0.0, 1.0),
+        )
+    }
+
+    /// 依次缩放、旋转、平移
+    pub fn from_scale_rotation_translation(scale: Vec3, rotation: Quat, translation: Vec3) -> Self {
+        let rotation = Self::from_quat(rotation);
+        Self::from_cols(
+            rotation.x_axis * scale.x,
+            rotation.y_axis * scale.y,
+            rotation.z_axis * scale.z,
+            translation.extend(1.0),
+        )
+    }
+
+    /// 正交投影, 把盒子映射到 -1 到 1 的裁剪空间
+    pub fn orthographic(left: f32, right: f32, bottom: f32, top: f32, near: f32, far: f32) -> Self {
+        let (width, height, depth) = (right - left, top - bottom, far - near);
+        Self::from_cols(
+            Vec4::new(2.0 / width, 0.0, 0.0, 0.0),
+            Vec4::new(0.0, 2.0 / height, 0.0, 0.0),
+            Vec4::new(0.0, 0.0, -2.0 / depth, 0.0),
+            Vec4::new(
+                -(right + left) / width,
+                -(top + bottom) / height,
+                -(far + near) / depth,
+                1.0,
+            ),
+        )
+    }
+
+    /// 透视投影, `fov_y` 是垂直视角的弧度, 相机看向 -z
+    pub fn perspective(fov_y: f32, aspect: f32, near: f32, far: f32) -> Self {
+        let f = 1.0 / float::tan(fov_y * 0.5);
+        let depth = near - far;
+        Self::from_cols(
+            Vec4::new(f / aspect, 0.0, 0.0, 0.0),
+            Vec4::new(0.0, f, 0.0, 0.0),
+            Vec4::new(0.0, 0.0, (far + near) / depth, -1.0),
+            Vec4::new(0.0, 0.0, 2.0 * far * near / depth, 0.0),
+        )
+    }
+
+    /// 位于 `eye` 看向 `target` 的相机的视图矩阵
+    pub fn look_at(eye: Vec3, target: Vec3, up: Vec3) -> Self {
+        let f = (target - eye).normalize_or_zero();
+        let s = f.cross(up).normalize_or_zero();
+        let u = s.cross(f);
+        Self::from_cols(
+            Vec4::new(s.x, u.x, -f.x, 0.0),
+            Vec4::new(s.y, u.y, -f.y, 0.0),
+            Vec4::new(s.z, u.z, -f.z, 0.0),
+            Vec4::new(-s.dot(eye), -u.dot(eye), f.dot(eye), 1.0),
+        )
+    }
+
+    /// 变换点并做透视除法
+    pub fn project_point(&self, point: Vec3) -> Vec3 {
+        let clip = *self * point.extend(1.0);
+        clip.truncate() / clip.w
+    }
+
+    /// 变换点, 假定是仿射变换
+    pub fn transform_point(&self, point: Vec3) -> Vec3 {
+        (*self * point.extend(1.0)).truncate()
+    }
+
+    /// 变换方向, 不包括平移
+    pub fn transform_vector(&self, vector: Vec3) -> Vec3 {
+        (*self * vector.extend(0.0)).truncate()
+    }
+
+    pub fn transpose(&self) -> Self {
+        let m = self.to_cols_array_2d();
+        Self::from_cols_array_2d(core::array::from_fn(|i| core::array::from_fn(|j| m[j][i])))
+    }
+
+    pub fn determinant(&self) -> f32 {
+        let (s, c) = self.minors();
+        s[0] * c[5] - s[1] * c[4] + s[2] * c[3] + s[3] * c[2] - s[4] * c[1] + s[5] * c[0]
+    }
+
+    /// 行列式为 0 时不可逆
+    pub fn inverse(&self) -> Option<Self> {
+        let a = self.to_cols_array_2d();
+        let (s, c) = self.minors();
+        let determinant =
+            s[0] * c[5] - s[1] * c[4] + s[2] * c[3] + s[3] * c[2] - s[4] * c[1] + s[5] * c[0];
+        if determinant == 0.0 || !determinant.is_finite() {
+            return None;
+        }
+        let inverse = [
+            [
+                a[1][1] * c[5] - a[1][2] * c[4] + a[1][3] * c[3],
+                -a[0][1] * c[5] + a[0][2] * c[4] - a[0][3] * c[3],
+                a[3][1] * s[5] - a[3][2] * s[4] + a[3][3] * s[3],
+                -a[2][1] * s[5] + a[2][2] * s[4] - a[2][3] * s[3],
+            ],
+            [
+                -a[1][0] * c[5] + a[1][2] * c[2] - a[1][3] * c[1],
+                a[0][0] * c[5] - a[0][2] * c[2] + a[0][3] * c[1],
+                -a[3][0] * s[5] + a[3][2] * s[2] - a[3][3] * s[1],
+                a[2][0] * s[5] - a[2][2] * s[2] + a[2][3] * s[1],
+            ],
+            [
+                a[1][0] * c[4] - a[1][1] * c[2] + a[1][3] * c[0],
+                -a[0][0] * c[4] + a[0][1] * c[2] - a[0][3] * c[0],
+                a[3][0] * s[4] - a[3][1] * s[2] + a[3][3] * s[0],
+                -a[2][0] * s[4] + a[2][1] * s[2] - a[2][3] * s[0],
+            ],
+            [
+                -a[1][0] * c[3] + a[1][1] * c[1] - a[1][2] * c[0],
+                a[0][0] * c[3] - a[0][1] * c[1] + a[0][2] * c[0],
+                -a[3][0] * s[3] + a[3][1] * s[1] - a[3][2] * s[0],
+                a[2][0] * s[3] - a[2][1] * s[1] + a[2][2] * s[0],
+            ],
+        ];
+        let scale = 1.0 / determinant;
+        Some(Self::from_cols_array_2d(
+            inverse.map(|col| col.map(|v| v * scale)),
+        ))
+    }
+
+    /// 拉普拉斯展开用到的前两列和后两列的 2x2 子式
+    fn minors(&self) -> ([f32; 6], [f32; 6]) {
+        let a = self.to_cols_array_2d();
+        let s = [
+            a[0][0] * a[1][1] - a[1][0] * a[0][1],
+            a[0][0] * a[1][2] - a[1][0] * a[0][2],
+            a[0][0] * a[1][3] - a[1][0] * a[0][3],
+            a[0][1] * a[1][2] - a[1][1] * a[0][2],
+            a[0][1] * a[1][3] - a[1][1] * a[0][3],
+            a[0][2] * a[1][3] - a[1][2] * a[0][3],
+        ];
+        let c = [
+            a[2][0] * a[3][1] - a[3][0] * a[2][1],
+            a[2][0] * a[3][2] - a[3][0] * a[2][2],
+            a[2][0] * a[3][3] - a[3][0] * a[2][3],
+            a[2][1] * a[3][2] - a[3][1] * a[2][2],
+            a[2][1] * a[3][3] - a[3][1] * a[2][3],
+            a[2][2] * a[3][3] - a[3][2] * a[2][3],
+        ];
+        (s, c)
+    }
+}
+
+impl Mul<Vec4> for Mat4 {
+    type Output = Vec4;
+
+    fn mul(self, v: Vec4) -> Vec4 {
+        self.x_axis * v.x + self.y_axis * v.y + self.z_axis * v.z + self.w_axis * v.w
+    }
+}
+
+/// `a * b` 先应用 `b` 再应用 `a`
+impl Mul for Mat4 {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        Self::from_cols(
+            self * other.x_axis,
+            self * other.y_axis,
+            self * other.z_axis,
+            self * other.w_axis,
+        )
+    }
+}
+
+#[cfg(test)]
+impl super::Near for Mat3 {
+    fn near(&self, other: &Self, eps: f32) -> bool {
+        self.x_axis.near(&other.x_axis, eps)
+            && self.y_axis.near(&other.y_axis, eps)
+            && self.z_axis.near(&other.z_axis, eps)
+    }
+}
+
+#[cfg(test)]
+impl super::Near for Mat4 {
+    fn near(&self, other: &Self, eps: f32) -> bool {
+        self.x_axis.near(&other.x_axis, eps)
+            && self.y_axis.near(&other.y_axis, eps)
+            && self.z_axis.near(&other.z_axis, eps)
+            && self.w_axis.near(&other.w_axis, eps)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::f32::consts::FRAC_PI_2;
+
+    use super::*;
+    use crate::math::assert_near;
+
+    #[test]
+    fn mat3_composes_2d_transforms() {
+        let m = Mat3::from_translation(Vec2::new(10.0, 0.0))
+            * Mat3::from_angle(FRAC_PI_2)
+            * Mat3::from_scale(Vec2::splat(2.0));
+        assert_near!(m.transform_point(Vec2::X), Vec2::new(10.0, 2.0));
+        assert_near!(m.transform_vector(Vec2::X), Vec2::new(0.0, 2.0));
+        assert_near!(
+            m,
+            Mat3::from_scale_angle_translation(Vec2::splat(2.0), FRAC_PI_2, Vec2::new(10.0, 0.0))
+        );
+        assert_near!(m.determinant(), 4.0);
+        assert_near!(m * m.inverse().unwrap(), Mat3::IDENTITY);
+        assert_eq!(Mat3::from_scale(Vec2::new(1.0, 0.0)).inverse(), None);
+    }
+
+    #[test]
+    fn mat4_inverse_and_determinant() {
+        let m = Mat4::from_scale_rotation_translation(
+            Vec3::new(2.0, 3.0, 4.0),
+            Quat::from_axis_angle(Vec3::new(1.0, 1.0, 0.0), 0.7),
+            Vec3::new(1.0, -2.0, 3.0),
+        );
+        assert_near!(m.determinant(), 24.0, 1e-4);
+        assert_near!(m * m.inverse().unwrap(), Mat4::IDENTITY);
+        assert_near!(m.inverse().unwrap() * m, Mat4::IDENTITY);
+        assert_near!(m.transpose().transpose(), m);
+        // 一般的矩阵
+        let general = Mat4::from_cols_array_2d([
+            [4.0, 3.0, 2.0, 2.0],
+            [0.0, 1.0, -3.0, 3.0],
+            [0.0, -1.0, 3.0, 3.0],
+            [0.0, 3.0, 1.0, 1.0],
+        ]);
+        assert_near!(general.determinant(), -240.0, 1e-3);
+        assert_near!(general * general.inverse().unwrap(), Mat4::IDENTITY);
+        assert_eq!(Mat4::from_scale(Vec3::ZERO).inverse(), None);
+    }
+
+    #[test]
+    fn projections_map_to_clip_space() {
+        let ortho = Mat4::orthographic(0.0, 800.0, 600.0, 0.0, -1.0, 1.0);
+        assert_near!(ortho.transform_point(Vec3::ZERO), Vec3::new(-1.0, 1.0, 0.0));
+        assert_near!(
+            ortho.transform_point(Vec3::new(800.0, 600.0, 0.0)),
+            Vec3::new(1.0, -1.0, 0.0)
+        );
+
+        let perspective = Mat4::perspective(FRAC_PI_2, 2.0, 1.0, 10.0);
+        assert_near!(perspective.project_point(Vec3::new(0.0, 0.0, -1.0)).z, -1.0);
+        assert_near!(perspective.project_point(Vec3::new(0.0, 0.0, -10.0)).z, 1.0);
+        // 90° 视角下近平面的上边缘在 y = 1
+        assert_near!(
+            perspective.project_point(Vec3::new(2.0, 1.0, -1.0)),
+            Vec3::new(1.0, 1.0, -1.0)
+        );
+
+        let view = Mat4::look_at(Vec3::new(0.0, 0.0, 5.0), Vec3::ZERO, Vec3::Y);
+        assert_near!(view.transform_point(Vec3::ZERO), Vec3::new(0.0, 0.0, -5.0));
+        let view = Mat4::look_at(Vec3::new(5.0, 0.0, 0.0), Vec3::ZERO, Vec3::Y);
+        assert_near!(
+            view.transform_point(Vec3::new(0.0, 0.0, -1.0)),
+            Vec3::new(1.0, 0.0, -5.0)
+        );
+    }
+}
@@ -0,0 +1,161 @@
+//! 表示 3D 旋转的四元数
+
+use core::ops::Mul;
+
+use super::{Vec3, Vec4, float};
+
+/// 四元数, 表示旋转时应当是单位长度
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Quat {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub w: f32,
+}
+
+impl Default for Quat {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Quat {
+    pub const IDENTITY: Self = Self::new(0.0, 0.0, 0.0, 1.0);
+
+    pub const fn new(x: f32, y: f32, z: f32, w: f32) -> Self {
+        Self { x, y, z, w }
+    }
+
+    /// 绕 `axis` 逆时针旋转 `angle` 弧度, 轴不需要是单位长度
+    pub fn from_axis_angle(axis: Vec3, angle: f32) -> Self {
+        let axis = axis.normalize_or_zero();
+        let half = angle * 0.5;
+        let (sin, cos) = (float::sin(half), float::cos(half));
+        Self::new(axis.x * sin, axis.y * sin, axis.z * sin, cos)
+    }
+
+    pub const fn to_vec4(self) -> Vec4 {
+        Vec4::new(self.x, self.y, self.z, self.w)
+    }
+
+    pub const fn from_vec4(v: Vec4) -> Self {
+        Self::new(v.x, v.y, v.z, v.w)
+    }
+
+    pub fn dot(self, other: Self) -> f32 {
+        self.to_vec4().dot(other.to_vec4())
+    }
+
+    pub fn length(self) -> f32 {
+        self.to_vec4().length()
+    }
+
+    /// 长度为 0 时返回单位四元数
+    pub fn normalize(self) -> Self {
+        self.to_vec4()
+            .try_normalize()
+            .map_or(Self::IDENTITY, Self::from_vec4)
+    }
+
+    /// 共轭, 对单位四元数就是逆
+    pub const fn conjugate(self) -> Self {
+        Self::new(-self.x, -self.y, -self.z, self.w)
+    }
+
+    pub fn inverse(self) -> Self {
+        Self::from_vec4(self.conjugate().to_vec4() / self.dot(self))
+    }
+
+    /// 球面插值, 沿最短的弧旋转
+    pub fn slerp(self, mut other: Self, t: f32) -> Self {
+        let mut dot = self.dot(other);
+        if dot < 0.0 {
+            other = Self::from_vec4(-other.to_vec4());
+            dot = -dot;
+        }
+        let (from, to) = (self.to_vec4(), other.to_vec4());
+        // 夹角很小时 sin 接近 0, 退化为线性插值
+        if dot > 0.9995 {
+            return Self::from_vec4(from.lerp(to, t)).normalize();
+        }
+        let theta = float::acos(dot);
+        let sin = float::sin(theta);
+        let a = float::sin((1.0 - t) * theta) / sin;
+        let b = float::sin(t * theta) / sin;
+        Self::from_vec4(from * a + to * b)
+    }
+}
+
+/// `a * b` 先旋转 `b` 再旋转 `a`
+impl Mul for Quat {
+    type Output = Self;
+
+    fn mul(self, b: Self) -> Self {
+        let a = self;
+        Self::new(
+            a.w * b.x + a.x * b.w + a.y * b.z - a.z * b.y,
+            a.w * b.y - a.x * b.z + a.y * b.w + a.z * b.x,
+            a.w * b.z + a.x * b.y - a.y * b.x + a.z * b.w,
+            a.w * b.w - a.x * b.x - a.y * b.y - a.z * b.z,
+        )
+    }
+}
+
+/// 旋转向量
+impl Mul<Vec3> for Quat {
+    type Output = Vec3;
+
+    fn mul(self, v: Vec3) -> Vec3 {
+        let axis = Vec3::new(self.x, self.y, self.z);
+        let t = 2.0 * axis.cross(v);
+        v + self.w * t + axis.cross(t)
+    }
+}
+
+#[cfg(test)]
+impl super::Near for Quat {
+    fn near(&self, other: &Self, eps: f32) -> bool {
+        self.to_vec4().near(&other.to_vec4(), eps)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::f32::consts::{FRAC_PI_2, FRAC_PI_4, PI};
+
+    use super::*;
+    use crate::math::{Mat4, assert_near};
+
+    #[test]
+    fn rotates_vectors() {
+        let q = Quat::from_axis_angle(Vec3::Z, FRAC_PI_2);
+        assert_near!(q * Vec3::X, Vec3::Y);
+        assert_near!(q * q * Vec3::X, -Vec3::X);
+        assert_near!(
+            q.inverse() * (q * Vec3::new(1.0, 2.0, 3.0)),
+            Vec3::new(1.0, 2.0, 3.0)
+        );
+        assert_near!(Quat::from_axis_angle(Vec3::X, PI) * Vec3::Y, -Vec3::Y);
+
+        // 和矩阵的结果一致
+        let q = Quat::from_axis_angle(Vec3::new(1.0, 2.0, 3.0), 1.2);
+        let v = Vec3::new(-4.0, 0.5, 2.0);
+        assert_near!(q * v, Mat4::from_quat(q).transform_vector(v));
+    }
+
+    #[test]
+    fn slerp_takes_the_short_way() {
+        let a = Quat::IDENTITY;
+        let b = Quat::from_axis_angle(Vec3::Z, FRAC_PI_2);
+        assert_near!(a.slerp(b, 0.0), a);
+        assert_near!(a.slerp(b, 1.0), b);
+        assert_near!(a.slerp(b, 0.5), Quat::from_axis_angle(Vec3::Z, FRAC_PI_4));
+        // -b 和 b 表示同一个旋转
+        let negated = Quat::from_vec4(-b.to_vec4());
+        assert_near!(
+            a.slerp(negated, 0.5) * Vec3::X,
+            Vec3::new(1.0, 1.0, 0.0).normalize_or_zero()
+        );
+        assert_near!(Quat::new(0.0, 0.0, 2.0, 0.0).normalize().length(), 1.0);
+    }
+}
@@ -0,0 +1,157 @@
+//! 可复现的随机数
+
+use core::f32::consts::TAU;
+use core::ops::Range;
+
+use super::Vec2;
+
+const MULTIPLIER: u64 = 6364136223846793005;
+/// PCG 参考实现的默认流
+const DEFAULT_STREAM: u64 = 0xda3e_39cb_94b9_5bdb;
+
+/// PCG32 随机数生成器, 相同的种子总是产生相同的序列, 适合回放和联机同步
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rng {
+    state: u64,
+    increment: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self::with_stream(seed, DEFAULT_STREAM)
+    }
+
+    /// 相同种子不同 `stream` 的序列互不相关, 和参考实现的 `pcg32_srandom` 一致
+    pub fn with_stream(seed: u64, stream: u64) -> Self {
+        let mut rng = Self {
+            state: 0,
+            increment: (stream << 1) | 1,
+        };
+        rng.next_u32();
+        rng.state = rng.state.wrapping_add(seed);
+        rng.next_u32();
+        rng
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        let old = self.state;
+        self.state = old.wrapping_mul(MULTIPLIER).wrapping_add(self.increment);
+        let xorshifted = (((old >> 18) ^ old) >> 27) as u32;
+        xorshifted.rotate_right((old >> 59) as u32)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        (u64::from(self.next_u32()) << 32) | u64::from(self.next_u32())
+    }
+
+    /// 0 到 1 之间, 不包括 1
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u32() >> 8) as f32 / (1u32 << 24) as f32
+    }
+
+    pub fn range_f32(&mut self, range: Range<f32>) -> f32 {
+        range.start + (range.end - range.start) * self.next_f32()
+    }
+
+    /// 均匀分布, 没有取模带来的偏差; 范围为空时 panic
+    pub fn range_u32(&mut self, range: Range<u32>) -> u32 {
+        assert!(range.start < range.end, "empty range {range:?}");
+        let bound = range.end - range.start;
+        // 丢掉开头不满一轮的部分
+        let threshold = bound.wrapping_neg() % bound;
+        loop {
+            let value = self.next_u32();
+            if value >= threshold {
+                return range.start + value % bound;
+            }
+        }
+    }
+
+    pub fn range_i32(&mut self, range: Range<i32>) -> i32 {
+        assert!(range.start < range.end, "empty range {range:?}");
+        let bound = range.end.wrapping_sub(range.start) as u32;
+        range.start.wrapping_add(self.range_u32(0..bound) as i32)
+    }
+
+    pub fn bool(&mut self) -> bool {
+        self.next_u32() >> 31 == 1
+    }
+
+    /// 以 `probability` 的概率返回 `true`
+    pub fn chance(&mut self, probability: f32) -> bool {
+        self.next_f32() < probability
+    }
+
+    /// 随机选一个元素, 切片为空时返回 `None`
+    pub fn pick<'a, T>(&mut self, items: &'a [T]) -> Option<&'a T> {
+        if items.is_empty() {
+            return None;
+        }
+        items.get(self.range_u32(0..items.len() as u32) as usize)
+    }
+
+    /// Fisher-Yates 洗牌
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = self.range_u32(0..i as u32 + 1) as usize;
+            items.swap(i, j);
+        }
+    }
+
+    /// 单位圆上的随机方向
+    pub fn on_circle(&mut self) -> Vec2 {
+        Vec2::from_angle(self.range_f32(0.0..TAU))
+    }
+
+    /// 单位圆内均匀分布的点
+    pub fn in_circle(&mut self) -> Vec2 {
+        loop {
+            let point = Vec2::new(self.range_f32(-1.0..1.0), self.range_f32(-1.0..1.0));
+            if point.length_squared() <= 1.0 {
+                return point;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_reference_implementation() {
+        // pcg32-demo 的输出
+        let mut rng = Rng::with_stream(42, 54);
+        let expected = [
+            0xa15c02b7, 0x7b47f409, 0xba1d3330, 0x83d2f293, 0xbfa4784b, 0xcbed606e,
+        ];
+        for value in expected {
+            assert_eq!(rng.next_u32(), value);
+        }
+        assert_eq!(Rng::new(7), Rng::new(7));
+        assert_ne!(Rng::new(7).next_u64(), Rng::new(8).next_u64());
+    }
+
+    #[test]
+    fn values_stay_in_range() {
+        let mut rng = Rng::new(1);
+        let mut seen = [false; 6];
+        for _ in 0..1000 {
+            let value = rng.range_i32(-3..3);
+            assert!((-3..3).contains(&value));
+            seen[(value + 3) as usize] = true;
+            let value = rng.range_f32(2.0..4.0);
+            assert!((2.0..4.0).contains(&value));
+            assert!(rng.in_circle().length() <= 1.0);
+            assert!((rng.on_circle().length() - 1.0).abs() < 1e-5);
+        }
+        assert!(seen.iter().all(|&seen| seen));
+        assert_eq!(rng.range_i32(i32::MIN..i32::MIN + 1), i32::MIN);
+        assert_eq!(rng.pick::<u8>(&[]), None);
+
+        let mut items = [1, 2, 3, 4, 5, 6, 7, 8];
+        rng.shuffle(&mut items);
+        items.sort();
+        assert_eq!(items, [1, 2, 3, 4, 5, 6, 7, 8]);
+    }
+}
@@ -0,0 +1,173 @@
+//! 矩形和圆, 以及它们之间的相交测试
+
+use super::Vec2;
+
+/// 轴对齐的矩形, 包含边界
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Rect {
+    pub min: Vec2,
+    pub max: Vec2,
+}
+
+impl Rect {
+    /// 左上角在 (`x`, `y`), 宽 `w` 高 `h`
+    pub const fn new(x: f32, y: f32, w: f32, h: f32) -> Self {
+        Self {
+            min: Vec2::new(x, y),
+            max: Vec2::new(x + w, y + h),
+        }
+    }
+
+    /// 两个角可以按任意顺序给出
+    pub fn from_corners(a: Vec2, b: Vec2) -> Self {
+        Self {
+            min: a.min(b),
+            max: a.max(b),
+        }
+    }
+
+    pub fn from_center_size(center: Vec2, size: Vec2) -> Self {
+        let half = size.abs() * 0.5;
+        Self {
+            min: center - half,
+            max: center + half,
+        }
+    }
+
+    pub fn size(&self) -> Vec2 {
+        self.max - self.min
+    }
+
+    pub fn width(&self) -> f32 {
+        self.max.x - self.min.x
+    }
+
+    pub fn height(&self) -> f32 {
+        self.max.y - self.min.y
+    }
+
+    pub fn center(&self) -> Vec2 {
+        (self.min + self.max) * 0.5
+    }
+
+    pub fn contains_point(&self, point: Vec2) -> bool {
+        point.x >= self.min.x
+            && point.x <= self.max.x
+            && point.y >= self.min.y
+            && point.y <= self.max.y
+    }
+
+    /// 只接触边界也算相交
+    pub fn intersects(&self, other: &Self) -> bool {
+        self.min.x <= other.max.x
+            && other.min.x <= self.max.x
+            && self.min.y <= other.max.y
+            && other.min.y <= self.max.y
+    }
+
+    /// 重叠的部分, 不相交时返回 `None`
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        self.intersects(other).then(|| Self {
+            min: self.min.max(other.min),
+            max: self.max.min(other.max),
+        })
+    }
+
+    /// 包含两个矩形的最小矩形
+    pub fn union(&self, other: &Self) -> Self {
+        Self {
+            min: self.min.min(other.min),
+            max: self.max.max(other.max),
+        }
+    }
+
+    /// 矩形内离 `point` 最近的点
+    pub fn closest_point(&self, point: Vec2) -> Vec2 {
+        point.clamp(self.min, self.max)
+    }
+
+    pub fn intersects_circle(&self, circle: &Circle) -> bool {
+        circle.intersects_rect(self)
+    }
+
+    /// 向外扩展 `amount`, 为负时收缩
+    pub fn inflate(&self, amount: f32) -> Self {
+        Self {
+            min: self.min - Vec2::splat(amount),
+            max: self.max + Vec2::splat(amount),
+        }
+    }
+}
+
+/// 圆, 包含边界
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Circle {
+    pub center: Vec2,
+    pub radius: f32,
+}
+
+impl Circle {
+    pub const fn new(center: Vec2, radius: f32) -> Self {
+        Self { center, radius }
+    }
+
+    pub fn contains_point(&self, point: Vec2) -> bool {
+        self.center.distance_squared(point) <= self.radius * self.radius
+    }
+
+    pub fn intersects_circle(&self, other: &Self) -> bool {
+        let radius = self.radius + other.radius;
+        self.center.distance_squared(other.center) <= radius * radius
+    }
+
+    /// 用矩形内离圆心最近的点判断
+    pub fn intersects_rect(&self, rect: &Rect) -> bool {
+        self.contains_point(rect.closest_point(self.center))
+    }
+
+    /// 包围圆的矩形
+    pub fn bounding_rect(&self) -> Rect {
+        Rect::from_center_size(self.center, Vec2::splat(self.radius * 2.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rect_overlaps() {
+        let a = Rect::new(0.0, 0.0, 10.0, 10.0);
+        let b = Rect::new(5.0, 5.0, 10.0, 10.0);
+        let c = Rect::new(20.0, 0.0, 5.0, 5.0);
+        assert!(a.intersects(&b));
+        assert!(!a.intersects(&c));
+        assert!(a.intersects(&Rect::new(10.0, 10.0, 1.0, 1.0)));
+        assert_eq!(a.intersection(&b), Some(Rect::new(5.0, 5.0, 5.0, 5.0)));
+        assert_eq!(a.intersection(&c), None);
+        assert_eq!(a.union(&c), Rect::new(0.0, 0.0, 25.0, 10.0));
+        assert_eq!(a.center(), Vec2::splat(5.0));
+        assert!(a.contains_point(Vec2::new(10.0, 0.0)));
+        assert!(!a.contains_point(Vec2::new(10.1, 0.0)));
+        assert_eq!(
+            Rect::from_corners(Vec2::new(4.0, 1.0), Vec2::new(2.0, 3.0)),
+            Rect::from_center_size(Vec2::new(3.0, 2.0), Vec2::splat(2.0))
+        );
+    }
+
+    #[test]
+    fn circle_overlaps() {
+        let circle = Circle::new(Vec2::ZERO, 5.0);
+        assert!(circle.contains_point(Vec2::new(3.0, 4.0)));
+        assert!(!circle.contains_point(Vec2::new(3.0, 4.1)));
+        assert!(circle.intersects_circle(&Circle::new(Vec2::new(8.0, 0.0), 3.0)));
+        assert!(!circle.intersects_circle(&Circle::new(Vec2::new(8.1, 0.0), 3.0)));
+
+        // 矩形的角离圆心 √50 > 5, 但边离圆心只有 4
+        assert!(circle.intersects_rect(&Rect::new(4.0, -1.0, 2.0, 2.0)));
+        assert!(!circle.intersects_rect(&Rect::new(4.0, 4.0, 2.0, 2.0)));
+        // 圆完全在矩形内
+        assert!(Rect::new(-10.0, -10.0, 20.0, 20.0).intersects_circle(&circle));
+        assert_eq!(circle.bounding_rect(), Rect::new(-5.0, -5.0, 10.0, 10.0));
+    }
+}
@@ -0,0 +1,135 @@
+//! 2D 变换
+
+use core::ops::Mul;
+
+use super::{Mat3, Vec2};
+
+/// 依次缩放、旋转、平移的 2D 变换, 可以组成父子层级
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform {
+    pub translation: Vec2,
+    /// 弧度
+    pub rotation: f32,
+    pub scale: Vec2,
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Transform {
+    pub const IDENTITY: Self = Self {
+        translation: Vec2::ZERO,
+        rotation: 0.0,
+        scale: Vec2::ONE,
+    };
+
+    pub const fn from_translation(translation: Vec2) -> Self {
+        Self {
+            translation,
+            ..Self::IDENTITY
+        }
+    }
+
+    pub const fn from_rotation(rotation: f32) -> Self {
+        Self {
+            rotation,
+            ..Self::IDENTITY
+        }
+    }
+
+    pub const fn from_scale(scale: Vec2) -> Self {
+        Self {
+            scale,
+            ..Self::IDENTITY
+        }
+    }
+
+    pub const fn with_translation(self, translation: Vec2) -> Self {
+        Self {
+            translation,
+            ..self
+        }
+    }
+
+    pub const fn with_rotation(self, rotation: f32) -> Self {
+        Self { rotation, ..self }
+    }
+
+    pub const fn with_scale(self, scale: Vec2) -> Self {
+        Self { scale, ..self }
+    }
+
+    pub fn to_mat3(&self) -> Mat3 {
+        Mat3::from_scale_angle_translation(self.scale, self.rotation, self.translation)
+    }
+
+    /// 把局部坐标的点变换到父坐标系
+    pub fn transform_point(&self, point: Vec2) -> Vec2 {
+        (point * self.scale).rotate(self.rotation) + self.translation
+    }
+
+    /// 把父坐标系的点变换回局部坐标, 缩放为 0 时结果是无穷大
+    pub fn inverse_transform_point(&self, point: Vec2) -> Vec2 {
+        let local = (point - self.translation).rotate(-self.rotation);
+        Vec2::new(local.x / self.scale.x, local.y / self.scale.y)
+    }
+
+    /// 把子节点的局部变换组合成全局变换, 即 `self * child`
+    ///
+    /// 父节点的缩放不均匀且子节点有旋转时会产生切变, 无法用 `Transform` 表示,
+    /// 结果只在缩放均匀时精确, 需要精确结果时用 [`to_mat3`](Self::to_mat3) 相乘.
+    pub fn mul_transform(&self, child: &Self) -> Self {
+        Self {
+            translation: self.transform_point(child.translation),
+            rotation: self.rotation + child.rotation,
+            scale: self.scale * child.scale,
+        }
+    }
+}
+
+impl Mul for Transform {
+    type Output = Self;
+
+    fn mul(self, child: Self) -> Self {
+        self.mul_transform(&child)
+    }
+}
+
+#[cfg(test)]
+impl super::Near for Transform {
+    fn near(&self, other: &Self, eps: f32) -> bool {
+        self.translation.near(&other.translation, eps)
+            && self.rotation.near(&other.rotation, eps)
+            && self.scale.near(&other.scale, eps)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::f32::consts::FRAC_PI_2;
+
+    use super::*;
+    use crate::math::assert_near;
+
+    #[test]
+    fn children_follow_their_parent() {
+        let parent = Transform::from_translation(Vec2::new(10.0, 0.0))
+            .with_rotation(FRAC_PI_2)
+            .with_scale(Vec2::splat(2.0));
+        let child = Transform::from_translation(Vec2::X).with_rotation(0.5);
+        let global = parent * child;
+        assert_near!(global.translation, Vec2::new(10.0, 2.0));
+        assert_near!(global.rotation, FRAC_PI_2 + 0.5);
+        assert_near!(global.scale, Vec2::splat(2.0));
+        assert_near!(global.to_mat3(), parent.to_mat3() * child.to_mat3());
+
+        // 孙节点的点经过两层变换
+        let point = Vec2::new(3.0, -1.0);
+        let world = global.transform_point(point);
+        assert_near!(world, parent.transform_point(child.transform_point(point)));
+        assert_near!(global.inverse_transform_point(world), point);
+    }
+}
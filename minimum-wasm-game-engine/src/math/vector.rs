@@ -0,0 +1,319 @@
+//! 向量
+
+use core::ops::{Add, AddAssign, Div, DivAssign, Index, Mul, MulAssign, Neg, Sub, SubAssign};
+
+use super::float;
+
+/// 定义向量类型和逐分量的运算
+macro_rules! vector {
+    ($(#[$meta:meta])* $name:ident { $($field:ident),+ }, $n:literal) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, PartialEq, Default)]
+        pub struct $name {
+            $(pub $field: f32,)+
+        }
+
+        impl $name {
+            pub const ZERO: Self = Self::splat(0.0);
+            pub const ONE: Self = Self::splat(1.0);
+
+            pub const fn new($($field: f32),+) -> Self {
+                Self { $($field),+ }
+            }
+
+            pub const fn splat(value: f32) -> Self {
+                Self { $($field: value),+ }
+            }
+
+            pub const fn to_array(self) -> [f32; $n] {
+                [$(self.$field),+]
+            }
+
+            pub const fn from_array([$($field),+]: [f32; $n]) -> Self {
+                Self { $($field),+ }
+            }
+
+            pub fn dot(self, other: Self) -> f32 {
+                0.0 $(+ self.$field * other.$field)+
+            }
+
+            pub fn length_squared(self) -> f32 {
+                self.dot(self)
+            }
+
+            pub fn length(self) -> f32 {
+                float::sqrt(self.length_squared())
+            }
+
+            pub fn distance(self, other: Self) -> f32 {
+                (self - other).length()
+            }
+
+            pub fn distance_squared(self, other: Self) -> f32 {
+                (self - other).length_squared()
+            }
+
+            /// 长度为 0 时返回 `None`
+            pub fn try_normalize(self) -> Option<Self> {
+                let length = self.length();
+                (length > 0.0 && length.is_finite()).then(|| self / length)
+            }
+
+            /// 长度为 0 时返回零向量
+            pub fn normalize_or_zero(self) -> Self {
+                self.try_normalize().unwrap_or(Self::ZERO)
+            }
+
+            /// 线性插值, `t` 为 0 时是 `self`, 为 1 时是 `other`
+            pub fn lerp(self, other: Self, t: f32) -> Self {
+                self + (other - self) * t
+            }
+
+            pub fn min(self, other: Self) -> Self {
+                Self { $($field: self.$field.min(other.$field)),+ }
+            }
+
+            pub fn max(self, other: Self) -> Self {
+                Self { $($field: self.$field.max(other.$field)),+ }
+            }
+
+            pub fn clamp(self, min: Self, max: Self) -> Self {
+                self.max(min).min(max)
+            }
+
+            pub fn abs(self) -> Self {
+                Self { $($field: self.$field.abs()),+ }
+            }
+
+            /// 长度不超过 `max`
+            pub fn clamp_length(self, max: f32) -> Self {
+                let length_squared = self.length_squared();
+                if length_squared > max * max {
+                    self * (max / float::sqrt(length_squared))
+                } else {
+                    self
+                }
+            }
+        }
+
+        impl Add for $name {
+            type Output = Self;
+
+            fn add(self, other: Self) -> Self {
+                Self { $($field: self.$field + other.$field),+ }
+            }
+        }
+
+        impl Sub for $name {
+            type Output = Self;
+
+            fn sub(self, other: Self) -> Self {
+                Self { $($field: self.$field - other.$field),+ }
+            }
+        }
+
+        /// 逐分量相乘
+        impl Mul for $name {
+            type Output = Self;
+
+            fn mul(self, other: Self) -> Self {
+                Self { $($field: self.$field * other.$field),+ }
+            }
+        }
+
+        impl Mul<f32> for $name {
+            type Output = Self;
+
+            fn mul(self, scale: f32) -> Self {
+                Self { $($field: self.$field * scale),+ }
+            }
+        }
+
+        impl Mul<$name> for f32 {
+            type Output = $name;
+
+            fn mul(self, vector: $name) -> $name {
+                vector * self
+            }
+        }
+
+        impl Div<f32> for $name {
+            type Output = Self;
+
+            fn div(self, scale: f32) -> Self {
+                Self { $($field: self.$field / scale),+ }
+            }
+        }
+
+        impl Neg for $name {
+            type Output = Self;
+
+            fn neg(self) -> Self {
+                Self { $($field: -self.$field),+ }
+            }
+        }
+
+        impl AddAssign for $name {
+            fn add_assign(&mut self, other: Self) {
+                *self = *self + other;
+            }
+        }
+
+        impl SubAssign for $name {
+            fn sub_assign(&mut self, other: Self) {
+                *self = *self - other;
+            }
+        }
+
+        impl MulAssign<f32> for $name {
+            fn mul_assign(&mut self, scale: f32) {
+                *self = *self * scale;
+            }
+        }
+
+        impl DivAssign<f32> for $name {
+            fn div_assign(&mut self, scale: f32) {
+                *self = *self / scale;
+            }
+        }
+
+        impl Index<usize> for $name {
+            type Output = f32;
+
+            fn index(&self, index: usize) -> &f32 {
+                [$(&self.$field),+][index]
+            }
+        }
+
+        impl From<[f32; $n]> for $name {
+            fn from(array: [f32; $n]) -> Self {
+                Self::from_array(array)
+            }
+        }
+
+        impl From<$name> for [f32; $n] {
+            fn from(vector: $name) -> Self {
+                vector.to_array()
+            }
+        }
+
+        #[cfg(test)]
+        impl super::Near for $name {
+            fn near(&self, other: &Self, eps: f32) -> bool {
+                true $(&& (self.$field - other.$field).abs() <= eps)+
+            }
+        }
+    };
+}
+
+vector!(
+    /// 二维向量, 屏幕坐标 y 轴向下
+    Vec2 { x, y }, 2
+);
+vector!(Vec3 { x, y, z }, 3);
+vector!(Vec4 { x, y, z, w }, 4);
+
+impl Vec2 {
+    pub const X: Self = Self::new(1.0, 0.0);
+    pub const Y: Self = Self::new(0.0, 1.0);
+
+    /// 和 x 轴夹角为 `angle` 的单位向量
+    pub fn from_angle(angle: f32) -> Self {
+        Self::new(float::cos(angle), float::sin(angle))
+    }
+
+    /// 和 x 轴的夹角, 在 -π 到 π 之间
+    pub fn angle(self) -> f32 {
+        float::atan2(self.y, self.x)
+    }
+
+    /// 旋转 90°
+    pub fn perp(self) -> Self {
+        Self::new(-self.y, self.x)
+    }
+
+    /// 二维叉积, `other` 在逆时针方向时为正
+    pub fn perp_dot(self, other: Self) -> f32 {
+        self.x * other.y - self.y * other.x
+    }
+
+    pub fn rotate(self, angle: f32) -> Self {
+        let (sin, cos) = (float::sin(angle), float::cos(angle));
+        Self::new(self.x * cos - self.y * sin, self.x * sin + self.y * cos)
+    }
+
+    pub const fn extend(self, z: f32) -> Vec3 {
+        Vec3::new(self.x, self.y, z)
+    }
+}
+
+impl Vec3 {
+    pub const X: Self = Self::new(1.0, 0.0, 0.0);
+    pub const Y: Self = Self::new(0.0, 1.0, 0.0);
+    pub const Z: Self = Self::new(0.0, 0.0, 1.0);
+
+    pub fn cross(self, other: Self) -> Self {
+        Self::new(
+            self.y * other.z - self.z * other.y,
+            self.z * other.x - self.x * other.z,
+            self.x * other.y - self.y * other.x,
+        )
+    }
+
+    pub const fn extend(self, w: f32) -> Vec4 {
+        Vec4::new(self.x, self.y, self.z, w)
+    }
+
+    pub const fn truncate(self) -> Vec2 {
+        Vec2::new(self.x, self.y)
+    }
+}
+
+impl Vec4 {
+    pub const fn truncate(self) -> Vec3 {
+        Vec3::new(self.x, self.y, self.z)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::f32::consts::FRAC_PI_2;
+
+    use super::*;
+    use crate::math::assert_near;
+
+    #[test]
+    fn basic_operations() {
+        let a = Vec3::new(1.0, 2.0, 3.0);
+        let b = Vec3::new(4.0, -5.0, 6.0);
+        assert_eq!(a + b, Vec3::new(5.0, -3.0, 9.0));
+        assert_eq!(a - b, Vec3::new(-3.0, 7.0, -3.0));
+        assert_eq!(2.0 * a, Vec3::new(2.0, 4.0, 6.0));
+        assert_eq!(a.dot(b), 12.0);
+        assert_eq!(a.cross(b), Vec3::new(27.0, 6.0, -13.0));
+        assert_eq!(Vec3::X.cross(Vec3::Y), Vec3::Z);
+        assert_eq!(a[2], 3.0);
+        assert_eq!(Vec4::from([1.0, 2.0, 3.0, 4.0]).truncate(), a);
+    }
+
+    #[test]
+    fn lengths_and_normalization() {
+        let v = Vec2::new(3.0, 4.0);
+        assert_eq!(v.length(), 5.0);
+        assert_near!(v.normalize_or_zero(), Vec2::new(0.6, 0.8));
+        assert_eq!(Vec2::ZERO.try_normalize(), None);
+        assert_near!(v.clamp_length(1.0).length(), 1.0);
+        assert_eq!(v.clamp_length(10.0), v);
+        assert_eq!(v.lerp(Vec2::ZERO, 0.5), Vec2::new(1.5, 2.0));
+        assert_eq!(Vec4::splat(2.0).length(), 4.0);
+    }
+
+    #[test]
+    fn angles_in_2d() {
+        assert_near!(Vec2::X.rotate(FRAC_PI_2), Vec2::Y);
+        assert_near!(Vec2::from_angle(FRAC_PI_2), Vec2::Y);
+        assert_near!(Vec2::new(-1.0, 0.0).angle(), core::f32::consts::PI);
+        assert_eq!(Vec2::X.perp(), Vec2::Y);
+        assert!(Vec2::X.perp_dot(Vec2::Y) > 0.0);
+    }
+}
@@ -0,0 +1,369 @@
+//! 查询
+//!
+//! `world.query::<(Entity, &mut Position, &Velocity)>()` 在创建时借用需要的组件列,
+//! 之后通过 [`Query::for_each`] 或 [`Query::get`] 访问. 同一列不能同时被可变借用和借用,
+//! 冲突时 panic. 遍历从元组中拥有实体最少的组件列开始.
+
+use std::marker::PhantomData;
+
+use crate::storage::Column;
+use crate::{Component, Entity, Ref, RefMut, World};
+
+/// 可以查询的数据: `&T`、`&mut T`、`Option<Q>`、`Entity` 和它们的元组
+pub trait QueryData {
+    /// 查询期间持有的借用
+    type Fetch<'w>;
+    type Item<'f>;
+
+    /// 借用需要的组件列, 必需的组件不存在时返回 `None`
+    fn fetch(world: &World) -> Option<Self::Fetch<'_>>;
+
+    /// 满足查询的实体一定在这个列表中, 没有限制时返回 `None`
+    fn candidates<'a>(fetch: &'a Self::Fetch<'_>) -> Option<&'a [Entity]>;
+
+    fn get<'f>(fetch: &'f mut Self::Fetch<'_>, entity: Entity) -> Option<Self::Item<'f>>;
+}
+
+impl<T: Component> QueryData for &T {
+    type Fetch<'w> = (&'w Column, Ref<'w, Vec<T>>);
+    type Item<'f> = &'f T;
+
+    fn fetch(world: &World) -> Option<Self::Fetch<'_>> {
+        let column = world.column::<T>()?;
+        Some((column, column.borrow()))
+    }
+
+    fn candidates<'a>(fetch: &'a Self::Fetch<'_>) -> Option<&'a [Entity]> {
+        Some(fetch.0.entities())
+    }
+
+    fn get<'f>(fetch: &'f mut Self::Fetch<'_>, entity: Entity) -> Option<Self::Item<'f>> {
+        let dense = fetch.0.dense_index(entity)?;
+        Some(&fetch.1[dense])
+    }
+}
+
+impl<T: Component> QueryData for &mut T {
+    type Fetch<'w> = (&'w Column, RefMut<'w, Vec<T>>);
+    type Item<'f> = &'f mut T;
+
+    fn fetch(world: &World) -> Option<Self::Fetch<'_>> {
+        let column = world.column::<T>()?;
+        Some((column, column.borrow_mut()))
+    }
+
+    fn candidates<'a>(fetch: &'a Self::Fetch<'_>) -> Option<&'a [Entity]> {
+        Some(fetch.0.entities())
+    }
+
+    fn get<'f>(fetch: &'f mut Self::Fetch<'_>, entity: Entity) -> Option<Self::Item<'f>> {
+        let dense = fetch.0.dense_index(entity)?;
+        Some(&mut fetch.1[dense])
+    }
+}
+
+impl<Q: QueryData> QueryData for Option<Q> {
+    type Fetch<'w> = Option<Q::Fetch<'w>>;
+    type Item<'f> = Option<Q::Item<'f>>;
+
+    fn fetch(world: &World) -> Option<Self::Fetch<'_>> {
+        Some(Q::fetch(world))
+    }
+
+    fn candidates<'a>(_fetch: &'a Self::Fetch<'_>) -> Option<&'a [Entity]> {
+        None
+    }
+
+    fn get<'f>(fetch: &'f mut Self::Fetch<'_>, entity: Entity) -> Option<Self::Item<'f>> {
+        Some(fetch.as_mut().and_then(|fetch| Q::get(fetch, entity)))
+    }
+}
+
+impl QueryData for Entity {
+    type Fetch<'w> = ();
+    type Item<'f> = Entity;
+
+    fn fetch(_world: &World) -> Option<Self::Fetch<'_>> {
+        Some(())
+    }
+
+    fn candidates<'a>(_fetch: &'a Self::Fetch<'_>) -> Option<&'a [Entity]> {
+        None
+    }
+
+    fn get<'f>(_fetch: &'f mut Self::Fetch<'_>, entity: Entity) -> Option<Self::Item<'f>> {
+        Some(entity)
+    }
+}
+
+/// 两个候选列表中较短的一个
+fn shorter<'a>(a: Option<&'a [Entity]>, b: Option<&'a [Entity]>) -> Option<&'a [Entity]> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(if b.len() < a.len() { b } else { a }),
+        (a, b) => a.or(b),
+    }
+}
+
+macro_rules! impl_query_data {
+    ($($name:ident),*) => {
+        #[allow(non_snake_case)]
+        impl<$($name: QueryData),*> QueryData for ($($name,)*) {
+            type Fetch<'w> = ($($name::Fetch<'w>,)*);
+            type Item<'f> = ($($name::Item<'f>,)*);
+
+            fn fetch(world: &World) -> Option<Self::Fetch<'_>> {
+                Some(($($name::fetch(world)?,)*))
+            }
+
+            fn candidates<'a>(fetch: &'a Self::Fetch<'_>) -> Option<&'a [Entity]> {
+                let ($($name,)*) = fetch;
+                let candidates = None;
+                $(let candidates = shorter(candidates, $name::candidates($name));)*
+                candidates
+            }
+
+            fn get<'f>(fetch: &'f mut Self::Fetch<'_>, entity: Entity) -> Option<Self::Item<'f>> {
+                let ($($name,)*) = fetch;
+                Some(($($name::get($name, entity)?,)*))
+            }
+        }
+    };
+}
+
+impl_query_data!(A);
+impl_query_data!(A, B);
+impl_query_data!(A, B, C);
+impl_query_data!(A, B, C, D);
+impl_query_data!(A, B, C, D, E);
+impl_query_data!(A, B, C, D, E, F);
+impl_query_data!(A, B, C, D, E, F, G);
+impl_query_data!(A, B, C, D, E, F, G, H);
+
+/// 查询的过滤条件: [`With`]、[`Without`] 和它们的元组
+///
+/// 过滤器只检查实体有没有组件, 不借用组件数据
+pub trait QueryFilter {
+    type State<'w>;
+
+    fn prepare(world: &World) -> Self::State<'_>;
+
+    fn candidates<'a>(state: &'a Self::State<'_>) -> Option<&'a [Entity]>;
+
+    fn matches(state: &Self::State<'_>, entity: Entity) -> bool;
+}
+
+/// 要求实体带有组件 `T`, 但不访问它
+pub struct With<T>(PhantomData<fn() -> T>);
+
+/// 要求实体没有组件 `T`
+pub struct Without<T>(PhantomData<fn() -> T>);
+
+impl<T: Component> QueryFilter for With<T> {
+    type State<'w> = Option<&'w Column>;
+
+    fn prepare(world: &World) -> Self::State<'_> {
+        world.column::<T>()
+    }
+
+    fn candidates<'a>(state: &'a Self::State<'_>) -> Option<&'a [Entity]> {
+        // 没有这一列时任何实体都不满足
+        Some(state.map_or(&[], |column| column.entities()))
+    }
+
+    fn matches(state: &Self::State<'_>, entity: Entity) -> bool {
+        state.is_some_and(|column| column.contains(entity))
+    }
+}
+
+impl<T: Component> QueryFilter for Without<T> {
+    type State<'w> = Option<&'w Column>;
+
+    fn prepare(world: &World) -> Self::State<'_> {
+        world.column::<T>()
+    }
+
+    fn candidates<'a>(_state: &'a Self::State<'_>) -> Option<&'a [Entity]> {
+        None
+    }
+
+    fn matches(state: &Self::State<'_>, entity: Entity) -> bool {
+        !state.is_some_and(|column| column.contains(entity))
+    }
+}
+
+macro_rules! impl_query_filter {
+    ($($name:ident),*) => {
+        #[allow(non_snake_case, unused_variables, clippy::unused_unit)]
+        impl<$($name: QueryFilter),*> QueryFilter for ($($name,)*) {
+            type State<'w> = ($($name::State<'w>,)*);
+
+            fn prepare(world: &World) -> Self::State<'_> {
+                ($($name::prepare(world),)*)
+            }
+
+            fn candidates<'a>(state: &'a Self::State<'_>) -> Option<&'a [Entity]> {
+                let ($($name,)*) = state;
+                let candidates = None;
+                $(let candidates = shorter(candidates, $name::candidates($name));)*
+                candidates
+            }
+
+            fn matches(state: &Self::State<'_>, entity: Entity) -> bool {
+                let ($($name,)*) = state;
+                true $(&& $name::matches($name, entity))*
+            }
+        }
+    };
+}
+
+impl_query_filter!();
+impl_query_filter!(A);
+impl_query_filter!(A, B);
+impl_query_filter!(A, B, C);
+impl_query_filter!(A, B, C, D);
+
+/// 借用着组件列的查询, 由 [`World::query`] 创建
+pub struct Query<'w, Q: QueryData, F: QueryFilter = ()> {
+    world: &'w World,
+    /// 必需的组件不存在时为 `None`, 查询结果为空
+    fetch: Option<Q::Fetch<'w>>,
+    filter: F::State<'w>,
+}
+
+impl<'w, Q: QueryData, F: QueryFilter> Query<'w, Q, F> {
+    pub(crate) fn new(world: &'w World) -> Self {
+        Self {
+            world,
+            fetch: Q::fetch(world),
+            filter: F::prepare(world),
+        }
+    }
+
+    /// 对每个满足查询的实体调用 `f`
+    pub fn for_each(&mut self, mut f: impl FnMut(Q::Item<'_>)) {
+        let candidates = self.candidates();
+        let Some(fetch) = &mut self.fetch else {
+            return;
+        };
+        for entity in candidates {
+            if !F::matches(&self.filter, entity) {
+                continue;
+            }
+            if let Some(item) = Q::get(fetch, entity) {
+                f(item);
+            }
+        }
+    }
+
+    /// 单个实体的查询结果, 不满足查询时返回 `None`
+    pub fn get(&mut self, entity: Entity) -> Option<Q::Item<'_>> {
+        if !self.world.is_alive(entity) || !F::matches(&self.filter, entity) {
+            return None;
+        }
+        Q::get(self.fetch.as_mut()?, entity)
+    }
+
+    /// 满足查询的实体数量
+    pub fn count(&mut self) -> usize {
+        let mut count = 0;
+        self.for_each(|_| count += 1);
+        count
+    }
+
+    /// 满足查询的所有实体
+    pub fn entities(&mut self) -> Vec<Entity> {
+        let mut entities = Vec::new();
+        let candidates = self.candidates();
+        let Some(fetch) = &mut self.fetch else {
+            return entities;
+        };
+        for entity in candidates {
+            if F::matches(&self.filter, entity) && Q::get(fetch, entity).is_some() {
+                entities.push(entity);
+            }
+        }
+        entities
+    }
+
+    /// 需要检查的实体, 复制一份后遍历时才能可变借用同一列的数据
+    fn candidates(&self) -> Vec<Entity> {
+        let Some(fetch) = &self.fetch else {
+            return Vec::new();
+        };
+        match shorter(Q::candidates(fetch), F::candidates(&self.filter)) {
+            Some(candidates) => candidates.to_vec(),
+            None => self.world.entities().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Entity, With, Without, World};
+
+    #[derive(Debug, PartialEq)]
+    struct Position(i32);
+    #[derive(Debug, PartialEq)]
+    struct Velocity(i32);
+    struct Frozen;
+
+    fn world() -> (World, [Entity; 4]) {
+        let mut world = World::new();
+        let entities = [
+            world.spawn((Position(0), Velocity(1))),
+            world.spawn((Position(10), Velocity(2), Frozen)),
+            world.spawn((Position(20),)),
+            world.spawn((Velocity(3),)),
+        ];
+        (world, entities)
+    }
+
+    #[test]
+    fn tuples_match_entities_with_every_component() {
+        let (world, [a, b, ..]) = world();
+        world
+            .query::<(&mut Position, &Velocity)>()
+            .for_each(|(position, velocity)| position.0 += velocity.0);
+        assert_eq!(*world.get::<Position>(a).unwrap(), Position(1));
+        assert_eq!(*world.get::<Position>(b).unwrap(), Position(12));
+
+        let mut query = world.query::<(Entity, &Position, Option<&Velocity>)>();
+        assert_eq!(query.count(), 3);
+        let (entity, _, velocity) = query.get(a).unwrap();
+        assert_eq!((entity, velocity), (a, Some(&Velocity(1))));
+    }
+
+    #[test]
+    fn filters_check_presence_without_borrowing() {
+        let (world, [a, b, c, _]) = world();
+        let mut moving = world.query_filtered::<&mut Position, (With<Velocity>, Without<Frozen>)>();
+        assert_eq!(moving.entities(), vec![a]);
+        assert!(moving.get(b).is_none());
+        assert!(moving.get(c).is_none());
+
+        // 过滤器可以引用查询正在可变借用的组件
+        let mut still = world.query_filtered::<&mut Velocity, Without<Position>>();
+        still.for_each(|velocity| velocity.0 = 0);
+        assert_eq!(still.count(), 1);
+        assert_eq!(
+            world.query_filtered::<Entity, With<Frozen>>().entities(),
+            vec![b]
+        );
+    }
+
+    #[test]
+    fn missing_components_give_empty_queries() {
+        struct Unused;
+        let (world, _) = world();
+        assert_eq!(world.query::<(&Position, &Unused)>().count(), 0);
+        assert_eq!(world.query_filtered::<Entity, With<Unused>>().count(), 0);
+        assert_eq!(world.query_filtered::<Entity, Without<Unused>>().count(), 4);
+    }
+
+    #[test]
+    #[should_panic]
+    fn conflicting_borrows_panic() {
+        let (world, _) = world();
+        world.query::<(&mut Position, &Position)>();
+    }
+}
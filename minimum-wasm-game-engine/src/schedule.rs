@@ -0,0 +1,644 @@
+//! 系统调度
+//!
+//! 系统注册到固定的几个阶段中, 每帧按 [`Stage`] 的顺序执行; [`Stage::FixedUpdate`]
+//! 由累加器驱动, 每帧执行 0 到多次. 同一阶段内的系统按 `before`/`after` 约束排序,
+//! 没有约束时按注册顺序. 系统通过 `&World` 访问组件和资源, 结构修改写进自己的
+//! [`CommandBuffer`], 在阶段结束时按系统的顺序应用.
+//!
+//! 并行执行器把访问不冲突的系统放进同一批并行执行, 结果和单线程执行相同.
+//! 没有声明访问的系统独占执行, [`Stage::Render`] 总是在当前线程中执行.
+
+use std::any::TypeId;
+
+use crate::{CommandBuffer, Component, Resource, World};
+
+/// 每帧依次执行的阶段
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Stage {
+    PreUpdate,
+    /// 以固定的时间步长执行, 步长见 [`FixedTime`]
+    FixedUpdate,
+    Update,
+    PostUpdate,
+    Render,
+}
+
+impl Stage {
+    pub const ALL: [Stage; 5] = [
+        Stage::PreUpdate,
+        Stage::FixedUpdate,
+        Stage::Update,
+        Stage::PostUpdate,
+        Stage::Render,
+    ];
+}
+
+/// 固定时间步长的累加器, 作为资源放在世界中
+#[derive(Debug, Clone)]
+pub struct FixedTime {
+    step: f32,
+    accumulator: f32,
+    /// 一帧最多执行的次数, 卡顿后不会为了追赶而越来越慢
+    max_steps: u32,
+}
+
+impl Default for FixedTime {
+    fn default() -> Self {
+        Self::new(1.0 / 60.0)
+    }
+}
+
+impl FixedTime {
+    pub fn new(step: f32) -> Self {
+        assert!(step > 0.0, "fixed time step must be positive");
+        Self {
+            step,
+            accumulator: 0.0,
+            max_steps: 5,
+        }
+    }
+
+    pub fn with_max_steps(mut self, max_steps: u32) -> Self {
+        self.max_steps = max_steps;
+        self
+    }
+
+    /// 每次 [`Stage::FixedUpdate`] 推进的时间
+    pub fn step(&self) -> f32 {
+        self.step
+    }
+
+    /// 累积了但还不够一步的时间占步长的比例, 用于渲染时插值
+    pub fn alpha(&self) -> f32 {
+        self.accumulator / self.step
+    }
+
+    /// 累加一帧的时间, 返回这一帧要执行的步数, 超过上限的时间被丢弃
+    pub fn accumulate(&mut self, delta: f32) -> u32 {
+        self.accumulator += delta;
+        let mut steps = 0;
+        while self.accumulator >= self.step {
+            if steps == self.max_steps {
+                self.accumulator %= self.step;
+                break;
+            }
+            self.accumulator -= self.step;
+            steps += 1;
+        }
+        steps
+    }
+}
+
+/// 只在资源 `S` 等于 `state` 时运行, 用作 [`SystemConfig::run_if`] 的条件
+pub fn in_state<S: Resource + PartialEq>(state: S) -> impl FnMut(&World) -> bool + Send + 'static {
+    move |world| {
+        world
+            .get_resource::<S>()
+            .is_some_and(|current| *current == state)
+    }
+}
+
+/// 只在资源 `R` 存在时运行
+pub fn resource_exists<R: Resource>() -> impl FnMut(&World) -> bool + Send + 'static {
+    |world| world.contains_resource::<R>()
+}
+
+type SystemFn = Box<dyn FnMut(&World, &mut CommandBuffer) + Send>;
+type Condition = Box<dyn FnMut(&World) -> bool + Send>;
+
+/// 系统读写的数据, 用来判断能否并行
+#[derive(Debug, Default, Clone)]
+struct Access {
+    reads: Vec<TypeId>,
+    writes: Vec<TypeId>,
+}
+
+impl Access {
+    fn conflicts(&self, other: &Access) -> bool {
+        self.writes
+            .iter()
+            .any(|id| other.reads.contains(id) || other.writes.contains(id))
+            || other.writes.iter().any(|id| self.reads.contains(id))
+    }
+}
+
+/// 注册好的系统, 由 [`Schedule::add_system`] 返回, 用来设置顺序、条件和访问
+pub struct SystemConfig {
+    name: &'static str,
+    system: SystemFn,
+    conditions: Vec<Condition>,
+    before: Vec<&'static str>,
+    after: Vec<&'static str>,
+    /// `None` 表示没有声明, 独占执行
+    access: Option<Access>,
+    commands: CommandBuffer,
+}
+
+impl SystemConfig {
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// 在名为 `name` 的系统之前执行, 同名的系统都算
+    pub fn before(&mut self, name: &'static str) -> &mut Self {
+        self.before.push(name);
+        self
+    }
+
+    /// 在名为 `name` 的系统之后执行
+    pub fn after(&mut self, name: &'static str) -> &mut Self {
+        self.after.push(name);
+        self
+    }
+
+    /// 所有条件都满足时才运行, 条件在系统所在的批次开始前求值
+    pub fn run_if(&mut self, condition: impl FnMut(&World) -> bool + Send + 'static) -> &mut Self {
+        self.conditions.push(Box::new(condition));
+        self
+    }
+
+    /// 声明读取组件或资源 `T`, 声明过访问的系统才会和其他系统并行
+    pub fn reads<T: Component>(&mut self) -> &mut Self {
+        self.access
+            .get_or_insert_with(Access::default)
+            .reads
+            .push(TypeId::of::<T>());
+        self
+    }
+
+    /// 声明修改组件或资源 `T`
+    pub fn writes<T: Component>(&mut self) -> &mut Self {
+        self.access
+            .get_or_insert_with(Access::default)
+            .writes
+            .push(TypeId::of::<T>());
+        self
+    }
+
+    fn conflicts(&self, other: &SystemConfig) -> bool {
+        match (&self.access, &other.access) {
+            (Some(a), Some(b)) => a.conflicts(b),
+            _ => true,
+        }
+    }
+
+    fn should_run(&mut self, world: &World) -> bool {
+        self.conditions.iter_mut().all(|condition| condition(world))
+    }
+
+    fn run(&mut self, world: &World) {
+        (self.system)(world, &mut self.commands);
+    }
+}
+
+/// 执行一个阶段中的系统的方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Executor {
+    SingleThreaded,
+    /// 不冲突的系统在线程池中并行执行, wasm32 上没有
+    #[cfg(not(target_arch = "wasm32"))]
+    Parallel,
+}
+
+impl Default for Executor {
+    /// wasm32 上单线程
+    #[cfg(target_arch = "wasm32")]
+    fn default() -> Self {
+        Executor::SingleThreaded
+    }
+
+    /// 其他平台并行
+    #[cfg(not(target_arch = "wasm32"))]
+    fn default() -> Self {
+        Executor::Parallel
+    }
+}
+
+#[derive(Default)]
+struct StageSystems {
+    systems: Vec<SystemConfig>,
+    /// 排好序的批次, 同一批内的系统可以并行, 添加系统后重新计算
+    batches: Option<Vec<Vec<usize>>>,
+}
+
+impl StageSystems {
+    /// 按顺序约束排序, 再把不冲突的系统分到同一批
+    fn batches(&mut self, stage: Stage) -> &[Vec<usize>] {
+        let systems = &self.systems;
+        self.batches.get_or_insert_with(|| {
+            let predecessors = predecessors(stage, systems);
+            let order = sort(stage, systems, &predecessors);
+            batch(systems, &predecessors, &order)
+        })
+    }
+}
+
+/// 每个系统必须在哪些系统之后执行
+fn predecessors(stage: Stage, systems: &[SystemConfig]) -> Vec<Vec<usize>> {
+    let named = |name: &'static str, by: &SystemConfig| -> Vec<usize> {
+        let found: Vec<usize> = (0..systems.len())
+            .filter(|&i| systems[i].name == name)
+            .collect();
+        assert!(
+            !found.is_empty(),
+            "system `{}` is ordered against unknown system `{name}` in {stage:?}",
+            by.name
+        );
+        found
+    };
+    let mut predecessors = vec![Vec::new(); systems.len()];
+    for (i, system) in systems.iter().enumerate() {
+        for &name in &system.after {
+            predecessors[i].extend(named(name, system));
+        }
+        for &name in &system.before {
+            for j in named(name, system) {
+                predecessors[j].push(i);
+            }
+        }
+    }
+    predecessors
+}
+
+/// 满足顺序约束的拓扑序, 没有约束的系统保持注册顺序
+fn sort(stage: Stage, systems: &[SystemConfig], predecessors: &[Vec<usize>]) -> Vec<usize> {
+    let mut done = vec![false; systems.len()];
+    let mut order = Vec::with_capacity(systems.len());
+    while order.len() < systems.len() {
+        let next =
+            (0..systems.len()).find(|&i| !done[i] && predecessors[i].iter().all(|&p| done[p]));
+        let Some(next) = next else {
+            let cycle: Vec<_> = (0..systems.len())
+                .filter(|&i| !done[i])
+                .map(|i| systems[i].name)
+                .collect();
+            panic!("ordering cycle between systems {cycle:?} in {stage:?}");
+        };
+        done[next] = true;
+        order.push(next);
+    }
+    order
+}
+
+/// 把排好序的系统分批, 系统在前面的批次执行完它的前驱后才能进入一批,
+/// 并且只有和前面还没执行的系统都不冲突时才能提前
+fn batch(
+    systems: &[SystemConfig],
+    predecessors: &[Vec<usize>],
+    order: &[usize],
+) -> Vec<Vec<usize>> {
+    let mut done = vec![false; systems.len()];
+    let mut pending = order.to_vec();
+    let mut batches = Vec::new();
+    while !pending.is_empty() {
+        let mut batch: Vec<usize> = Vec::new();
+        let mut blocked: Vec<usize> = Vec::new();
+        for &i in &pending {
+            let ready = predecessors[i].iter().all(|&p| done[p]);
+            let free = batch
+                .iter()
+                .chain(&blocked)
+                .all(|&j| !systems[i].conflicts(&systems[j]));
+            if ready && free {
+                batch.push(i);
+            } else {
+                blocked.push(i);
+            }
+        }
+        for &i in &batch {
+            done[i] = true;
+        }
+        pending = blocked;
+        batches.push(batch);
+    }
+    batches
+}
+
+/// 按阶段组织的系统
+#[derive(Default)]
+pub struct Schedule {
+    stages: [StageSystems; 5],
+    executor: Executor,
+}
+
+impl Schedule {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_executor(executor: Executor) -> Self {
+        Self {
+            executor,
+            ..Self::default()
+        }
+    }
+
+    pub fn executor(&self) -> Executor {
+        self.executor
+    }
+
+    /// 注册系统, 返回的配置可以继续设置顺序、条件和访问
+    pub fn add_system(
+        &mut self,
+        stage: Stage,
+        name: &'static str,
+        system: impl FnMut(&World, &mut CommandBuffer) + Send + 'static,
+    ) -> &mut SystemConfig {
+        let stage = &mut self.stages[stage as usize];
+        stage.batches = None;
+        stage.systems.push(SystemConfig {
+            name,
+            system: Box::new(system),
+            conditions: Vec::new(),
+            before: Vec::new(),
+            after: Vec::new(),
+            access: None,
+            commands: CommandBuffer::new(),
+        });
+        stage.systems.last_mut().unwrap()
+    }
+
+    /// 执行一帧: 按顺序执行各个阶段, 固定步长阶段按累积的时间执行若干次
+    pub fn run(&mut self, world: &mut World, delta: f32) {
+        if !world.contains_resource::<FixedTime>() {
+            world.insert_resource(FixedTime::default());
+        }
+        self.run_stage(Stage::PreUpdate, world);
+        let steps = world.resource_mut::<FixedTime>().accumulate(delta);
+        for _ in 0..steps {
+            self.run_stage(Stage::FixedUpdate, world);
+        }
+        self.run_stage(Stage::Update, world);
+        self.run_stage(Stage::PostUpdate, world);
+        self.run_stage(Stage::Render, world);
+    }
+
+    /// 执行一个阶段, 然后应用其中系统记录的命令
+    pub fn run_stage(&mut self, stage: Stage, world: &mut World) {
+        // 绘图 API 通常只能在主线程调用
+        let executor = match stage {
+            Stage::Render => Executor::SingleThreaded,
+            _ => self.executor,
+        };
+        let stage_systems = &mut self.stages[stage as usize];
+        stage_systems.batches(stage);
+        let StageSystems { systems, batches } = stage_systems;
+        let batches = batches.as_deref().unwrap_or_default();
+        world.flush();
+        match executor {
+            Executor::SingleThreaded => {
+                for &i in batches.iter().flatten() {
+                    if systems[i].should_run(world) {
+                        systems[i].run(world);
+                    }
+                }
+            }
+            #[cfg(not(target_arch = "wasm32"))]
+            Executor::Parallel => {
+                for batch in batches {
+                    run_parallel(systems, batch, world);
+                }
+            }
+        }
+        for &i in batches.iter().flatten() {
+            systems[i].commands.apply(world);
+        }
+    }
+}
+
+/// 并行执行一批系统, 只有一个要运行时直接在当前线程执行
+#[cfg(not(target_arch = "wasm32"))]
+fn run_parallel(systems: &mut [SystemConfig], batch: &[usize], world: &World) {
+    use rayon::prelude::*;
+
+    let mut running: Vec<&mut SystemConfig> = systems
+        .iter_mut()
+        .enumerate()
+        .filter(|(i, _)| batch.contains(i))
+        .map(|(_, system)| system)
+        .filter_map(|system| system.should_run(world).then_some(system))
+        .collect();
+    match running.as_mut_slice() {
+        [] => {}
+        [system] => system.run(world),
+        running => running.par_iter_mut().for_each(|system| system.run(world)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+
+    /// 记录系统执行顺序的系统
+    fn log(
+        log: &Arc<Mutex<Vec<&'static str>>>,
+        name: &'static str,
+    ) -> impl FnMut(&World, &mut CommandBuffer) + Send + 'static {
+        let log = log.clone();
+        move |_, _| log.lock().unwrap().push(name)
+    }
+
+    #[test]
+    fn stages_run_in_order_and_respect_constraints() {
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let mut schedule = Schedule::with_executor(Executor::SingleThreaded);
+        schedule.add_system(Stage::Render, "draw", log(&order, "draw"));
+        schedule
+            .add_system(Stage::Update, "ai", log(&order, "ai"))
+            .after("physics");
+        schedule.add_system(Stage::Update, "physics", log(&order, "physics"));
+        schedule
+            .add_system(Stage::Update, "input", log(&order, "input"))
+            .before("physics");
+        schedule.add_system(Stage::PreUpdate, "poll", log(&order, "poll"));
+
+        schedule.run(&mut World::new(), 0.0);
+        assert_eq!(
+            *order.lock().unwrap(),
+            ["poll", "input", "physics", "ai", "draw"]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "ordering cycle")]
+    fn cycles_are_reported() {
+        let mut schedule = Schedule::new();
+        schedule
+            .add_system(Stage::Update, "a", |_, _| {})
+            .after("b");
+        schedule
+            .add_system(Stage::Update, "b", |_, _| {})
+            .after("a");
+        schedule.run(&mut World::new(), 0.0);
+    }
+
+    #[test]
+    fn fixed_update_follows_the_accumulator() {
+        #[derive(Default)]
+        struct Ticks(u32);
+
+        let mut world = World::new();
+        world.insert_resource(Ticks::default());
+        world.insert_resource(FixedTime::new(0.1).with_max_steps(3));
+        let mut schedule = Schedule::new();
+        schedule
+            .add_system(Stage::FixedUpdate, "tick", |world, _| {
+                world.resource_mut::<Ticks>().0 += 1
+            })
+            .writes::<Ticks>();
+
+        schedule.run(&mut world, 0.05);
+        assert_eq!(world.resource::<Ticks>().0, 0);
+        schedule.run(&mut world, 0.16);
+        assert_eq!(world.resource::<Ticks>().0, 2);
+        assert!((world.resource::<FixedTime>().alpha() - 0.1).abs() < 1e-4);
+        // 卡顿时最多追 3 步, 剩下的时间丢弃
+        schedule.run(&mut world, 1.0);
+        assert_eq!(world.resource::<Ticks>().0, 5);
+        assert!(world.resource::<FixedTime>().alpha() < 1.0);
+    }
+
+    #[test]
+    fn run_conditions_gate_systems() {
+        #[derive(PartialEq)]
+        enum GameState {
+            Playing,
+            Paused,
+        }
+        struct Score(u32);
+
+        let mut world = World::new();
+        world.insert_resource(GameState::Playing);
+        world.insert_resource(Score(0));
+        let mut schedule = Schedule::new();
+        schedule
+            .add_system(Stage::Update, "score", |world, _| {
+                world.resource_mut::<Score>().0 += 1
+            })
+            .run_if(in_state(GameState::Playing))
+            .run_if(resource_exists::<Score>());
+
+        schedule.run(&mut world, 0.0);
+        world.insert_resource(GameState::Paused);
+        schedule.run(&mut world, 0.0);
+        assert_eq!(world.resource::<Score>().0, 1);
+    }
+
+    #[test]
+    fn commands_are_applied_after_the_stage() {
+        struct Bullet;
+
+        let mut world = World::new();
+        let mut schedule = Schedule::new();
+        schedule.add_system(Stage::Update, "shoot", |world, commands| {
+            commands.spawn(world, (Bullet,));
+        });
+        schedule
+            .add_system(Stage::Update, "count", |world, _| {
+                // 同一阶段中还看不到新的实体
+                assert_eq!(world.query::<&Bullet>().count(), 0);
+            })
+            .after("shoot")
+            .run_if(|world| world.is_empty());
+        schedule.run(&mut world, 0.0);
+        schedule.run(&mut world, 0.0);
+        assert_eq!(world.query::<&Bullet>().count(), 2);
+    }
+
+    #[test]
+    fn batches_group_systems_without_conflicts() {
+        struct A;
+        struct B;
+        let mut schedule = Schedule::new();
+        schedule
+            .add_system(Stage::Update, "write_a", |_, _| {})
+            .writes::<A>();
+        schedule
+            .add_system(Stage::Update, "read_b", |_, _| {})
+            .reads::<B>();
+        schedule
+            .add_system(Stage::Update, "read_a", |_, _| {})
+            .reads::<A>();
+        schedule
+            .add_system(Stage::Update, "after_b", |_, _| {})
+            .reads::<B>()
+            .after("read_b");
+        schedule.add_system(Stage::Update, "exclusive", |_, _| {});
+        schedule
+            .add_system(Stage::Update, "last", |_, _| {})
+            .reads::<A>();
+
+        let stage = &mut schedule.stages[Stage::Update as usize];
+        let batches = stage.batches(Stage::Update).to_vec();
+        let names: Vec<Vec<_>> = batches
+            .iter()
+            .map(|batch| batch.iter().map(|&i| stage.systems[i].name).collect())
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                vec!["write_a", "read_b"],
+                vec!["read_a", "after_b"],
+                vec!["exclusive"],
+                vec!["last"],
+            ]
+        );
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn parallel_executor_matches_single_threaded() {
+        struct Position(i32);
+        struct Velocity(i32);
+        struct Spin(i32);
+        struct Total(i64);
+
+        let run = |executor| {
+            let mut world = World::new();
+            for i in 0..1000 {
+                world.spawn((Position(i), Velocity(i % 7), Spin(i)));
+            }
+            world.insert_resource(Total(0));
+            let mut schedule = Schedule::with_executor(executor);
+            schedule
+                .add_system(Stage::Update, "move", |world, _| {
+                    world
+                        .query::<(&mut Position, &Velocity)>()
+                        .for_each(|(p, v)| p.0 += v.0);
+                })
+                .writes::<Position>()
+                .reads::<Velocity>();
+            schedule
+                .add_system(Stage::Update, "accelerate", |world, _| {
+                    world.query::<&mut Velocity>().for_each(|v| v.0 += 1);
+                })
+                .writes::<Velocity>();
+            // 和 move 不冲突, 并行执行
+            schedule
+                .add_system(Stage::Update, "spin", |world, _| {
+                    world
+                        .query::<&mut Spin>()
+                        .for_each(|s| s.0 = (s.0 * 3 + 1) % 360);
+                })
+                .writes::<Spin>();
+            schedule
+                .add_system(Stage::Update, "sum", |world, _| {
+                    let mut total = 0;
+                    world
+                        .query::<(&Position, &Spin)>()
+                        .for_each(|(p, s)| total += (p.0 + s.0) as i64);
+                    world.resource_mut::<Total>().0 += total;
+                })
+                .reads::<Position>()
+                .reads::<Spin>()
+                .writes::<Total>();
+            for _ in 0..10 {
+                schedule.run(&mut world, 1.0 / 60.0);
+            }
+            world.resource::<Total>().0
+        };
+        assert_eq!(run(Executor::Parallel), run(Executor::SingleThreaded));
+    }
+}
@@ -0,0 +1,137 @@
+//! 稀疏集组件存储
+//!
+//! 每种组件一列, 值连续存放在 `Vec<T>` 中, 稀疏数组按实体索引找到值的位置.
+//! 位置信息放在借用检查外面, 查询借用组件数据时过滤器仍然可以检查实体有没有组件.
+
+use crate::cell::{AtomicRefCell, Ref, RefMut};
+use crate::{Component, Entity};
+use std::any::Any;
+
+/// 稀疏数组中的空位
+const EMPTY: u32 = u32::MAX;
+
+/// 擦除类型的组件数组
+trait ErasedVec: Send + Sync {
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+    fn swap_remove_drop(&mut self, index: usize);
+}
+
+impl<T: Component> ErasedVec for Vec<T> {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn swap_remove_drop(&mut self, index: usize) {
+        self.swap_remove(index);
+    }
+}
+
+/// 一种组件的存储, 只在查询的关联类型中出现, 外部无法使用
+pub struct Column {
+    /// 实体索引到 `entities` 中位置的映射
+    sparse: Vec<u32>,
+    /// `data[i]` 属于哪个实体
+    entities: Vec<Entity>,
+    data: AtomicRefCell<Box<dyn ErasedVec>>,
+}
+
+impl Column {
+    pub(crate) fn new<T: Component>() -> Self {
+        Self {
+            sparse: Vec::new(),
+            entities: Vec::new(),
+            data: AtomicRefCell::new(Box::new(Vec::<T>::new())),
+        }
+    }
+
+    /// 拥有这个组件的实体, 和组件数据的顺序一致
+    pub(crate) fn entities(&self) -> &[Entity] {
+        &self.entities
+    }
+
+    /// 实体的组件在数组中的位置, 会检查世代数
+    pub(crate) fn dense_index(&self, entity: Entity) -> Option<usize> {
+        let dense = *self.sparse.get(entity.index() as usize)?;
+        if dense == EMPTY || self.entities[dense as usize] != entity {
+            return None;
+        }
+        Some(dense as usize)
+    }
+
+    pub(crate) fn contains(&self, entity: Entity) -> bool {
+        self.dense_index(entity).is_some()
+    }
+
+    /// 借用组件数据, 已经被可变借用时 panic
+    pub(crate) fn borrow<T: Component>(&self) -> Ref<'_, Vec<T>> {
+        Ref::map(self.data.borrow(), |data| {
+            data.as_any()
+                .downcast_ref()
+                .expect("component type mismatch")
+        })
+    }
+
+    /// 可变借用组件数据, 已经被借用时 panic
+    pub(crate) fn borrow_mut<T: Component>(&self) -> RefMut<'_, Vec<T>> {
+        RefMut::map(self.data.borrow_mut(), |data| {
+            data.as_any_mut()
+                .downcast_mut()
+                .expect("component type mismatch")
+        })
+    }
+
+    fn data_mut<T: Component>(&mut self) -> &mut Vec<T> {
+        self.data
+            .get_mut()
+            .as_any_mut()
+            .downcast_mut()
+            .expect("component type mismatch")
+    }
+
+    /// 添加或替换组件, 返回旧的值
+    pub(crate) fn insert<T: Component>(&mut self, entity: Entity, value: T) -> Option<T> {
+        if let Some(dense) = self.dense_index(entity) {
+            return Some(std::mem::replace(&mut self.data_mut()[dense], value));
+        }
+        let index = entity.index() as usize;
+        if self.sparse.len() <= index {
+            self.sparse.resize(index + 1, EMPTY);
+        }
+        self.sparse[index] = self.entities.len() as u32;
+        self.entities.push(entity);
+        self.data_mut().push(value);
+        None
+    }
+
+    pub(crate) fn remove<T: Component>(&mut self, entity: Entity) -> Option<T> {
+        let dense = self.unlink(entity)?;
+        Some(self.data_mut().swap_remove(dense))
+    }
+
+    /// 不知道组件类型时删除并丢弃组件
+    pub(crate) fn remove_erased(&mut self, entity: Entity) -> bool {
+        match self.unlink(entity) {
+            Some(dense) => {
+                self.data.get_mut().swap_remove_drop(dense);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 从位置信息中删除实体, 最后一个实体移到它的位置, 数据需要调用方同样处理
+    fn unlink(&mut self, entity: Entity) -> Option<usize> {
+        let dense = self.dense_index(entity)?;
+        self.sparse[entity.index() as usize] = EMPTY;
+        self.entities.swap_remove(dense);
+        if let Some(moved) = self.entities.get(dense) {
+            self.sparse[moved.index() as usize] = dense as u32;
+        }
+        Some(dense)
+    }
+}
@@ -0,0 +1,51 @@
+//! 时间和窗口资源
+
+/// 帧时间, 每帧由 [`App::update`](crate::App::update) 更新
+#[derive(Debug, Default, Clone)]
+pub struct Time {
+    delta: f32,
+    elapsed: f64,
+    frame: u64,
+}
+
+impl Time {
+    /// 上一帧的时长, 单位秒
+    pub fn delta(&self) -> f32 {
+        self.delta
+    }
+
+    /// 从第一帧开始经过的时间
+    pub fn elapsed(&self) -> f64 {
+        self.elapsed
+    }
+
+    /// 已经开始的帧数, 第一帧为 1
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+
+    pub(crate) fn advance(&mut self, delta: f32) {
+        self.delta = delta;
+        self.elapsed += delta as f64;
+        self.frame += 1;
+    }
+}
+
+/// 窗口大小, 单位是逻辑像素
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WindowSize {
+    pub width: f32,
+    pub height: f32,
+    /// 物理像素和逻辑像素之比
+    pub scale_factor: f32,
+}
+
+impl Default for WindowSize {
+    fn default() -> Self {
+        Self {
+            width: 800.0,
+            height: 600.0,
+            scale_factor: 1.0,
+        }
+    }
+}
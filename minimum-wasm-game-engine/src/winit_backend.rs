@@ -0,0 +1,226 @@
+//! winit 后端
+//!
+//! 只负责窗口、输入和主循环, 窗口作为 [`WinitWindow`] 资源交给渲染插件创建 surface.
+//! wasm32 上没有 `std::time::Instant`, 只在原生平台可用.
+
+use std::sync::Arc;
+use std::time::Instant;
+
+use winit::application::ApplicationHandler;
+use winit::dpi::LogicalSize;
+use winit::event::{ElementState, WindowEvent};
+use winit::event_loop::{ActiveEventLoop, ControlFlow, EventLoop};
+use winit::keyboard::{KeyCode, PhysicalKey};
+use winit::window::{Window, WindowId};
+
+use crate::{App, Backend, Input, Key, MouseButton, WindowSize};
+
+/// winit 创建的窗口
+#[derive(Clone)]
+pub struct WinitWindow(pub Arc<Window>);
+
+pub struct Winit {
+    title: String,
+    size: (f32, f32),
+}
+
+impl Winit {
+    pub fn new(title: &str) -> Self {
+        Self {
+            title: title.to_owned(),
+            size: (800.0, 600.0),
+        }
+    }
+
+    pub fn with_size(mut self, width: f32, height: f32) -> Self {
+        self.size = (width, height);
+        self
+    }
+}
+
+impl Backend for Winit {
+    fn run(self, app: App) {
+        let event_loop = EventLoop::new().expect("failed to create event loop");
+        event_loop.set_control_flow(ControlFlow::Poll);
+        let mut runner = Runner {
+            backend: self,
+            app,
+            window: None,
+            last_frame: None,
+        };
+        event_loop.run_app(&mut runner).expect("event loop error");
+    }
+}
+
+struct Runner {
+    backend: Winit,
+    app: App,
+    window: Option<Arc<Window>>,
+    last_frame: Option<Instant>,
+}
+
+impl Runner {
+    fn set_size(&mut self, window: &Window) {
+        let scale_factor = window.scale_factor();
+        let size = window.inner_size().to_logical::<f32>(scale_factor);
+        self.app.world.insert_resource(WindowSize {
+            width: size.width,
+            height: size.height,
+            scale_factor: scale_factor as f32,
+        });
+    }
+
+    fn frame(&mut self, event_loop: &ActiveEventLoop) {
+        let now = Instant::now();
+        let delta = self
+            .last_frame
+            .map_or(0.0, |last| (now - last).as_secs_f32());
+        self.last_frame = Some(now);
+        self.app.update(delta);
+        if self.app.should_exit() {
+            event_loop.exit();
+        }
+    }
+
+    fn input(&self) -> crate::RefMut<'_, Input> {
+        self.app.world.resource_mut::<Input>()
+    }
+}
+
+impl ApplicationHandler for Runner {
+    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+        if self.window.is_some() {
+            return;
+        }
+        let (width, height) = self.backend.size;
+        let attributes = Window::default_attributes()
+            .with_title(&self.backend.title)
+            .with_inner_size(LogicalSize::new(width, height));
+        let window = Arc::new(
+            event_loop
+                .create_window(attributes)
+                .expect("failed to create window"),
+        );
+        self.set_size(&window);
+        self.app.world.insert_resource(WinitWindow(window.clone()));
+        self.window = Some(window);
+    }
+
+    fn window_event(&mut self, event_loop: &ActiveEventLoop, _id: WindowId, event: WindowEvent) {
+        match event {
+            WindowEvent::CloseRequested => event_loop.exit(),
+            WindowEvent::Resized(_) | WindowEvent::ScaleFactorChanged { .. } => {
+                if let Some(window) = self.window.clone() {
+                    self.set_size(&window);
+                }
+            }
+            WindowEvent::KeyboardInput { event, .. } => {
+                let PhysicalKey::Code(code) = event.physical_key else {
+                    return;
+                };
+                let Some(key) = key(code) else {
+                    return;
+                };
+                match event.state {
+                    ElementState::Pressed => self.input().press_key(key),
+                    ElementState::Released => self.input().release_key(key),
+                }
+            }
+            WindowEvent::MouseInput { state, button, .. } => {
+                let button = match button {
+                    winit::event::MouseButton::Left => MouseButton::Left,
+                    winit::event::MouseButton::Right => MouseButton::Right,
+                    winit::event::MouseButton::Middle => MouseButton::Middle,
+                    _ => return,
+                };
+                match state {
+                    ElementState::Pressed => self.input().press_mouse(button),
+                    ElementState::Released => self.input().release_mouse(button),
+                }
+            }
+            WindowEvent::CursorMoved { position, .. } => {
+                let scale_factor = self.app.world.resource::<WindowSize>().scale_factor as f64;
+                let position = position.to_logical::<f32>(scale_factor);
+                self.input().set_mouse_position((position.x, position.y));
+            }
+            WindowEvent::RedrawRequested => self.frame(event_loop),
+            _ => {}
+        }
+    }
+
+    fn about_to_wait(&mut self, _event_loop: &ActiveEventLoop) {
+        if let Some(window) = &self.window {
+            window.request_redraw();
+        }
+    }
+}
+
+fn key(code: KeyCode) -> Option<Key> {
+    use KeyCode as C;
+    Some(match code {
+        C::KeyA => Key::A,
+        C::KeyB => Key::B,
+        C::KeyC => Key::C,
+        C::KeyD => Key::D,
+        C::KeyE => Key::E,
+        C::KeyF => Key::F,
+        C::KeyG => Key::G,
+        C::KeyH => Key::H,
+        C::KeyI => Key::I,
+        C::KeyJ => Key::J,
+        C::KeyK => Key::K,
+        C::KeyL => Key::L,
+        C::KeyM => Key::M,
+        C::KeyN => Key::N,
+        C::KeyO => Key::O,
+        C::KeyP => Key::P,
+        C::KeyQ => Key::Q,
+        C::KeyR => Key::R,
+        C::KeyS => Key::S,
+        C::KeyT => Key::T,
+        C::KeyU => Key::U,
+        C::KeyV => Key::V,
+        C::KeyW => Key::W,
+        C::KeyX => Key::X,
+        C::KeyY => Key::Y,
+        C::KeyZ => Key::Z,
+        C::Digit0 => Key::Key0,
+        C::Digit1 => Key::Key1,
+        C::Digit2 => Key::Key2,
+        C::Digit3 => Key::Key3,
+        C::Digit4 => Key::Key4,
+        C::Digit5 => Key::Key5,
+        C::Digit6 => Key::Key6,
+        C::Digit7 => Key::Key7,
+        C::Digit8 => Key::Key8,
+        C::Digit9 => Key::Key9,
+        C::ArrowLeft => Key::Left,
+        C::ArrowRight => Key::Right,
+        C::ArrowUp => Key::Up,
+        C::ArrowDown => Key::Down,
+        C::Space => Key::Space,
+        C::Enter => Key::Enter,
+        C::Escape => Key::Escape,
+        C::Tab => Key::Tab,
+        C::Backspace => Key::Backspace,
+        C::ShiftLeft => Key::LeftShift,
+        C::ShiftRight => Key::RightShift,
+        C::ControlLeft => Key::LeftControl,
+        C::ControlRight => Key::RightControl,
+        C::AltLeft => Key::LeftAlt,
+        C::AltRight => Key::RightAlt,
+        C::F1 => Key::F1,
+        C::F2 => Key::F2,
+        C::F3 => Key::F3,
+        C::F4 => Key::F4,
+        C::F5 => Key::F5,
+        C::F6 => Key::F6,
+        C::F7 => Key::F7,
+        C::F8 => Key::F8,
+        C::F9 => Key::F9,
+        C::F10 => Key::F10,
+        C::F11 => Key::F11,
+        C::F12 => Key::F12,
+        _ => return None,
+    })
+}
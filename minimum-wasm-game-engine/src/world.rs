@@ -0,0 +1,262 @@
+//! 世界: 实体、组件和资源
+//!
+//! 组件列和资源在运行时做借用检查, `&World` 可以交给并行执行的系统,
+//! 在没有线程的 wasm32 上也一样工作.
+
+use std::any::{Any, TypeId, type_name};
+use std::collections::HashMap;
+
+use pool::ConcurrentPool;
+
+use crate::cell::AtomicRefCell;
+use crate::storage::Column;
+use crate::{Entity, Query, QueryData, QueryFilter, Ref, RefMut};
+
+/// 可以作为组件的类型, 所有可以在线程间共享的类型都自动实现
+pub trait Component: Send + Sync + 'static {}
+
+impl<T: Send + Sync + 'static> Component for T {}
+
+/// 可以作为资源的类型, 所有可以在线程间共享的类型都自动实现
+pub trait Resource: Send + Sync + 'static {}
+
+impl<T: Send + Sync + 'static> Resource for T {}
+
+#[derive(Default)]
+pub struct World {
+    /// 实体的分配器, 可以通过 `&self` 预留实体, 在 [`World::flush`] 时生效
+    entities: ConcurrentPool<()>,
+    columns: HashMap<TypeId, Column>,
+    resources: HashMap<TypeId, AtomicRefCell<Box<dyn Any + Send + Sync>>>,
+}
+
+impl World {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 存活的实体数量, 不包括还没生效的预留实体
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+
+    /// 创建带有一组组件的实体
+    pub fn spawn(&mut self, bundle: impl Bundle) -> Entity {
+        let entity = self.reserve_entity();
+        self.flush();
+        bundle.insert_into(self, entity);
+        entity
+    }
+
+    /// 预留一个实体, 在下一次 [`flush`](Self::flush) 后存活,
+    /// 命令缓冲用它在应用之前就返回实体
+    pub fn reserve_entity(&self) -> Entity {
+        Entity(self.entities.spawn(()))
+    }
+
+    /// 让预留的实体生效, 修改世界结构的方法会先调用它
+    pub fn flush(&mut self) {
+        self.entities.commit();
+    }
+
+    /// 销毁实体和它的所有组件
+    pub fn despawn(&mut self, entity: Entity) -> bool {
+        self.flush();
+        if self.entities.despawn(entity.0).is_none() {
+            return false;
+        }
+        for column in self.columns.values_mut() {
+            column.remove_erased(entity);
+        }
+        true
+    }
+
+    pub fn is_alive(&self, entity: Entity) -> bool {
+        self.entities.contains(entity.0)
+    }
+
+    /// 所有存活的实体
+    pub fn entities(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.entities.iter().map(|(handle, _)| Entity(handle))
+    }
+
+    /// 添加或替换组件, 返回旧的值; 实体不存在时 panic
+    pub fn insert<T: Component>(&mut self, entity: Entity, component: T) -> Option<T> {
+        self.flush();
+        assert!(self.is_alive(entity), "insert on dead entity {entity:?}");
+        self.columns
+            .entry(TypeId::of::<T>())
+            .or_insert_with(Column::new::<T>)
+            .insert(entity, component)
+    }
+
+    /// 给实体添加一组组件
+    pub fn insert_bundle(&mut self, entity: Entity, bundle: impl Bundle) {
+        self.flush();
+        assert!(self.is_alive(entity), "insert on dead entity {entity:?}");
+        bundle.insert_into(self, entity);
+    }
+
+    pub fn remove<T: Component>(&mut self, entity: Entity) -> Option<T> {
+        self.columns.get_mut(&TypeId::of::<T>())?.remove(entity)
+    }
+
+    pub fn has<T: Component>(&self, entity: Entity) -> bool {
+        self.column::<T>()
+            .is_some_and(|column| column.contains(entity))
+    }
+
+    /// 借用实体的组件, 组件所在的列正被可变借用时 panic
+    pub fn get<T: Component>(&self, entity: Entity) -> Option<Ref<'_, T>> {
+        let column = self.column::<T>()?;
+        let dense = column.dense_index(entity)?;
+        Some(Ref::map(column.borrow::<T>(), |data| &data[dense]))
+    }
+
+    /// 可变借用实体的组件, 组件所在的列正被借用时 panic
+    pub fn get_mut<T: Component>(&self, entity: Entity) -> Option<RefMut<'_, T>> {
+        let column = self.column::<T>()?;
+        let dense = column.dense_index(entity)?;
+        Some(RefMut::map(column.borrow_mut::<T>(), |data| {
+            &mut data[dense]
+        }))
+    }
+
+    /// 查询所有带有 `Q` 中组件的实体
+    pub fn query<Q: QueryData>(&self) -> Query<'_, Q> {
+        Query::new(self)
+    }
+
+    /// 同 [`query`](Self::query), 额外用 `F` 过滤实体, 例如 `(With<A>, Without<B>)`
+    pub fn query_filtered<Q: QueryData, F: QueryFilter>(&self) -> Query<'_, Q, F> {
+        Query::new(self)
+    }
+
+    pub(crate) fn column<T: Component>(&self) -> Option<&Column> {
+        self.columns.get(&TypeId::of::<T>())
+    }
+
+    /// 添加或替换资源, 返回旧的值
+    pub fn insert_resource<R: Resource>(&mut self, resource: R) -> Option<R> {
+        let old = self
+            .resources
+            .insert(TypeId::of::<R>(), AtomicRefCell::new(Box::new(resource)))?;
+        let old: Box<dyn Any> = old.into_inner();
+        old.downcast().ok().map(|old| *old)
+    }
+
+    pub fn remove_resource<R: Resource>(&mut self) -> Option<R> {
+        let old = self.resources.remove(&TypeId::of::<R>())?;
+        let old: Box<dyn Any> = old.into_inner();
+        old.downcast().ok().map(|old| *old)
+    }
+
+    pub fn contains_resource<R: Resource>(&self) -> bool {
+        self.resources.contains_key(&TypeId::of::<R>())
+    }
+
+    /// 借用资源, 不存在时返回 `None`
+    pub fn get_resource<R: Resource>(&self) -> Option<Ref<'_, R>> {
+        let cell = self.resources.get(&TypeId::of::<R>())?;
+        Some(Ref::map(cell.borrow(), |resource| {
+            resource.downcast_ref().unwrap()
+        }))
+    }
+
+    pub fn get_resource_mut<R: Resource>(&self) -> Option<RefMut<'_, R>> {
+        let cell = self.resources.get(&TypeId::of::<R>())?;
+        Some(RefMut::map(cell.borrow_mut(), |resource| {
+            resource.downcast_mut().unwrap()
+        }))
+    }
+
+    /// 借用资源, 不存在时 panic
+    #[track_caller]
+    pub fn resource<R: Resource>(&self) -> Ref<'_, R> {
+        self.get_resource()
+            .unwrap_or_else(|| panic!("missing resource {}", type_name::<R>()))
+    }
+
+    #[track_caller]
+    pub fn resource_mut<R: Resource>(&self) -> RefMut<'_, R> {
+        self.get_resource_mut()
+            .unwrap_or_else(|| panic!("missing resource {}", type_name::<R>()))
+    }
+}
+
+/// 一组可以一起添加到实体上的组件, 由最多 8 个组件的元组实现
+pub trait Bundle: Send + 'static {
+    fn insert_into(self, world: &mut World, entity: Entity);
+}
+
+macro_rules! impl_bundle {
+    ($($name:ident),*) => {
+        impl<$($name: Component),*> Bundle for ($($name,)*) {
+            #[allow(non_snake_case, unused_variables)]
+            fn insert_into(self, world: &mut World, entity: Entity) {
+                let ($($name,)*) = self;
+                $(world.insert(entity, $name);)*
+            }
+        }
+    };
+}
+
+impl_bundle!();
+impl_bundle!(A);
+impl_bundle!(A, B);
+impl_bundle!(A, B, C);
+impl_bundle!(A, B, C, D);
+impl_bundle!(A, B, C, D, E);
+impl_bundle!(A, B, C, D, E, F);
+impl_bundle!(A, B, C, D, E, F, G);
+impl_bundle!(A, B, C, D, E, F, G, H);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct Health(i32);
+    #[derive(Debug, PartialEq)]
+    struct Name(&'static str);
+
+    #[test]
+    fn components_follow_their_entity() {
+        let mut world = World::new();
+        let a = world.spawn((Health(10), Name("a")));
+        let b = world.spawn((Health(20),));
+        assert_eq!(*world.get::<Health>(b).unwrap(), Health(20));
+        assert!(world.get::<Name>(b).is_none());
+
+        // 删除 a 后 b 的组件被移到前面, 仍然能找到
+        assert!(world.despawn(a));
+        assert!(!world.despawn(a));
+        assert_eq!(*world.get::<Health>(b).unwrap(), Health(20));
+        world.get_mut::<Health>(b).unwrap().0 -= 5;
+        assert_eq!(world.remove::<Health>(b), Some(Health(15)));
+        assert!(!world.has::<Health>(b));
+
+        // 复用了 a 的索引, 旧的实体不会访问到新实体的组件
+        let c = world.spawn((Name("c"),));
+        assert_eq!(c.index(), a.index());
+        assert!(world.get::<Name>(a).is_none());
+        assert_eq!(*world.get::<Name>(c).unwrap(), Name("c"));
+        assert_eq!(world.len(), 2);
+    }
+
+    #[test]
+    fn resources_are_stored_by_type() {
+        let mut world = World::new();
+        assert!(world.get_resource::<u32>().is_none());
+        world.insert_resource(1u32);
+        *world.resource_mut::<u32>() += 1;
+        assert_eq!(world.insert_resource(10u32), Some(2));
+        assert_eq!(*world.resource::<u32>(), 10);
+        assert_eq!(world.remove_resource::<u32>(), Some(10));
+        assert!(!world.contains_resource::<u32>());
+    }
+}
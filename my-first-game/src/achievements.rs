@@ -0,0 +1,272 @@
+use std::collections::BTreeSet;
+
+use serde::{Deserialize, Serialize};
+
+use crate::events::{GameEvent, Subscriber};
+use crate::shape::EnemySize;
+use crate::shop::{self, Upgrades};
+
+/// 累计击毁多少方块解锁 `Centurion`
+const CENTURION_KILLS: u32 = 100;
+/// 累计击毁多少大方块解锁 `BigGameHunter`
+const BIG_GAME_KILLS: u32 = 10;
+/// 单局存活多少秒解锁 `Survivor`
+const SURVIVOR_SECONDS: u32 = 300;
+
+/// 成就
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Achievement {
+    /// 击毁第一个方块
+    FirstBlood,
+    /// 累计击毁 100 个方块
+    Centurion,
+    /// 累计击毁 10 个大方块
+    BigGameHunter,
+    /// 单局存活 5 分钟
+    Survivor,
+    /// 一整波都没有被撞到
+    Untouchable,
+}
+
+impl Achievement {
+    pub const ALL: [Achievement; 5] = [
+        Achievement::FirstBlood,
+        Achievement::Centurion,
+        Achievement::BigGameHunter,
+        Achievement::Survivor,
+        Achievement::Untouchable,
+    ];
+
+    pub fn title(self) -> &'static str {
+        match self {
+            Achievement::FirstBlood => "第一滴血",
+            Achievement::Centurion => "百人斩",
+            Achievement::BigGameHunter => "大猎物",
+            Achievement::Survivor => "幸存者",
+            Achievement::Untouchable => "毫发无伤",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Achievement::FirstBlood => "击毁第一个方块",
+            Achievement::Centurion => "累计击毁 100 个方块",
+            Achievement::BigGameHunter => "累计击毁 10 个大方块",
+            Achievement::Survivor => "单局存活 5 分钟",
+            Achievement::Untouchable => "一整波都没有被撞到",
+        }
+    }
+}
+
+/// 跨局累计的统计数据
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Stats {
+    /// 游玩局数
+    pub runs: u32,
+    /// 按体型统计的击毁数: 小, 中, 大
+    pub kills: [u32; 3],
+    /// 发射的子弹数
+    pub shots_fired: u32,
+    /// 最长的一局(秒)
+    pub longest_run: f32,
+    /// 总游玩时间(秒)
+    pub play_time: f32,
+}
+
+impl Stats {
+    pub fn total_kills(&self) -> u32 {
+        self.kills.iter().sum()
+    }
+
+    /// 命中率, 每次击毁消耗一颗子弹
+    pub fn accuracy(&self) -> f32 {
+        if self.shots_fired == 0 {
+            0.0
+        } else {
+            self.total_kills() as f32 / self.shots_fired as f32
+        }
+    }
+}
+
+/// 玩家档案, 保存统计数据、已解锁的成就和商店升级
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Profile {
+    pub stats: Stats,
+    pub unlocked: BTreeSet<Achievement>,
+    /// 金币, 每局结束时按分数发放
+    pub coins: u32,
+    pub upgrades: Upgrades,
+    /// 当前这一波是否被撞到过
+    #[serde(skip)]
+    hit_this_wave: bool,
+    /// 还没有提示过的新成就
+    #[serde(skip)]
+    fresh: Vec<Achievement>,
+}
+
+impl Profile {
+    /// 档案在存档目录中的文件名
+    pub fn save_name(profile: &str) -> String {
+        format!("profile-{}", profile)
+    }
+
+    fn unlock(&mut self, achievement: Achievement) {
+        if self.unlocked.insert(achievement) {
+            self.fresh.push(achievement);
+        }
+    }
+
+    /// 取出上次调用以来新解锁的成就
+    pub fn take_unlocked(&mut self) -> Vec<Achievement> {
+        std::mem::take(&mut self.fresh)
+    }
+}
+
+impl Subscriber<GameEvent> for Profile {
+    fn on_event(&mut self, event: &GameEvent) {
+        match *event {
+            GameEvent::RunStarted => {
+                self.stats.runs += 1;
+                self.hit_this_wave = false;
+            }
+            GameEvent::BulletFired { count, .. } => self.stats.shots_fired += count as u32,
+            GameEvent::EnemyDestroyed { size, .. } => {
+                let size = EnemySize::from_size(size);
+                self.stats.kills[size as usize] += 1;
+                self.unlock(Achievement::FirstBlood);
+                if self.stats.total_kills() >= CENTURION_KILLS {
+                    self.unlock(Achievement::Centurion);
+                }
+                if self.stats.kills[EnemySize::Big as usize] >= BIG_GAME_KILLS {
+                    self.unlock(Achievement::BigGameHunter);
+                }
+            }
+            GameEvent::PlayerHit { .. } => self.hit_this_wave = true,
+            GameEvent::WaveStarted { wave } => {
+                if wave > 1 && !self.hit_this_wave {
+                    self.unlock(Achievement::Untouchable);
+                }
+                self.hit_this_wave = false;
+            }
+            GameEvent::Survived { seconds } => {
+                if seconds >= SURVIVOR_SECONDS {
+                    self.unlock(Achievement::Survivor);
+                }
+            }
+            GameEvent::GameOver { score, duration } => {
+                self.stats.longest_run = self.stats.longest_run.max(duration);
+                self.stats.play_time += duration;
+                self.coins += shop::coins_for(score);
+            }
+            GameEvent::PowerUpCollected { .. } | GameEvent::AchievementUnlocked(_) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::EventBus;
+
+    fn kill(size: f32) -> GameEvent {
+        GameEvent::EnemyDestroyed {
+            player: 0,
+            x: 0.0,
+            y: 0.0,
+            size,
+        }
+    }
+
+    fn replay(profile: &mut Profile, events: Vec<GameEvent>) {
+        let mut bus = EventBus::new();
+        for event in events {
+            bus.publish(event);
+        }
+        bus.dispatch(&mut [profile]);
+    }
+
+    #[test]
+    fn stats_accumulate_across_runs() {
+        let mut profile = Profile::default();
+        for _ in 0..2 {
+            replay(
+                &mut profile,
+                vec![
+                    GameEvent::RunStarted,
+                    GameEvent::BulletFired {
+                        player: 0,
+                        x: 0.0,
+                        y: 0.0,
+                        count: 3,
+                    },
+                    kill(20.0),
+                    kill(60.0),
+                    GameEvent::GameOver {
+                        score: 80,
+                        duration: 12.0,
+                    },
+                ],
+            );
+        }
+        let stats = &profile.stats;
+        assert_eq!(stats.runs, 2);
+        assert_eq!(stats.kills, [2, 0, 2]);
+        assert_eq!(stats.shots_fired, 6);
+        assert_eq!(stats.longest_run, 12.0);
+        assert_eq!(stats.play_time, 24.0);
+        assert_eq!(profile.coins, 16);
+        assert!((stats.accuracy() - 4.0 / 6.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn unlocks_are_reported_once() {
+        let mut profile = Profile::default();
+        replay(&mut profile, vec![kill(20.0), kill(20.0)]);
+        assert_eq!(profile.take_unlocked(), [Achievement::FirstBlood]);
+        replay(&mut profile, vec![kill(20.0)]);
+        assert!(profile.take_unlocked().is_empty());
+    }
+
+    #[test]
+    fn untouchable_requires_a_clean_wave() {
+        let mut profile = Profile::default();
+        replay(
+            &mut profile,
+            vec![
+                GameEvent::RunStarted,
+                GameEvent::WaveStarted { wave: 1 },
+                GameEvent::PlayerHit {
+                    player: 0,
+                    x: 0.0,
+                    y: 0.0,
+                    size: 20.0,
+                },
+                GameEvent::WaveStarted { wave: 2 },
+            ],
+        );
+        assert!(!profile.unlocked.contains(&Achievement::Untouchable));
+        replay(&mut profile, vec![GameEvent::WaveStarted { wave: 3 }]);
+        assert!(profile.unlocked.contains(&Achievement::Untouchable));
+    }
+
+    #[test]
+    fn lifetime_kills_unlock_centurion() {
+        let mut profile = Profile::default();
+        replay(&mut profile, (0..99).map(|_| kill(20.0)).collect());
+        assert!(!profile.unlocked.contains(&Achievement::Centurion));
+        replay(&mut profile, vec![kill(20.0)]);
+        assert!(profile.unlocked.contains(&Achievement::Centurion));
+    }
+
+    #[test]
+    fn profile_round_trips_through_ron() {
+        let mut profile = Profile::default();
+        replay(&mut profile, vec![GameEvent::RunStarted, kill(60.0)]);
+        let text = ron::to_string(&profile).unwrap();
+        let loaded: Profile = ron::from_str(&text).unwrap();
+        assert_eq!(loaded.stats, profile.stats);
+        assert_eq!(loaded.unlocked, profile.unlocked);
+    }
+}
@@ -0,0 +1,48 @@
+#[cfg(feature = "audio")]
+use macroquad::audio::{Sound, load_sound, play_sound_once};
+
+use crate::events::{GameEvent, Subscriber};
+
+/// 音效, 订阅射击和爆炸事件
+///
+/// 只有开启 `audio` 特性时才会加载和播放, 否则所有事件都被忽略
+pub struct Audio {
+    #[cfg(feature = "audio")]
+    laser: Option<Sound>,
+    #[cfg(feature = "audio")]
+    explosion: Option<Sound>,
+    /// 静音时忽略所有事件, 窗口失去焦点时开启
+    pub muted: bool,
+}
+
+impl Audio {
+    pub async fn load() -> Self {
+        Self {
+            #[cfg(feature = "audio")]
+            laser: load_sound("my-first-game/assets/laser.wav").await.ok(),
+            #[cfg(feature = "audio")]
+            explosion: load_sound("my-first-game/assets/explosion.wav").await.ok(),
+            muted: false,
+        }
+    }
+}
+
+impl Subscriber<GameEvent> for Audio {
+    #[cfg(feature = "audio")]
+    fn on_event(&mut self, event: &GameEvent) {
+        if self.muted {
+            return;
+        }
+        let sound = match event {
+            GameEvent::BulletFired { .. } => &self.laser,
+            GameEvent::EnemyDestroyed { .. } | GameEvent::PlayerHit { .. } => &self.explosion,
+            _ => return,
+        };
+        if let Some(sound) = sound {
+            play_sound_once(sound);
+        }
+    }
+
+    #[cfg(not(feature = "audio"))]
+    fn on_event(&mut self, _event: &GameEvent) {}
+}
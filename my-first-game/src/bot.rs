@@ -0,0 +1,120 @@
+//! 自动驾驶, 躲避方块并射击, 用于压力测试和调整难度
+
+use macroquad::prelude::*;
+
+use crate::canvas::{VIRTUAL_HEIGHT, VIRTUAL_WIDTH};
+use crate::sim::{Input, World};
+
+/// 预判时的采样时刻(秒)
+const SAMPLES: [f32; 6] = [0.05, 0.1, 0.2, 0.3, 0.45, 0.6];
+/// 平时停留的高度, 靠近屏幕底部留出躲避的时间
+const HOME_Y: f32 = VIRTUAL_HEIGHT * 0.8;
+
+/// 自动驾驶
+///
+/// 每帧尝试九个移动方向, 预判飞船和方块在接下来几个时刻的位置, 选择最安全的方向,
+/// 安全时移动到最低的方块下方射击
+#[derive(Debug, Clone, Copy)]
+pub struct Bot {
+    /// 和方块保持的额外距离
+    pub margin: f32,
+}
+
+impl Default for Bot {
+    fn default() -> Self {
+        Self { margin: 12.0 }
+    }
+}
+
+impl Bot {
+    /// 第 `index` 名玩家本帧的操作
+    pub fn input(&self, world: &World, index: usize) -> Input {
+        let player = &world.players[index];
+        let ship = &player.ship;
+        let pos = vec2(ship.x, ship.y);
+        let squares = || world.squares.iter().filter(|square| !square.collided);
+
+        // 最低的一个在飞船上方的方块作为目标
+        let target = squares()
+            .filter(|square| square.y < pos.y)
+            .max_by(|a, b| a.y.total_cmp(&b.y));
+
+        let mut best = (f32::MAX, Vec2::ZERO);
+        for x in [-1.0, 0.0, 1.0] {
+            for y in [-1.0, 0.0, 1.0] {
+                let direction = vec2(x, y);
+                let mut danger = 0.0;
+                for t in SAMPLES {
+                    let future = (pos + direction * ship.speed * t)
+                        .clamp(Vec2::ZERO, vec2(VIRTUAL_WIDTH, VIRTUAL_HEIGHT));
+                    for square in squares() {
+                        let center = vec2(square.x, square.y + square.speed * t);
+                        let reach = (ship.size + square.size) / 2.0 + self.margin;
+                        let gap = (future - center).abs().max_element();
+                        if gap < reach {
+                            // 越早撞上越危险
+                            danger += (reach - gap) / t;
+                        }
+                    }
+                }
+                let end = pos + direction * ship.speed * SAMPLES[SAMPLES.len() - 1];
+                let aim = target.map_or(0.0, |square| (end.x - square.x).abs());
+                let cost = danger * 100.0 + aim + (end.y - HOME_Y).abs() * 0.5;
+                if cost < best.0 {
+                    best = (cost, direction);
+                }
+            }
+        }
+
+        let fire = squares()
+            .any(|square| square.y < pos.y && (square.x - pos.x).abs() < square.size / 2.0 + 10.0);
+        Input {
+            direction: best.1,
+            fire,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::EventBus;
+    use crate::player::CoopSettings;
+    use crate::shop::Upgrades;
+
+    /// 用 `pilot` 玩一局, 返回存活时间
+    fn survival(seed: u64, pilot: impl Fn(&World) -> Input) -> f32 {
+        let mut world = World::new(1, CoopSettings::default(), &Upgrades::default(), seed);
+        let mut events = EventBus::new();
+        while world.run_time < 90.0 {
+            let input = pilot(&world);
+            if world
+                .step(1.0 / 60.0, &[input], None, &mut events)
+                .is_some()
+            {
+                break;
+            }
+            events.drain();
+        }
+        world.run_time
+    }
+
+    #[test]
+    fn bot_outlives_an_idle_player() {
+        let bot = Bot::default();
+        let (mut idle, mut piloted) = (0.0, 0.0);
+        for seed in 1..=3 {
+            idle += survival(seed, |_| Input::default());
+            piloted += survival(seed, |world| bot.input(world, 0));
+        }
+        assert!(piloted > idle * 2.0, "bot {piloted} idle {idle}");
+    }
+
+    #[test]
+    fn same_seed_and_inputs_replay_identically() {
+        let bot = Bot::default();
+        let a = survival(7, |world| bot.input(world, 0));
+        let b = survival(7, |world| bot.input(world, 0));
+        assert_eq!(a, b);
+    }
+}
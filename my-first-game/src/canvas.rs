@@ -0,0 +1,205 @@
+//! 固定分辨率的虚拟画布, 游戏逻辑和界面都使用虚拟坐标, 与窗口大小无关
+
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// 虚拟画布的宽度
+pub const VIRTUAL_WIDTH: f32 = 800.0;
+/// 虚拟画布的高度
+pub const VIRTUAL_HEIGHT: f32 = 600.0;
+
+/// 画布缩放到窗口的方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Scaling {
+    /// 等比缩放填满窗口, 多余部分留黑边
+    #[default]
+    Letterbox,
+    /// 只按整数倍缩放, 像素保持清晰, 窗口比画布小时退回等比缩放
+    Integer,
+}
+
+impl Scaling {
+    pub fn name(self) -> &'static str {
+        match self {
+            Scaling::Letterbox => "等比缩放",
+            Scaling::Integer => "整数倍缩放",
+        }
+    }
+}
+
+/// 画布在窗口中的位置和缩放
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Viewport {
+    /// 画布左上角在窗口中的位置
+    pub offset: Vec2,
+    pub scale: f32,
+}
+
+impl Viewport {
+    /// 把画布放进 `screen` 大小的窗口并居中
+    pub fn fit(screen: Vec2, scaling: Scaling) -> Self {
+        let fit = (screen.x / VIRTUAL_WIDTH).min(screen.y / VIRTUAL_HEIGHT);
+        let scale = match scaling {
+            Scaling::Integer if fit >= 1.0 => fit.floor(),
+            _ => fit,
+        };
+        let size = vec2(VIRTUAL_WIDTH, VIRTUAL_HEIGHT) * scale;
+        Self {
+            offset: ((screen - size) / 2.0).floor(),
+            scale,
+        }
+    }
+
+    /// 窗口坐标转换为虚拟坐标
+    pub fn to_virtual(self, screen: Vec2) -> Vec2 {
+        (screen - self.offset) / self.scale
+    }
+}
+
+/// 虚拟坐标是否落在画布内, 黑边上的点不算
+pub fn on_canvas(point: Vec2) -> bool {
+    (0.0..=VIRTUAL_WIDTH).contains(&point.x) && (0.0..=VIRTUAL_HEIGHT).contains(&point.y)
+}
+
+/// 界面元素到画布边缘的默认距离
+pub const MARGIN: f32 = 10.0;
+
+/// 界面元素的锚点
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Anchor {
+    TopLeft,
+    TopCenter,
+    TopRight,
+    BottomLeft,
+}
+
+impl Anchor {
+    /// 大小为 `size` 的元素贴着锚点摆放时左上角的虚拟坐标, `margin` 为到画布边缘的距离
+    pub fn place(self, size: Vec2, margin: f32) -> Vec2 {
+        match self {
+            Anchor::TopLeft => vec2(margin, margin),
+            Anchor::TopCenter => vec2((VIRTUAL_WIDTH - size.x) / 2.0, margin),
+            Anchor::TopRight => vec2(VIRTUAL_WIDTH - size.x - margin, margin),
+            Anchor::BottomLeft => vec2(margin, VIRTUAL_HEIGHT - size.y - margin),
+        }
+    }
+}
+
+/// 虚拟画布, 每帧先把所有内容画到渲染目标上, 再缩放到窗口
+pub struct Canvas {
+    target: RenderTarget,
+    camera: Camera2D,
+    viewport: Viewport,
+}
+
+impl Canvas {
+    pub fn new() -> Self {
+        let target = render_target(VIRTUAL_WIDTH as u32, VIRTUAL_HEIGHT as u32);
+        let mut camera =
+            Camera2D::from_display_rect(Rect::new(0.0, 0.0, VIRTUAL_WIDTH, VIRTUAL_HEIGHT));
+        camera.render_target = Some(target.clone());
+        Self {
+            target,
+            camera,
+            viewport: Viewport::fit(vec2(VIRTUAL_WIDTH, VIRTUAL_HEIGHT), Scaling::Letterbox),
+        }
+    }
+
+    /// 开始绘制虚拟画布, 之后的绘制都使用虚拟坐标
+    pub fn begin(&mut self, scaling: Scaling) {
+        self.viewport = Viewport::fit(vec2(screen_width(), screen_height()), scaling);
+        set_camera(&self.camera);
+    }
+
+    /// 结束绘制并把画布缩放到窗口
+    pub fn end(&self, scaling: Scaling) {
+        set_default_camera();
+        clear_background(BLACK);
+        self.target.texture.set_filter(match scaling {
+            Scaling::Letterbox => FilterMode::Linear,
+            Scaling::Integer => FilterMode::Nearest,
+        });
+        let Viewport { offset, scale } = self.viewport;
+        draw_texture_ex(
+            &self.target.texture,
+            offset.x,
+            offset.y,
+            WHITE,
+            DrawTextureParams {
+                dest_size: Some(vec2(VIRTUAL_WIDTH, VIRTUAL_HEIGHT) * scale),
+                // 渲染目标是上下颠倒的
+                flip_y: true,
+                ..Default::default()
+            },
+        );
+    }
+
+    /// 鼠标在虚拟画布中的位置
+    pub fn mouse_position(&self) -> Vec2 {
+        self.viewport.to_virtual(mouse_position().into())
+    }
+
+    /// 本帧是否在画布内点击, 浏览器里的触摸也会当作鼠标点击
+    pub fn tapped(&self) -> bool {
+        is_mouse_button_pressed(MouseButton::Left) && on_canvas(self.mouse_position())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn letterbox_fills_the_shorter_side() {
+        // 宽屏窗口, 左右留黑边
+        let viewport = Viewport::fit(vec2(1920.0, 1080.0), Scaling::Letterbox);
+        assert_eq!(viewport.scale, 1.8);
+        assert_eq!(viewport.offset, vec2(240.0, 0.0));
+    }
+
+    #[test]
+    fn integer_scaling_rounds_down() {
+        let viewport = Viewport::fit(vec2(1920.0, 1080.0), Scaling::Integer);
+        assert_eq!(viewport.scale, 1.0);
+        assert_eq!(viewport.offset, vec2(560.0, 240.0));
+        // 窗口比画布小时无法整数缩放
+        let small = Viewport::fit(vec2(400.0, 300.0), Scaling::Integer);
+        assert_eq!(small.scale, 0.5);
+    }
+
+    #[test]
+    fn screen_points_map_to_the_canvas() {
+        // 竖屏窗口, 缩放 1.25 倍, 上下各留 225 像素黑边
+        let viewport = Viewport::fit(vec2(1000.0, 1200.0), Scaling::Letterbox);
+        assert_eq!(
+            viewport.to_virtual(vec2(500.0, 600.0)),
+            vec2(VIRTUAL_WIDTH, VIRTUAL_HEIGHT) / 2.0
+        );
+        assert_eq!(
+            viewport.to_virtual(vec2(1000.0, 975.0)),
+            vec2(VIRTUAL_WIDTH, VIRTUAL_HEIGHT)
+        );
+        // 窗口左上角的黑边映射到画布外
+        assert!(viewport.to_virtual(Vec2::ZERO).y < 0.0);
+        assert!(!on_canvas(viewport.to_virtual(Vec2::ZERO)));
+        assert!(on_canvas(viewport.to_virtual(vec2(500.0, 600.0))));
+    }
+
+    #[test]
+    fn anchors_stay_inside_the_canvas() {
+        let size = vec2(100.0, 20.0);
+        assert_eq!(Anchor::TopLeft.place(size, 10.0), vec2(10.0, 10.0));
+        assert_eq!(
+            Anchor::TopRight.place(size, 10.0),
+            vec2(VIRTUAL_WIDTH - 110.0, 10.0)
+        );
+        assert_eq!(
+            Anchor::TopCenter.place(size, 0.0),
+            vec2((VIRTUAL_WIDTH - 100.0) / 2.0, 0.0)
+        );
+        assert_eq!(
+            Anchor::BottomLeft.place(size, MARGIN),
+            vec2(MARGIN, VIRTUAL_HEIGHT - 30.0)
+        );
+    }
+}
@@ -0,0 +1,414 @@
+//! 无窗口截图测试
+//!
+//! 用软件渲染把界面和菜单画到内存中的 RGBA 缓冲区, 再和 `tests/golden` 中的基准图比较,
+//! 在没有 GPU 的 CI 上也能发现画面变化。
+//! 星空背景是 `starfield-shader.glsl` 中的片段着色器, 只能在 GPU 上运行, 这里没有覆盖。
+//! 画面有意改动后用 `UPDATE_GOLDEN=1 cargo test -p my-first-game` 重新生成基准图。
+//!
+//! 文字使用 `assets/atari_games.ttf` 渲染, 这个字体没有中文, 中文显示为方框, 只用来检查布局
+
+use std::fs::{self, File};
+use std::io::BufWriter;
+use std::path::PathBuf;
+
+use macroquad::prelude::*;
+
+use crate::canvas::{VIRTUAL_HEIGHT, VIRTUAL_WIDTH};
+use crate::paint::Painter;
+
+/// 基准图所在的目录
+const GOLDEN_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/golden");
+/// 比较失败时实际画面的输出目录, CI 会把它作为构建产物上传
+const FAILED_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../target/golden-failed");
+/// 每个颜色通道允许的误差
+const TOLERANCE: u8 = 8;
+/// 允许超出误差的像素比例
+const MAX_MISMATCH: f32 = 0.001;
+
+/// 内存中的 RGBA 画面, 用软件光栅化实现 `Painter`, 不做抗锯齿
+pub struct Frame {
+    pub width: u32,
+    pub height: u32,
+    pixels: Vec<[u8; 4]>,
+    font: fontdue::Font,
+}
+
+impl Frame {
+    pub fn new(width: u32, height: u32, background: Color) -> Self {
+        let font = fontdue::Font::from_bytes(
+            include_bytes!("../assets/atari_games.ttf") as &[u8],
+            fontdue::FontSettings::default(),
+        )
+        .unwrap();
+        Self {
+            width,
+            height,
+            pixels: vec![background.into(); (width * height) as usize],
+            font,
+        }
+    }
+
+    pub fn pixel(&self, x: u32, y: u32) -> [u8; 4] {
+        self.pixels[(y * self.width + x) as usize]
+    }
+
+    pub fn set_pixel(&mut self, x: u32, y: u32, color: [u8; 4]) {
+        self.pixels[(y * self.width + x) as usize] = color;
+    }
+
+    /// 按透明度把颜色混合到一个像素上, `coverage` 为覆盖率
+    fn blend(&mut self, x: i32, y: i32, color: Color, coverage: f32) {
+        if x < 0 || y < 0 || x >= self.width as i32 || y >= self.height as i32 {
+            return;
+        }
+        let index = (y as u32 * self.width + x as u32) as usize;
+        let [r, g, b, a] = self.pixels[index];
+        let dst = Color::from_rgba(r, g, b, a);
+        let a = color.a * coverage;
+        let mix = |src: f32, dst: f32| src * a + dst * (1.0 - a);
+        self.pixels[index] = Color::new(
+            mix(color.r, dst.r),
+            mix(color.g, dst.g),
+            mix(color.b, dst.b),
+            a + dst.a * (1.0 - a),
+        )
+        .into();
+    }
+
+    /// 填充包围盒内像素中心满足 `inside` 的像素
+    fn fill(&mut self, min: Vec2, max: Vec2, color: Color, inside: impl Fn(Vec2) -> bool) {
+        for y in min.y.floor() as i32..max.y.ceil() as i32 {
+            for x in min.x.floor() as i32..max.x.ceil() as i32 {
+                if inside(vec2(x as f32 + 0.5, y as f32 + 0.5)) {
+                    self.blend(x, y, color, 1.0);
+                }
+            }
+        }
+    }
+
+    pub fn load_png(path: &PathBuf) -> Option<Self> {
+        let decoder = png::Decoder::new(File::open(path).ok()?);
+        let mut reader = decoder.read_info().ok()?;
+        let mut buffer = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut buffer).ok()?;
+        assert_eq!(info.color_type, png::ColorType::Rgba, "{:?}", path);
+        let mut frame = Frame::new(info.width, info.height, BLANK);
+        for (pixel, rgba) in frame.pixels.iter_mut().zip(buffer.chunks_exact(4)) {
+            pixel.copy_from_slice(rgba);
+        }
+        Some(frame)
+    }
+
+    pub fn save_png(&self, path: &PathBuf) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let mut encoder = png::Encoder::new(
+            BufWriter::new(File::create(path).unwrap()),
+            self.width,
+            self.height,
+        );
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let data: Vec<u8> = self.pixels.iter().flatten().copied().collect();
+        encoder
+            .write_header()
+            .unwrap()
+            .write_image_data(&data)
+            .unwrap();
+    }
+
+    /// 任一通道误差超过 `tolerance` 的像素数量, 尺寸不同时返回 `None`
+    pub fn mismatches(&self, other: &Frame, tolerance: u8) -> Option<usize> {
+        if (self.width, self.height) != (other.width, other.height) {
+            return None;
+        }
+        let count = self
+            .pixels
+            .iter()
+            .zip(&other.pixels)
+            .filter(|(a, b)| {
+                a.iter()
+                    .zip(b.iter())
+                    .any(|(a, b)| a.abs_diff(*b) > tolerance)
+            })
+            .count();
+        Some(count)
+    }
+}
+
+/// 正多边形的顶点, 和 `draw_poly` 的算法一致
+fn poly_vertices(center: Vec2, sides: u8, radius: f32, rotation: f32) -> Vec<Vec2> {
+    let rotation = rotation.to_radians();
+    (0..sides)
+        .map(|i| {
+            let angle = i as f32 / sides as f32 * std::f32::consts::TAU + rotation;
+            center + Vec2::from_angle(angle) * radius
+        })
+        .collect()
+}
+
+/// 点到线段的距离
+fn segment_distance(point: Vec2, a: Vec2, b: Vec2) -> f32 {
+    let ab = b - a;
+    let t = ((point - a).dot(ab) / ab.length_squared()).clamp(0.0, 1.0);
+    point.distance(a + ab * t)
+}
+
+impl Painter for Frame {
+    fn rect(&mut self, x: f32, y: f32, w: f32, h: f32, color: Color) {
+        self.fill(vec2(x, y), vec2(x + w, y + h), color, |_| true);
+    }
+
+    fn rect_lines(&mut self, x: f32, y: f32, w: f32, h: f32, thickness: f32, color: Color) {
+        let inner = Rect::new(
+            x + thickness,
+            y + thickness,
+            w - thickness * 2.0,
+            h - thickness * 2.0,
+        );
+        self.fill(vec2(x, y), vec2(x + w, y + h), color, |p| {
+            !inner.contains(p)
+        });
+    }
+
+    fn circle(&mut self, x: f32, y: f32, r: f32, color: Color) {
+        let center = vec2(x, y);
+        self.fill(center - r, center + r, color, |p| p.distance(center) <= r);
+    }
+
+    fn circle_lines(&mut self, x: f32, y: f32, r: f32, thickness: f32, color: Color) {
+        let center = vec2(x, y);
+        let outer = r + thickness / 2.0;
+        self.fill(center - outer, center + outer, color, |p| {
+            (p.distance(center) - r).abs() <= thickness / 2.0
+        });
+    }
+
+    fn poly(&mut self, x: f32, y: f32, sides: u8, radius: f32, rotation: f32, color: Color) {
+        let center = vec2(x, y);
+        let vertices = poly_vertices(center, sides, radius, rotation);
+        self.fill(center - radius, center + radius, color, |p| {
+            // 凸多边形: 点在每条边的同一侧
+            (0..vertices.len()).all(|i| {
+                let (a, b) = (vertices[i], vertices[(i + 1) % vertices.len()]);
+                (b - a).perp_dot(p - a) >= 0.0
+            })
+        });
+    }
+
+    fn poly_lines(
+        &mut self,
+        x: f32,
+        y: f32,
+        sides: u8,
+        radius: f32,
+        rotation: f32,
+        thickness: f32,
+        color: Color,
+    ) {
+        let center = vec2(x, y);
+        let vertices = poly_vertices(center, sides, radius, rotation);
+        let outer = radius + thickness;
+        self.fill(center - outer, center + outer, color, |p| {
+            (0..vertices.len()).any(|i| {
+                let (a, b) = (vertices[i], vertices[(i + 1) % vertices.len()]);
+                segment_distance(p, a, b) <= thickness / 2.0
+            })
+        });
+    }
+
+    fn text(&mut self, text: &str, x: f32, y: f32, font_size: u16, color: Color) {
+        let mut pen = x;
+        for ch in text.chars() {
+            let (metrics, bitmap) = self.font.rasterize(ch, font_size as f32);
+            let left = (pen + metrics.xmin as f32).round() as i32;
+            let top = (y - metrics.height as f32 - metrics.ymin as f32).round() as i32;
+            for (i, coverage) in bitmap.iter().enumerate() {
+                let (gx, gy) = ((i % metrics.width) as i32, (i / metrics.width) as i32);
+                self.blend(left + gx, top + gy, color, *coverage as f32 / 255.0);
+            }
+            pen += metrics.advance_width;
+        }
+    }
+
+    fn measure_text(&self, text: &str, font_size: u16) -> TextDimensions {
+        let mut width = 0.0;
+        let (mut top, mut bottom) = (0.0f32, 0.0f32);
+        for ch in text.chars() {
+            let metrics = self.font.metrics(ch, font_size as f32);
+            width += metrics.advance_width;
+            top = top.max(metrics.height as f32 + metrics.ymin as f32);
+            bottom = bottom.min(metrics.ymin as f32);
+        }
+        TextDimensions {
+            width,
+            height: top - bottom,
+            offset_y: top,
+        }
+    }
+}
+
+/// 和基准图比较, 设置了 `UPDATE_GOLDEN` 时改为重新生成基准图
+pub fn assert_golden(name: &str, frame: &Frame) {
+    let golden_path = PathBuf::from(GOLDEN_DIR).join(format!("{}.png", name));
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        frame.save_png(&golden_path);
+        return;
+    }
+    let Some(golden) = Frame::load_png(&golden_path) else {
+        panic!("缺少基准图 {:?}, 用 UPDATE_GOLDEN=1 生成", golden_path);
+    };
+    let mismatches = frame.mismatches(&golden, TOLERANCE);
+    let limit = (frame.width * frame.height) as f32 * MAX_MISMATCH;
+    if mismatches.is_none_or(|count| count as f32 > limit) {
+        let failed_path = PathBuf::from(FAILED_DIR).join(format!("{}.png", name));
+        frame.save_png(&failed_path);
+        panic!(
+            "{} 和基准图不一致: {:?} 个像素超出误差, 实际画面保存在 {:?}",
+            name, mismatches, failed_path
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::achievements::Achievement;
+    use crate::events::{GameEvent, Subscriber};
+    use crate::hud::{Hud, HudView, Toasts};
+    use crate::mode::GameMode;
+    use crate::paint::{Ship, WorldView};
+    use crate::player::CoopSettings;
+    use crate::powerup::{Pickup, PowerUp};
+    use crate::settings::{Accessibility, Palette};
+    use crate::shape::Shape;
+    use crate::state::MainMenu;
+
+    fn canvas() -> Frame {
+        Frame::new(VIRTUAL_WIDTH as u32, VIRTUAL_HEIGHT as u32, BLACK)
+    }
+
+    fn shape(x: f32, y: f32, size: f32, color: Color) -> Shape {
+        Shape {
+            size,
+            speed: 0.0,
+            drift: 0.0,
+            x,
+            y,
+            color,
+            collided: false,
+        }
+    }
+
+    fn paint_world(settings: &Accessibility) -> Frame {
+        let ship = shape(400.0, 450.0, 32.0, Color::from_rgba(200, 80, 40, 255));
+        let bullets = [
+            shape(400.0, 380.0, 5.0, Color::from_rgba(40, 200, 90, 255)),
+            shape(420.0, 330.0, 5.0, Color::from_rgba(90, 40, 200, 200)),
+        ];
+        let squares = [
+            shape(150.0, 120.0, 24.0, Color::from_rgba(255, 0, 0, 255)),
+            shape(400.0, 200.0, 48.0, Color::from_rgba(30, 160, 220, 120)),
+            shape(650.0, 300.0, 64.0, Color::from_rgba(250, 230, 20, 20)),
+        ];
+        let pickups = [Pickup {
+            kind: PowerUp::Spread,
+            pos: vec2(250.0, 400.0),
+        }];
+        let mut frame = canvas();
+        WorldView {
+            ships: vec![Ship {
+                shape: &ship,
+                bullets: &bullets,
+                alpha: 1.0,
+                shield: 2,
+            }],
+            pickups: &pickups,
+            squares: &squares,
+            settings,
+        }
+        .paint(&mut frame);
+        frame
+    }
+
+    #[test]
+    fn mismatches_respect_tolerance() {
+        let a = Frame::new(4, 4, BLACK);
+        let mut b = Frame::new(4, 4, BLACK);
+        b.set_pixel(0, 0, [TOLERANCE, 0, 0, 255]);
+        assert_eq!(a.mismatches(&b, TOLERANCE), Some(0));
+        b.set_pixel(1, 1, [0, TOLERANCE + 1, 0, 255]);
+        assert_eq!(a.mismatches(&b, TOLERANCE), Some(1));
+        assert_eq!(a.mismatches(&Frame::new(2, 2, BLACK), TOLERANCE), None);
+    }
+
+    #[test]
+    fn shapes_are_blended_over_the_background() {
+        let mut frame = Frame::new(20, 20, BLACK);
+        frame.rect(2.0, 2.0, 4.0, 4.0, WHITE);
+        frame.circle(14.0, 14.0, 3.0, Color::new(1.0, 0.0, 0.0, 0.5));
+        assert_eq!(frame.pixel(3, 3), [255, 255, 255, 255]);
+        assert_eq!(frame.pixel(6, 6), [0, 0, 0, 255]);
+        assert_eq!(frame.pixel(14, 14)[0], 127);
+        assert_eq!(frame.pixel(18, 14), [0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn world() {
+        assert_golden("world", &paint_world(&Accessibility::default()));
+    }
+
+    #[test]
+    fn world_high_contrast() {
+        let settings = Accessibility {
+            palette: Palette::OkabeIto,
+            high_contrast: true,
+            ..Default::default()
+        };
+        assert_golden("world-high-contrast", &paint_world(&settings));
+    }
+
+    #[test]
+    fn hud() {
+        let mut hud = Hud::default();
+        hud.on_event(&GameEvent::WaveStarted { wave: 3 });
+        hud.on_event(&GameEvent::EnemyDestroyed {
+            player: 0,
+            x: 300.0,
+            y: 250.0,
+            size: 48.0,
+        });
+        let mut toasts = Toasts::default();
+        toasts.on_event(&GameEvent::AchievementUnlocked(Achievement::FirstBlood));
+        toasts.update(1.0);
+
+        let mut frame = canvas();
+        hud.paint(
+            &mut frame,
+            &HudView {
+                score: 1234,
+                high_score: 5678,
+                remaining: Some(75),
+                lines: vec![
+                    ("P1 SCORE 600".to_string(), SKYBLUE),
+                    ("P2 DOWN".to_string(), ORANGE),
+                ],
+            },
+        );
+        toasts.paint(&mut frame);
+        assert_golden("hud", &frame);
+    }
+
+    #[test]
+    fn main_menu() {
+        let mut frame = canvas();
+        MainMenu::paint(
+            &mut frame,
+            GameMode::ScoreAttack,
+            CoopSettings {
+                shared_score: true,
+                shared_lives: false,
+            },
+        );
+        assert_golden("main-menu", &frame);
+    }
+}
@@ -0,0 +1,178 @@
+//! 游戏时钟, 区分真实时间、游戏时间和界面时间
+//!
+//! - 真实时间: 每帧实际经过的时间, 只用于统计帧率
+//! - 界面时间: 不受暂停和缩放影响, 菜单、切换动画和提示在暂停时也能播放
+//! - 游戏时间: 暂停和顿帧时停止, 再乘上调试和辅助功能的时间缩放
+//!
+//! 死亡慢动作属于模拟本身, 由 `World` 在游戏时间之上再缩放, 所以几种效果可以叠加
+
+use crate::events::{GameEvent, Subscriber};
+
+/// 单帧时间的上限, 卡顿或窗口切回后不会一帧跳过太久
+pub const MAX_FRAME_TIME: f32 = 0.1;
+/// 玩家被撞到时的顿帧时长(秒)
+const HIT_STOP: f32 = 0.08;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Clock {
+    real_delta: f32,
+    ui_delta: f32,
+    ui_time: f64,
+    delta: f32,
+    /// 游戏时间的缩放
+    scale: f32,
+    paused: bool,
+    /// 剩余的顿帧时间, 按界面时间倒数
+    hit_stop: f32,
+    /// 下一次 `tick` 丢弃这一帧的时间
+    resync: bool,
+}
+
+impl Default for Clock {
+    fn default() -> Self {
+        Self {
+            real_delta: 0.0,
+            ui_delta: 0.0,
+            ui_time: 0.0,
+            delta: 0.0,
+            scale: 1.0,
+            paused: false,
+            hit_stop: 0.0,
+            resync: false,
+        }
+    }
+}
+
+impl Clock {
+    /// 每帧开始时调用一次, `real_delta` 为 `get_frame_time` 的值
+    pub fn tick(&mut self, real_delta: f32) {
+        self.real_delta = real_delta;
+        self.ui_delta = if std::mem::take(&mut self.resync) {
+            0.0
+        } else {
+            real_delta.clamp(0.0, MAX_FRAME_TIME)
+        };
+        self.ui_time += self.ui_delta as f64;
+
+        self.delta = if self.paused {
+            0.0
+        } else if self.hit_stop > 0.0 {
+            // 顿帧在这一帧中途结束时, 剩下的时间照常推进
+            self.hit_stop -= self.ui_delta;
+            (-self.hit_stop).max(0.0) * self.scale
+        } else {
+            self.ui_delta * self.scale
+        };
+        self.hit_stop = self.hit_stop.max(0.0);
+    }
+
+    /// 本帧的游戏时间
+    pub fn delta(&self) -> f32 {
+        self.delta
+    }
+
+    /// 本帧的界面时间
+    pub fn ui_delta(&self) -> f32 {
+        self.ui_delta
+    }
+
+    /// 界面时间总和, 用于菜单动画
+    pub fn ui_time(&self) -> f64 {
+        self.ui_time
+    }
+
+    /// 本帧真实经过的时间, 没有上限, 供调试叠加层统计帧时间
+    #[cfg(debug_assertions)]
+    pub fn real_delta(&self) -> f32 {
+        self.real_delta
+    }
+
+    /// 设置游戏时间的缩放, 从下一次 `tick` 开始生效
+    pub fn set_scale(&mut self, scale: f32) {
+        self.scale = scale.max(0.0);
+    }
+
+    /// 供调试叠加层显示
+    #[cfg(debug_assertions)]
+    pub fn scale(&self) -> f32 {
+        self.scale
+    }
+
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    pub fn resume(&mut self) {
+        self.paused = false;
+    }
+
+    /// 下一帧不推进任何时间, 窗口切回后的第一帧可能积累了很久
+    pub fn resync(&mut self) {
+        self.resync = true;
+    }
+
+    /// 冻结游戏时间 `duration` 秒, 和正在进行的顿帧取较长的一个
+    pub fn hit_stop(&mut self, duration: f32) {
+        self.hit_stop = self.hit_stop.max(duration);
+    }
+}
+
+/// 玩家被撞到时顿帧
+impl Subscriber<GameEvent> for Clock {
+    fn on_event(&mut self, event: &GameEvent) {
+        if let GameEvent::PlayerHit { .. } = event {
+            self.hit_stop(HIT_STOP);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pause_freezes_game_time_but_not_ui_time() {
+        let mut clock = Clock::default();
+        clock.tick(0.02);
+        clock.pause();
+        clock.tick(0.02);
+        assert_eq!(clock.delta(), 0.0);
+        assert_eq!(clock.ui_delta(), 0.02);
+        clock.resume();
+        clock.tick(0.02);
+        assert_eq!(clock.delta(), 0.02);
+        assert!((clock.ui_time() - 0.06).abs() < 1e-6);
+    }
+
+    #[test]
+    fn long_frames_are_clamped() {
+        let mut clock = Clock::default();
+        clock.tick(3.0);
+        assert_eq!(clock.ui_delta(), MAX_FRAME_TIME);
+        assert_eq!(clock.delta(), MAX_FRAME_TIME);
+        clock.resync();
+        clock.tick(3.0);
+        assert_eq!(clock.ui_delta(), 0.0);
+        assert_eq!(clock.delta(), 0.0);
+        clock.tick(0.02);
+        assert_eq!(clock.delta(), 0.02);
+    }
+
+    #[test]
+    fn hit_stop_composes_with_scale_and_pause() {
+        let mut clock = Clock::default();
+        clock.set_scale(0.5);
+        clock.hit_stop(0.05);
+        clock.tick(0.04);
+        assert_eq!(clock.delta(), 0.0);
+        // 暂停时顿帧不倒数
+        clock.pause();
+        clock.tick(0.04);
+        clock.resume();
+        // 剩下的 0.01 秒顿帧之后按缩放推进
+        clock.tick(0.04);
+        assert!((clock.delta() - 0.015).abs() < 1e-6);
+        clock.tick(0.04);
+        assert!((clock.delta() - 0.02).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,397 @@
+//! 调试工具: 性能叠加层和开发者控制台, 只在 debug 构建中编译
+
+use std::collections::VecDeque;
+
+use macroquad::prelude::*;
+
+use crate::canvas::{VIRTUAL_HEIGHT, VIRTUAL_WIDTH};
+use crate::effects::EffectStats;
+use crate::game::Game;
+use crate::player::Player;
+use crate::powerup::PowerUp;
+use crate::shape::{EnemySize, Shape};
+
+/// 帧时间曲线保留的帧数
+const FRAME_HISTORY: usize = 120;
+/// 控制台保留的输出行数
+const CONSOLE_LINES: usize = 8;
+
+/// 控制台命令
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    /// `spawn enemy <small|medium|big> <x> <y>`
+    SpawnEnemy {
+        size: EnemySize,
+        x: f32,
+        y: f32,
+    },
+    /// `god` 切换无敌
+    God,
+    /// `bot` 切换一号玩家的自动驾驶
+    Bot,
+    /// `timescale <scale>`
+    TimeScale(f32),
+    /// `give powerup <kind>`
+    GivePowerUp(PowerUp),
+    /// `wave <n>`
+    Wave(u32),
+    Help,
+    Clear,
+}
+
+const HELP: &str = "spawn enemy <small|medium|big> <x> <y> | god | bot | timescale <s> | give powerup spread | wave <n> | clear";
+
+impl Command {
+    /// 解析一行命令
+    pub fn parse(line: &str) -> Result<Self, String> {
+        let args: Vec<&str> = line.split_whitespace().collect();
+        match args.as_slice() {
+            ["spawn", "enemy", size, x, y] => Ok(Command::SpawnEnemy {
+                size: EnemySize::parse(size)
+                    .ok_or_else(|| format!("unknown enemy size: {size}"))?,
+                x: parse_number(x)?,
+                y: parse_number(y)?,
+            }),
+            ["god"] => Ok(Command::God),
+            ["bot"] => Ok(Command::Bot),
+            ["timescale", scale] => {
+                let scale: f32 = parse_number(scale)?;
+                if scale.is_finite() && scale >= 0.0 {
+                    Ok(Command::TimeScale(scale))
+                } else {
+                    Err("timescale must be a finite, non-negative number".to_string())
+                }
+            }
+            ["give", "powerup", kind] => PowerUp::parse(kind)
+                .map(Command::GivePowerUp)
+                .ok_or_else(|| format!("unknown powerup: {kind}")),
+            ["wave", n] => Ok(Command::Wave(parse_number(n)?)),
+            ["help"] => Ok(Command::Help),
+            ["clear"] => Ok(Command::Clear),
+            [] => Err(String::new()),
+            _ => Err(format!("unknown command: {line}")),
+        }
+    }
+}
+
+fn parse_number<T: std::str::FromStr>(arg: &str) -> Result<T, String> {
+    arg.parse().map_err(|_| format!("invalid number: {arg}"))
+}
+
+/// 开发者控制台, 按 ` 键开关
+#[derive(Default)]
+pub struct Console {
+    open: bool,
+    input: String,
+    lines: VecDeque<String>,
+}
+
+impl Console {
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    /// 输出一行信息
+    pub fn print(&mut self, line: impl Into<String>) {
+        self.lines.push_back(line.into());
+        while self.lines.len() > CONSOLE_LINES {
+            self.lines.pop_front();
+        }
+    }
+
+    /// 处理键盘输入, 回车提交时返回解析成功的命令
+    pub fn update(&mut self) -> Option<Command> {
+        // 字符队列是后进先出的, 先全部取出再按顺序处理
+        let mut chars = vec![];
+        while let Some(c) = get_char_pressed() {
+            chars.push(c);
+        }
+        chars.reverse();
+
+        if is_key_pressed(KeyCode::GraveAccent) {
+            self.open = !self.open;
+            self.input.clear();
+            return None;
+        }
+        if !self.open {
+            return None;
+        }
+        if is_key_pressed(KeyCode::Escape) {
+            self.open = false;
+            return None;
+        }
+        if is_key_pressed(KeyCode::Backspace) {
+            self.input.pop();
+        }
+        self.input
+            .extend(chars.into_iter().filter(|c| !c.is_control() && *c != '`'));
+
+        if !is_key_pressed(KeyCode::Enter) {
+            return None;
+        }
+        let line = std::mem::take(&mut self.input);
+        match Command::parse(&line) {
+            Ok(Command::Help) => {
+                self.print(HELP);
+                None
+            }
+            Ok(Command::Clear) => {
+                self.lines.clear();
+                None
+            }
+            Ok(command) => {
+                self.print(format!("> {line}"));
+                Some(command)
+            }
+            Err(err) => {
+                if !err.is_empty() {
+                    self.print(err);
+                }
+                None
+            }
+        }
+    }
+
+    pub fn draw(&self) {
+        if !self.open {
+            return;
+        }
+        let line_height = 20.0;
+        let height = line_height * (CONSOLE_LINES as f32 + 1.5);
+        let top = VIRTUAL_HEIGHT - height;
+        draw_rectangle(
+            0.0,
+            top,
+            VIRTUAL_WIDTH,
+            height,
+            Color::new(0.0, 0.0, 0.0, 0.75),
+        );
+        for (i, line) in self.lines.iter().enumerate() {
+            draw_text(
+                line,
+                8.0,
+                top + line_height * (i as f32 + 1.0),
+                18.0,
+                LIGHTGRAY,
+            );
+        }
+        // 光标闪烁
+        let cursor = if get_time().fract() < 0.5 { "_" } else { "" };
+        draw_text(
+            &format!("> {}{}", self.input, cursor),
+            8.0,
+            VIRTUAL_HEIGHT - 8.0,
+            18.0,
+            WHITE,
+        );
+    }
+}
+
+/// 叠加层需要显示的游戏信息
+pub struct DebugInfo<'a> {
+    pub state: &'a str,
+    pub players: &'a [Player],
+    pub squares: &'a [Shape],
+    pub effects: EffectStats,
+    pub wave: u32,
+    pub god: bool,
+    pub time_scale: f32,
+    /// 鼠标在虚拟画布中的位置
+    pub mouse: Vec2,
+}
+
+/// 性能叠加层, 按 F1 开关
+pub struct DebugOverlay {
+    visible: bool,
+    frame_times: VecDeque<f32>,
+}
+
+impl DebugOverlay {
+    pub fn new() -> Self {
+        Self {
+            visible: false,
+            frame_times: VecDeque::with_capacity(FRAME_HISTORY),
+        }
+    }
+
+    /// 记录真实的帧时间并处理开关
+    pub fn update(&mut self, frame_time: f32) {
+        if is_key_pressed(KeyCode::F1) {
+            self.visible = !self.visible;
+        }
+        if self.frame_times.len() == FRAME_HISTORY {
+            self.frame_times.pop_front();
+        }
+        self.frame_times.push_back(frame_time);
+    }
+
+    pub fn draw(&self, info: &DebugInfo) {
+        if !self.visible {
+            return;
+        }
+
+        // 碰撞体轮廓
+        for player in info.players {
+            let ship = &player.ship;
+            draw_circle_lines(ship.x, ship.y, ship.size / 2.0, 1.0, GREEN);
+            for bullet in &player.bullets {
+                draw_circle_lines(bullet.x, bullet.y, bullet.size / 2.0, 1.0, GREEN);
+            }
+        }
+        for square in info.squares {
+            draw_rectangle_lines(
+                square.x - square.size / 2.0,
+                square.y - square.size / 2.0,
+                square.size,
+                square.size,
+                1.0,
+                RED,
+            );
+        }
+
+        let left = 10.0;
+        let top = 60.0;
+        let lines = [
+            format!("FPS: {}", get_fps()),
+            format!("STATE: {}", info.state),
+            format!("WAVE: {}", info.wave),
+            format!(
+                "SQUARES: {}  BULLETS: {}",
+                info.squares.len(),
+                info.players
+                    .iter()
+                    .map(|player| player.bullets.len())
+                    .sum::<usize>()
+            ),
+            format!(
+                "EFFECTS: {} live / {} pooled / {} dropped",
+                info.effects.live, info.effects.pooled, info.effects.dropped
+            ),
+            format!("TIMESCALE: {:.2}  GOD: {}", info.time_scale, info.god),
+            format!("MOUSE: {:.0}, {:.0}", info.mouse.x, info.mouse.y),
+        ];
+        draw_rectangle(
+            left - 5.0,
+            top - 20.0,
+            330.0,
+            198.0,
+            Color::new(0.0, 0.0, 0.0, 0.6),
+        );
+        for (i, line) in lines.iter().enumerate() {
+            draw_text(line, left, top + i as f32 * 18.0, 18.0, WHITE);
+        }
+
+        // 帧时间曲线, 黄线为 60 FPS 对应的 16.7ms
+        let graph_top = top + lines.len() as f32 * 18.0;
+        let graph_height = 40.0;
+        let ms_to_px = graph_height / 33.3;
+        for (i, frame_time) in self.frame_times.iter().enumerate() {
+            let h = (frame_time * 1000.0 * ms_to_px).min(graph_height);
+            let color = if *frame_time > 1.0 / 30.0 { RED } else { GREEN };
+            draw_line(
+                left + i as f32 * 2.5,
+                graph_top + graph_height,
+                left + i as f32 * 2.5,
+                graph_top + graph_height - h,
+                2.0,
+                color,
+            );
+        }
+        let target = graph_top + graph_height - 16.7 * ms_to_px;
+        draw_line(
+            left,
+            target,
+            left + FRAME_HISTORY as f32 * 2.5,
+            target,
+            1.0,
+            YELLOW,
+        );
+    }
+}
+
+/// 执行控制台命令
+pub fn run_command(game: &mut Game, console: &mut Console, command: Command) {
+    match command {
+        Command::SpawnEnemy { size, x, y } => {
+            game.world.squares.push(Shape {
+                size: size.size(),
+                speed: rand::gen_range(16.0, 64.0),
+                drift: 0.0,
+                x,
+                y,
+                color: color_u8!(
+                    rand::gen_range(0, 255),
+                    rand::gen_range(0, 255),
+                    rand::gen_range(0, 255),
+                    rand::gen_range(0, 255)
+                ),
+                collided: false,
+            });
+        }
+        Command::God => {
+            game.world.god = !game.world.god;
+            console.print(format!("god: {}", game.world.god));
+        }
+        Command::Bot => {
+            game.autopilot = !game.autopilot;
+            console.print(format!("bot: {}", game.autopilot));
+        }
+        Command::TimeScale(scale) => game.debug_time_scale = scale,
+        Command::GivePowerUp(kind) => game.world.powerups.give(kind),
+        Command::Wave(number) => game.world.waves.set(number),
+        Command::Help | Command::Clear => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_commands() {
+        assert_eq!(
+            Command::parse("spawn enemy big 100 -20"),
+            Ok(Command::SpawnEnemy {
+                size: EnemySize::Big,
+                x: 100.0,
+                y: -20.0,
+            })
+        );
+        assert_eq!(Command::parse("  god "), Ok(Command::God));
+        assert_eq!(Command::parse("timescale 0.5"), Ok(Command::TimeScale(0.5)));
+        assert_eq!(
+            Command::parse("give powerup spread"),
+            Ok(Command::GivePowerUp(PowerUp::Spread))
+        );
+        assert_eq!(Command::parse("wave 12"), Ok(Command::Wave(12)));
+    }
+
+    #[test]
+    fn rejects_invalid_commands() {
+        assert_eq!(
+            Command::parse("spawn enemy huge 0 0"),
+            Err("unknown enemy size: huge".to_string())
+        );
+        assert_eq!(
+            Command::parse("spawn enemy small x 0"),
+            Err("invalid number: x".to_string())
+        );
+        for scale in ["-1", "inf", "NaN"] {
+            assert!(Command::parse(&format!("timescale {scale}")).is_err());
+        }
+        assert_eq!(
+            Command::parse("give powerup laser"),
+            Err("unknown powerup: laser".to_string())
+        );
+        assert_eq!(
+            Command::parse("wave -1"),
+            Err("invalid number: -1".to_string())
+        );
+        assert_eq!(
+            Command::parse("fly"),
+            Err("unknown command: fly".to_string())
+        );
+        // 空行不输出错误
+        assert_eq!(Command::parse("   "), Err(String::new()));
+    }
+}
@@ -0,0 +1,464 @@
+use macroquad::miniquad::{BlendFactor, BlendState, BlendValue, Equation};
+use macroquad::prelude::*;
+use macroquad_particles::{Emitter, EmitterConfig};
+
+use crate::canvas::{VIRTUAL_HEIGHT, VIRTUAL_WIDTH};
+use crate::events::{GameEvent, Subscriber};
+use crate::presets::ParticleLibrary;
+use crate::shape::EnemySize;
+
+/// 特效优先级, 超出上限时先淘汰优先级低的特效
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum EffectPriority {
+    Low,
+    Normal,
+    High,
+}
+
+/// 特效统计, 供调试界面显示
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EffectStats {
+    /// 正在播放的特效数量
+    pub live: usize,
+    /// 池中空闲的发射器数量
+    pub pooled: usize,
+    /// 因为上限而被丢弃的新特效数量
+    pub dropped: u32,
+    /// 被更高优先级特效挤掉的特效数量
+    pub evicted: u32,
+}
+
+/// 正在播放的特效
+pub struct ActiveEffect<P> {
+    pub preset: String,
+    pub payload: P,
+    pub pos: Vec2,
+    pub priority: EffectPriority,
+    /// 剩余的播放时间, 为 0 时所有粒子都已消失
+    pub remaining: f32,
+}
+
+/// 特效槽位的簿记: 上限、按优先级淘汰、播完回收和复用
+///
+/// 与渲染无关, `P` 是每个槽位实际播放特效的对象
+pub struct EffectSlots<P> {
+    active: Vec<ActiveEffect<P>>,
+    /// 空闲的对象和它们对应的预设名
+    pool: Vec<(String, P)>,
+    capacity: usize,
+    dropped: u32,
+    evicted: u32,
+}
+
+impl<P> EffectSlots<P> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            active: Vec::with_capacity(capacity),
+            pool: Vec::new(),
+            capacity,
+            dropped: 0,
+            evicted: 0,
+        }
+    }
+
+    /// 为预设 `preset` 占用一个槽位, 播放 `lifetime` 秒
+    ///
+    /// 达到上限时淘汰优先级最低且最旧的特效, 如果新特效的优先级更低则丢弃并返回 `None`.
+    /// 优先复用池中同一预设最近回收的对象, 没有时调用 `create` 创建
+    pub fn acquire(
+        &mut self,
+        preset: &str,
+        pos: Vec2,
+        priority: EffectPriority,
+        lifetime: f32,
+        create: impl FnOnce() -> P,
+    ) -> Option<&mut ActiveEffect<P>> {
+        if self.capacity == 0 {
+            self.dropped += 1;
+            return None;
+        }
+        if self.active.len() >= self.capacity {
+            // min_by_key 在相等时返回第一个, 也就是最旧的特效
+            let (victim, lowest) = self
+                .active
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, effect)| effect.priority)
+                .map(|(i, effect)| (i, effect.priority))
+                .unwrap();
+            if lowest > priority {
+                self.dropped += 1;
+                return None;
+            }
+            let effect = self.active.remove(victim);
+            self.pool.push((effect.preset, effect.payload));
+            self.evicted += 1;
+        }
+
+        let payload = match self.pool.iter().rposition(|(name, _)| name == preset) {
+            Some(i) => self.pool.swap_remove(i).1,
+            None => create(),
+        };
+        self.active.push(ActiveEffect {
+            preset: preset.to_string(),
+            payload,
+            pos,
+            priority,
+            remaining: lifetime,
+        });
+        self.active.last_mut()
+    }
+
+    /// 推进 `dt` 秒, 播放完毕的特效回收到池中
+    pub fn advance(&mut self, dt: f32) {
+        for effect in self.active.iter_mut() {
+            effect.remaining -= dt;
+        }
+        let mut i = 0;
+        while i < self.active.len() {
+            if self.active[i].remaining <= 0.0 {
+                let effect = self.active.remove(i);
+                self.pool.push((effect.preset, effect.payload));
+            } else {
+                i += 1;
+            }
+        }
+    }
+
+    /// 从旧到新的所有特效
+    pub fn active_mut(&mut self) -> impl Iterator<Item = &mut ActiveEffect<P>> {
+        self.active.iter_mut()
+    }
+
+    pub fn clear(&mut self) {
+        self.active.clear();
+        self.pool.clear();
+    }
+
+    pub fn stats(&self) -> EffectStats {
+        EffectStats {
+            live: self.active.len(),
+            pooled: self.pool.len(),
+            dropped: self.dropped,
+            evicted: self.evicted,
+        }
+    }
+}
+
+/// 按游戏时间决定每帧是否推进粒子
+///
+/// macroquad-particles 只能在 `Emitter::draw` 中按 `get_frame_time` 推进一整帧,
+/// 所以把游戏时间攒起来, 攒够半帧才推进一次. 暂停和顿帧时不推进, 慢动作时隔帧推进
+#[derive(Debug, Default)]
+pub struct ParticleClock {
+    /// 攒下的游戏时间减去已经推进的帧时间
+    pending: f32,
+}
+
+impl ParticleClock {
+    /// 本帧经过游戏时间 `game_dt` 和界面时间 `frame_dt`, 返回是否推进粒子
+    pub fn tick(&mut self, game_dt: f32, frame_dt: f32) -> bool {
+        self.pending += game_dt;
+        if self.pending <= 0.0 || self.pending < frame_dt / 2.0 {
+            return false;
+        }
+        // 比帧时间快时最多每帧推进一次, 多出来的时间不留到以后
+        self.pending = (self.pending - frame_dt).min(frame_dt);
+        true
+    }
+}
+
+/// 把特效层叠加到画布上, 特效层中没有粒子的地方是黑色, 当作透明
+///
+/// 粒子的管线不写入透明通道, 特效层的透明度始终是清屏时的值, 只能按颜色判断
+const LAYER_FRAGMENT_SHADER: &str = "#version 100
+precision lowp float;
+
+varying vec2 uv;
+
+uniform sampler2D Texture;
+
+void main() {
+    vec3 color = texture2D(Texture, uv).rgb;
+    gl_FragColor = vec4(color, step(0.001, max(color.r, max(color.g, color.b))));
+}
+";
+
+const LAYER_VERTEX_SHADER: &str = "#version 100
+attribute vec3 position;
+attribute vec2 texcoord;
+
+varying vec2 uv;
+
+uniform mat4 Model;
+uniform mat4 Projection;
+
+void main() {
+    gl_Position = Projection * Model * vec4(position, 1);
+    uv = texcoord;
+}
+";
+
+/// 槽位中的发射器
+///
+/// `reset` 不是公开接口, 所以发射器始终处于 `emitting: false` 状态,
+/// 通过 `Emitter::emit` 一次性喷出整批粒子来复用
+struct Particles {
+    emitter: Emitter,
+    /// 上次绘制时的位置, 非局部坐标的粒子以它为原点生成
+    last_pos: Vec2,
+}
+
+/// 一次性粒子特效管理器, 复用发射器并限制同屏特效数量
+///
+/// 粒子画在单独的特效层上, 不推进的帧直接重画上一次的特效层
+pub struct EffectManager {
+    slots: EffectSlots<Particles>,
+    clock: ParticleClock,
+    layer: RenderTarget,
+    camera: Camera2D,
+    material: Material,
+}
+
+impl EffectManager {
+    pub fn new(capacity: usize) -> Self {
+        let layer = render_target(VIRTUAL_WIDTH as u32, VIRTUAL_HEIGHT as u32);
+        layer.texture.set_filter(FilterMode::Nearest);
+        let mut camera =
+            Camera2D::from_display_rect(Rect::new(0.0, 0.0, VIRTUAL_WIDTH, VIRTUAL_HEIGHT));
+        camera.render_target = Some(layer.clone());
+        let material = load_material(
+            ShaderSource::Glsl {
+                vertex: LAYER_VERTEX_SHADER,
+                fragment: LAYER_FRAGMENT_SHADER,
+            },
+            MaterialParams {
+                pipeline_params: PipelineParams {
+                    color_blend: Some(BlendState::new(
+                        Equation::Add,
+                        BlendFactor::Value(BlendValue::SourceAlpha),
+                        BlendFactor::OneMinusValue(BlendValue::SourceAlpha),
+                    )),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        Self {
+            slots: EffectSlots::new(capacity),
+            clock: ParticleClock::default(),
+            layer,
+            camera,
+            material,
+        }
+    }
+
+    /// 在 `pos` 处播放预设 `name`, 喷出 `amount` 个粒子
+    ///
+    /// 达到上限时淘汰优先级最低且最旧的特效, 如果新特效的优先级更低则直接丢弃
+    pub fn spawn(
+        &mut self,
+        presets: &ParticleLibrary,
+        name: &str,
+        pos: Vec2,
+        amount: u32,
+        priority: EffectPriority,
+    ) -> bool {
+        let config = EmitterConfig {
+            emitting: false,
+            ..presets.get(name)
+        };
+        // 刚被淘汰的特效残留的粒子会和复用它的新特效一起播完
+        let Some(effect) = self
+            .slots
+            .acquire(name, pos, priority, config.lifetime, || Particles {
+                emitter: Emitter::new(config.clone()),
+                last_pos: Vec2::ZERO,
+            })
+        else {
+            return false;
+        };
+
+        let particles = &mut effect.payload;
+        let offset = if config.local_coords {
+            Vec2::ZERO
+        } else {
+            pos - particles.last_pos
+        };
+        particles.emitter.config = config;
+        particles.emitter.emit(offset, amount as usize);
+        true
+    }
+
+    /// 绘制所有特效, 按游戏时间 `dt` 推进粒子和扣除寿命, `frame_dt` 是本帧的界面时间
+    ///
+    /// 暂停、顿帧和慢动作时粒子停住或放慢, 停住的帧仍然显示上一次的画面
+    pub fn draw(&mut self, dt: f32, frame_dt: f32) {
+        if self.clock.tick(dt, frame_dt) {
+            push_camera_state();
+            set_camera(&self.camera);
+            clear_background(BLACK);
+            for effect in self.slots.active_mut() {
+                effect.payload.emitter.draw(effect.pos);
+                effect.payload.last_pos = effect.pos;
+            }
+            pop_camera_state();
+        }
+        self.slots.advance(dt);
+
+        gl_use_material(&self.material);
+        draw_texture_ex(
+            &self.layer.texture,
+            0.0,
+            0.0,
+            WHITE,
+            DrawTextureParams {
+                dest_size: Some(vec2(VIRTUAL_WIDTH, VIRTUAL_HEIGHT)),
+                // 渲染目标是上下颠倒的
+                flip_y: true,
+                ..Default::default()
+            },
+        );
+        gl_use_default_material();
+    }
+
+    /// 预设热重载后丢弃所有发射器, 之后按新配置重新创建
+    pub fn invalidate(&mut self) {
+        self.slots.clear();
+    }
+
+    pub fn stats(&self) -> EffectStats {
+        self.slots.stats()
+    }
+}
+
+/// 订阅击毁事件, 在方块的位置播放爆炸特效
+pub struct Explosions<'a> {
+    pub effects: &'a mut EffectManager,
+    pub presets: &'a ParticleLibrary,
+}
+
+impl Subscriber<GameEvent> for Explosions<'_> {
+    fn on_event(&mut self, event: &GameEvent) {
+        if let GameEvent::EnemyDestroyed { x, y, size, .. } = *event {
+            let priority = match EnemySize::from_size(size) {
+                EnemySize::Big => EffectPriority::High,
+                EnemySize::Medium => EffectPriority::Normal,
+                EnemySize::Small => EffectPriority::Low,
+            };
+            self.effects.spawn(
+                self.presets,
+                "explosion",
+                vec2(x, y),
+                size.round() as u32 * 2,
+                priority,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 用编号代替发射器, 记录每个槽位复用的是哪个对象
+    fn spawn(slots: &mut EffectSlots<u32>, preset: &str, priority: EffectPriority) -> Option<u32> {
+        let next = slots.stats().live as u32 + slots.stats().pooled as u32;
+        slots
+            .acquire(preset, Vec2::ZERO, priority, 1.0, || next)
+            .map(|effect| effect.payload)
+    }
+
+    fn priorities(slots: &mut EffectSlots<u32>) -> Vec<EffectPriority> {
+        slots.active_mut().map(|effect| effect.priority).collect()
+    }
+
+    #[test]
+    fn capacity_limits_live_effects() {
+        let mut slots = EffectSlots::new(2);
+        assert!(spawn(&mut slots, "a", EffectPriority::Normal).is_some());
+        assert!(spawn(&mut slots, "a", EffectPriority::Normal).is_some());
+        assert!(spawn(&mut slots, "a", EffectPriority::Normal).is_some());
+        let stats = slots.stats();
+        assert_eq!((stats.live, stats.evicted, stats.dropped), (2, 1, 0));
+
+        let mut none = EffectSlots::new(0);
+        assert!(spawn(&mut none, "a", EffectPriority::High).is_none());
+        assert_eq!(none.stats().dropped, 1);
+    }
+
+    #[test]
+    fn evicts_oldest_lowest_priority() {
+        let mut slots = EffectSlots::new(3);
+        spawn(&mut slots, "a", EffectPriority::Normal);
+        spawn(&mut slots, "a", EffectPriority::Low);
+        spawn(&mut slots, "a", EffectPriority::Low);
+        // 淘汰最旧的低优先级特效 1, 新特效复用了它的对象
+        spawn(&mut slots, "a", EffectPriority::Normal);
+        assert_eq!(
+            priorities(&mut slots),
+            [
+                EffectPriority::Normal,
+                EffectPriority::Low,
+                EffectPriority::Normal
+            ]
+        );
+        let payloads: Vec<u32> = slots.active_mut().map(|effect| effect.payload).collect();
+        assert_eq!(payloads, [0, 2, 1]);
+        assert_eq!(slots.stats().evicted, 1);
+    }
+
+    #[test]
+    fn drops_new_effect_when_all_are_higher() {
+        let mut slots = EffectSlots::new(2);
+        spawn(&mut slots, "a", EffectPriority::High);
+        spawn(&mut slots, "a", EffectPriority::Normal);
+        assert!(spawn(&mut slots, "a", EffectPriority::Low).is_none());
+        let stats = slots.stats();
+        assert_eq!((stats.live, stats.dropped, stats.evicted), (2, 1, 0));
+        assert_eq!(
+            priorities(&mut slots),
+            [EffectPriority::High, EffectPriority::Normal]
+        );
+    }
+
+    #[test]
+    fn particles_follow_game_time() {
+        let frame = 1.0 / 60.0;
+        let mut clock = ParticleClock::default();
+        assert!(clock.tick(frame, frame));
+        // 暂停和顿帧时粒子不动
+        for _ in 0..10 {
+            assert!(!clock.tick(0.0, frame));
+        }
+        // 慢动作时大约按缩放比例推进
+        let steps = (0..100).filter(|_| clock.tick(frame * 0.3, frame)).count();
+        assert!((29..=31).contains(&steps), "{steps}");
+        // 比帧时间快时也只推进一次, 不会攒到以后
+        assert!(clock.tick(frame * 4.0, frame));
+        assert!(clock.tick(frame, frame));
+        assert!(clock.tick(frame, frame));
+    }
+
+    #[test]
+    fn finished_effects_are_reused_by_preset() {
+        let mut slots = EffectSlots::new(4);
+        let a = spawn(&mut slots, "a", EffectPriority::Normal).unwrap();
+        let b = spawn(&mut slots, "b", EffectPriority::Normal).unwrap();
+        slots.advance(0.5);
+        assert_eq!(slots.stats().live, 2);
+        slots.advance(0.5);
+        let stats = slots.stats();
+        assert_eq!((stats.live, stats.pooled), (0, 2));
+
+        // 暂停时寿命不减少
+        assert_eq!(spawn(&mut slots, "b", EffectPriority::Low), Some(b));
+        slots.advance(0.0);
+        assert_eq!(slots.stats().live, 1);
+        assert_eq!(spawn(&mut slots, "a", EffectPriority::Low), Some(a));
+        // 池中没有同名的对象时新建
+        assert_eq!(spawn(&mut slots, "c", EffectPriority::Low), Some(2));
+        assert_eq!(slots.stats().pooled, 0);
+    }
+}
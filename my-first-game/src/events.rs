@@ -0,0 +1,182 @@
+//! 事件总线: 玩法代码只负责发布事件, 音效、粒子、界面和统计各自订阅处理
+
+use crate::achievements::Achievement;
+use crate::powerup::PowerUp;
+
+/// 游戏事件
+#[derive(Debug, Clone, PartialEq)]
+pub enum GameEvent {
+    /// 新的一局开始
+    RunStarted,
+    /// 玩家发射子弹, 散射时 `count` 大于 1
+    BulletFired {
+        player: usize,
+        x: f32,
+        y: f32,
+        count: usize,
+    },
+    /// 方块被 `player` 的子弹击毁
+    EnemyDestroyed {
+        player: usize,
+        x: f32,
+        y: f32,
+        size: f32,
+    },
+    /// 玩家拾取了掉落的道具
+    PowerUpCollected { player: usize, kind: PowerUp },
+    /// 玩家被方块撞到, `size` 为撞到玩家的方块边长
+    PlayerHit {
+        player: usize,
+        x: f32,
+        y: f32,
+        size: f32,
+    },
+    /// 进入新的一波
+    WaveStarted { wave: u32 },
+    /// 本局存活的时间每满一分钟发布一次
+    Survived { seconds: u32 },
+    /// 本局结束, `duration` 为本局时长(秒)
+    GameOver { score: u32, duration: f32 },
+    /// 解锁了新成就
+    AchievementUnlocked(Achievement),
+}
+
+/// 事件订阅者
+pub trait Subscriber<E> {
+    fn on_event(&mut self, event: &E);
+}
+
+/// 闭包也可以作为订阅者, 处理一次性的逻辑
+impl<E, F: FnMut(&E)> Subscriber<E> for F {
+    fn on_event(&mut self, event: &E) {
+        self(event)
+    }
+}
+
+/// 事件队列, 发布的事件在每帧末尾统一分发
+pub struct EventBus<E> {
+    pending: Vec<E>,
+    /// 开启记录后保存所有发布过的事件, 供测试断言
+    recorded: Option<Vec<E>>,
+}
+
+impl<E: Clone> EventBus<E> {
+    pub fn new() -> Self {
+        Self {
+            pending: Vec::new(),
+            recorded: None,
+        }
+    }
+
+    pub fn publish(&mut self, event: E) {
+        if let Some(recorded) = self.recorded.as_mut() {
+            recorded.push(event.clone());
+        }
+        self.pending.push(event);
+    }
+
+    /// 取出所有待分发的事件, 按发布顺序排列
+    pub fn drain(&mut self) -> Vec<E> {
+        std::mem::take(&mut self.pending)
+    }
+
+    /// 把待分发的事件依次交给订阅者
+    pub fn dispatch(&mut self, subscribers: &mut [&mut dyn Subscriber<E>]) {
+        for event in self.drain() {
+            for subscriber in subscribers.iter_mut() {
+                subscriber.on_event(&event);
+            }
+        }
+    }
+
+    /// 开始记录事件
+    #[cfg(test)]
+    pub fn record(&mut self) {
+        self.recorded.get_or_insert_with(Vec::new);
+    }
+
+    /// 开始记录以来发布过的所有事件
+    #[cfg(test)]
+    pub fn recorded(&self) -> &[E] {
+        self.recorded.as_deref().unwrap_or(&[])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct Counter {
+        kills: u32,
+    }
+
+    impl Subscriber<GameEvent> for Counter {
+        fn on_event(&mut self, event: &GameEvent) {
+            if let GameEvent::EnemyDestroyed { .. } = event {
+                self.kills += 1;
+            }
+        }
+    }
+
+    fn kill(size: f32) -> GameEvent {
+        GameEvent::EnemyDestroyed {
+            player: 0,
+            x: 0.0,
+            y: 0.0,
+            size,
+        }
+    }
+
+    #[test]
+    fn drain_keeps_publish_order() {
+        let mut bus = EventBus::new();
+        bus.publish(GameEvent::RunStarted);
+        bus.publish(GameEvent::WaveStarted { wave: 1 });
+        assert_eq!(
+            bus.drain(),
+            [GameEvent::RunStarted, GameEvent::WaveStarted { wave: 1 }]
+        );
+        assert!(bus.drain().is_empty());
+    }
+
+    #[test]
+    fn dispatch_reaches_every_subscriber() {
+        let mut bus = EventBus::new();
+        let mut a = Counter::default();
+        let mut b = Counter::default();
+        bus.publish(kill(20.0));
+        bus.publish(GameEvent::PlayerHit {
+            player: 0,
+            x: 0.0,
+            y: 0.0,
+            size: 20.0,
+        });
+        bus.publish(kill(40.0));
+        bus.dispatch(&mut [&mut a, &mut b]);
+        assert_eq!((a.kills, b.kills), (2, 2));
+    }
+
+    #[test]
+    fn recording_survives_dispatch() {
+        let mut bus = EventBus::new();
+        bus.publish(GameEvent::RunStarted);
+        bus.record();
+        bus.publish(kill(20.0));
+        bus.dispatch(&mut []);
+        bus.publish(GameEvent::GameOver {
+            score: 20,
+            duration: 1.0,
+        });
+        assert_eq!(
+            bus.recorded(),
+            [
+                kill(20.0),
+                GameEvent::GameOver {
+                    score: 20,
+                    duration: 1.0,
+                }
+            ]
+        );
+    }
+}
@@ -0,0 +1,100 @@
+//! 窗口焦点, 失去焦点时自动暂停并静音
+//!
+//! 只有网页版能检测焦点: `web/focus.js` 插件监听 `visibilitychange` 和 `blur`/`focus`
+//! 后调用 [`focus_changed`].
+//!
+//! 原生平台目前检测不到焦点. miniquad 的 `window_minimized_event`/`window_restored_event`
+//! 由 macroquad 0.4 内部的事件处理器接收 (只在 Android 上暂停音频), 既不转发给输入订阅者,
+//! 游戏也不能换成自己的 `EventHandler`, 所以原生版切到别的窗口而主循环继续运行时,
+//! 游戏不会暂停也不会静音.
+//!
+//! [`stalled`] 检查的是另一件事: 主循环停住过 (有些平台拖动或最小化窗口时会这样),
+//! 这一帧的时间不可信, 和焦点无关
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// 超过这个时长的一帧视为主循环被挂起过
+const STALL: f32 = 0.5;
+
+static FOCUSED: AtomicBool = AtomicBool::new(true);
+
+/// 由 `web/focus.js` 在网页可见性或焦点变化时调用
+#[cfg(target_arch = "wasm32")]
+#[unsafe(no_mangle)]
+pub extern "C" fn focus_changed(focused: bool) {
+    FOCUSED.store(focused, Ordering::Relaxed);
+}
+
+/// miniquad 用它检查 JS 插件和 Rust 代码的版本是否一致
+#[cfg(target_arch = "wasm32")]
+#[unsafe(no_mangle)]
+pub extern "C" fn focus_crate_version() -> u32 {
+    1
+}
+
+/// 长度为 `frame_time` 的一帧之前主循环是否被挂起过
+pub fn stalled(frame_time: f32) -> bool {
+    frame_time > STALL
+}
+
+/// 焦点的变化
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FocusChange {
+    Lost,
+    Regained,
+}
+
+impl FocusChange {
+    fn detect(was_focused: bool, focused: bool) -> Option<Self> {
+        match (was_focused, focused) {
+            (true, false) => Some(FocusChange::Lost),
+            (false, true) => Some(FocusChange::Regained),
+            _ => None,
+        }
+    }
+}
+
+pub struct FocusWatcher {
+    focused: bool,
+}
+
+impl FocusWatcher {
+    pub fn new() -> Self {
+        Self { focused: true }
+    }
+
+    /// 窗口当前是否有焦点, 原生平台上总是 `true`
+    pub fn focused(&self) -> bool {
+        self.focused
+    }
+
+    /// 每帧开始时调用一次, 返回这一帧焦点的变化
+    pub fn update(&mut self) -> Option<FocusChange> {
+        let focused = FOCUSED.load(Ordering::Relaxed);
+        let change = FocusChange::detect(self.focused, focused);
+        self.focused = focused;
+        change
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_focus_changes() {
+        assert_eq!(FocusChange::detect(true, false), Some(FocusChange::Lost));
+        assert_eq!(
+            FocusChange::detect(false, true),
+            Some(FocusChange::Regained)
+        );
+        assert_eq!(FocusChange::detect(true, true), None);
+        assert_eq!(FocusChange::detect(false, false), None);
+    }
+
+    #[test]
+    fn long_frames_are_stalls() {
+        assert!(stalled(3.0));
+        assert!(!stalled(0.016));
+    }
+}
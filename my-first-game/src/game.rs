@@ -0,0 +1,274 @@
+use macroquad::prelude::*;
+use macroquad_particles::Emitter;
+
+use crate::MAX_EFFECTS;
+use crate::achievements::Profile;
+use crate::audio::Audio;
+use crate::canvas::Anchor;
+use crate::clock::Clock;
+use crate::effects::{EffectManager, Explosions};
+use crate::events::{EventBus, GameEvent};
+use crate::hud::{Hud, HudView, Toasts};
+use crate::leaderboard::{Entry, LEADERBOARDS_SAVE, Leaderboards};
+use crate::mode::{self, GameMode};
+use crate::paint::{Painter, Screen, WorldView};
+use crate::player::{CoopSettings, Player};
+use crate::presets::ParticleLibrary;
+use crate::save::{self, load_high_score};
+use crate::score::Scoreboard;
+use crate::settings::{Accessibility, SETTINGS_SAVE};
+use crate::sim::World;
+
+/// 所有场景共享的游戏上下文
+pub struct Game {
+    pub font: Option<Font>,
+    pub presets: ParticleLibrary,
+    /// 本帧预设文件是否被热重载
+    pub presets_reloaded: bool,
+    /// 爆炸粒子特效
+    pub effects: EffectManager,
+    pub audio: Audio,
+    pub hud: Hud,
+    /// 成就解锁提示
+    pub toasts: Toasts,
+    /// 本帧发布的游戏事件
+    pub events: EventBus<GameEvent>,
+    /// 辅助功能设置
+    pub settings: Accessibility,
+
+    /// 当前这一局的模拟状态
+    pub world: World,
+    /// 每名玩家的火箭尾焰粒子, 和 `world.players` 一一对应
+    pub exhausts: Vec<Emitter>,
+    /// 下一局的玩家人数, 在主菜单中选择
+    pub player_count: usize,
+    pub coop: CoopSettings,
+    pub scoreboard: Scoreboard,
+    /// 下一局的游戏模式, 在主菜单中选择
+    pub mode: GameMode,
+    /// 本局开始的日期, 每日挑战按它生成种子
+    pub day: u32,
+    pub leaderboards: Leaderboards,
+    /// 上一局在排行榜中的名次
+    pub last_rank: Option<usize>,
+    /// 当前档案的名字
+    pub profile_name: String,
+    /// 当前档案的统计数据和成就
+    pub profile: Profile,
+
+    /// 由自动驾驶控制一号玩家, 由调试控制台开关
+    pub autopilot: bool,
+    /// 调试控制台设置的额外时间缩放
+    pub debug_time_scale: f32,
+    /// 控制台打开时屏蔽游戏按键
+    pub input_blocked: bool,
+    /// 窗口没有焦点 (只有网页版能检测到), 或这一帧之前主循环停住过, 游戏中会自动暂停
+    pub focus_lost: bool,
+    /// 本帧在画布内点击或触摸过, 黑边上的点击不算
+    pub tap: bool,
+    /// 区分游戏时间和界面时间, 暂停时只有游戏时间停止
+    pub clock: Clock,
+
+    /// 背景星空的偏移
+    #[cfg(not(target_arch = "wasm32"))]
+    pub direction_modifier: f32,
+}
+
+impl Game {
+    pub async fn new(profile_name: String) -> Self {
+        // 加载字体
+        let font = load_ttf_font("my-first-game/assets/fonts/NotoSansSC-Regular.ttf")
+            .await
+            .ok();
+
+        // 加载粒子预设
+        let presets = ParticleLibrary::load().await;
+
+        Self {
+            font,
+            presets,
+            presets_reloaded: false,
+            effects: EffectManager::new(MAX_EFFECTS),
+            audio: Audio::load().await,
+            hud: Hud::default(),
+            toasts: Toasts::default(),
+            events: EventBus::new(),
+            settings: save::load(SETTINGS_SAVE),
+            world: World::default(),
+            exhausts: vec![],
+            player_count: 1,
+            coop: CoopSettings::default(),
+            scoreboard: Scoreboard::new(0),
+            mode: GameMode::Endless,
+            day: mode::today(),
+            leaderboards: load_leaderboards(&profile_name),
+            last_rank: None,
+            profile: save::load(&Profile::save_name(&profile_name)),
+            profile_name,
+            autopilot: false,
+            debug_time_scale: 1.0,
+            input_blocked: false,
+            focus_lost: false,
+            tap: false,
+            clock: Clock::default(),
+            #[cfg(not(target_arch = "wasm32"))]
+            direction_modifier: 0.0,
+        }
+    }
+
+    /// 开始新的一局
+    pub fn reset(&mut self) {
+        self.day = mode::today();
+        let seed = match self.mode {
+            GameMode::DailyChallenge => mode::daily_seed(self.day),
+            _ => rand::rand() as u64,
+        };
+        // 调试时开启的无敌模式在新的一局中保留
+        let god = self.world.god;
+        self.world = World::new(self.player_count, self.coop, &self.profile.upgrades, seed);
+        self.world.god = god;
+        self.exhausts = self
+            .world
+            .players
+            .iter()
+            .map(|_| Emitter::new(self.presets.get("rocket_exhaust")))
+            .collect();
+        self.scoreboard.high_score = self.leaderboards.best(self.mode, self.day);
+        self.last_rank = None;
+    }
+
+    /// 热重载粒子预设, 重建常驻的发射器
+    pub fn hot_reload(&mut self) {
+        self.presets_reloaded = self.presets.hot_reload();
+        if self.presets_reloaded {
+            for exhaust in self.exhausts.iter_mut() {
+                *exhaust = Emitter::new(self.presets.get("rocket_exhaust"));
+            }
+            self.effects.invalidate();
+        }
+    }
+
+    /// 把本帧发布的事件分发给各个订阅者
+    pub fn dispatch_events(&mut self) {
+        let mut final_score = None;
+        let mut game_over = |event: &GameEvent| {
+            if let GameEvent::GameOver { score, .. } = *event {
+                final_score = Some(score);
+            }
+        };
+        let mut explosions = Explosions {
+            effects: &mut self.effects,
+            presets: &self.presets,
+        };
+        self.events.dispatch(&mut [
+            &mut self.scoreboard,
+            &mut self.profile,
+            &mut explosions,
+            &mut self.audio,
+            &mut self.hud,
+            &mut self.toasts,
+            &mut self.clock,
+            &mut game_over,
+        ]);
+
+        // 一局结束时提交到当前模式的排行榜
+        if let Some(score) = final_score {
+            self.last_rank = self.leaderboards.submit(
+                self.mode,
+                Entry {
+                    profile: self.profile_name.clone(),
+                    score,
+                    day: self.day,
+                },
+            );
+            save::store(LEADERBOARDS_SAVE, &self.leaderboards);
+        }
+
+        // 新成就在下一帧作为事件分发, 解锁成就或一局结束时保存档案
+        let unlocked = self.profile.take_unlocked();
+        if final_score.is_some() || !unlocked.is_empty() {
+            save::store(&Profile::save_name(&self.profile_name), &self.profile);
+        }
+        for achievement in unlocked {
+            self.events
+                .publish(GameEvent::AchievementUnlocked(achievement));
+        }
+    }
+
+    pub fn key_down(&self, key: KeyCode) -> bool {
+        !self.input_blocked && is_key_down(key)
+    }
+
+    pub fn key_pressed(&self, key: KeyCode) -> bool {
+        !self.input_blocked && is_key_pressed(key)
+    }
+
+    /// 点击画布和按空格效果相同
+    pub fn confirm_pressed(&self) -> bool {
+        self.key_pressed(KeyCode::Space) || (!self.input_blocked && self.tap)
+    }
+
+    /// 绘制到窗口的画笔, 字号按辅助功能设置缩放
+    pub fn screen(&self) -> Screen<'_> {
+        Screen {
+            font: self.font.as_ref(),
+            settings: &self.settings,
+        }
+    }
+
+    /// 绘制文字, 字号按辅助功能设置缩放
+    pub fn draw_text(&self, text: &str, x: f32, y: f32, font_size: u16, color: Color) {
+        self.screen().text(text, x, y, font_size, color);
+    }
+
+    /// 在屏幕水平居中的位置绘制文字, 返回文字尺寸
+    pub fn draw_centered_text(
+        &self,
+        text: &str,
+        y: f32,
+        font_size: u16,
+        color: Color,
+    ) -> TextDimensions {
+        self.screen().centered_text(text, y, font_size, color)
+    }
+
+    /// 贴着画布边缘绘制一行文字
+    pub fn draw_anchored(&self, text: &str, anchor: Anchor, font_size: u16, color: Color) {
+        self.screen().anchored_text(text, anchor, font_size, color);
+    }
+
+    /// 测量缩放后的文字尺寸
+    pub fn measure_text(&self, text: &str, font_size: u16) -> TextDimensions {
+        self.screen().measure_text(text, font_size)
+    }
+
+    /// 渲染玩家、子弹、方块和界面
+    pub fn draw_world(&self) {
+        let mut screen = self.screen();
+        WorldView {
+            ships: self.world.players.iter().map(Player::ship).collect(),
+            pickups: &self.world.pickups,
+            squares: &self.world.squares,
+            settings: &self.settings,
+        }
+        .paint(&mut screen);
+        self.hud.paint(&mut screen, &HudView::new(self));
+    }
+}
+
+/// 读取排行榜, 旧版本保存的最高分迁移到无尽模式的排行榜
+fn load_leaderboards(profile_name: &str) -> Leaderboards {
+    let mut leaderboards: Leaderboards = save::load(LEADERBOARDS_SAVE);
+    let high_score = load_high_score();
+    if high_score > leaderboards.best(GameMode::Endless, 0) {
+        leaderboards.submit(
+            GameMode::Endless,
+            Entry {
+                profile: profile_name.to_string(),
+                score: high_score,
+                day: mode::today(),
+            },
+        );
+    }
+    leaderboards
+}
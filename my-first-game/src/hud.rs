@@ -0,0 +1,225 @@
+use std::collections::VecDeque;
+
+use macroquad::prelude::*;
+
+use crate::achievements::Achievement;
+use crate::canvas::{Anchor, MARGIN, VIRTUAL_HEIGHT, VIRTUAL_WIDTH};
+use crate::events::{GameEvent, Subscriber};
+use crate::game::Game;
+use crate::paint::Painter;
+
+/// 波次提示的显示时长
+const BANNER_DURATION: f32 = 2.0;
+/// 得分飘字的显示时长
+const POPUP_DURATION: f32 = 0.8;
+/// 每条成就提示的显示时长
+const TOAST_DURATION: f32 = 3.0;
+/// 成就提示滑入和滑出的时长
+const TOAST_SLIDE: f32 = 0.3;
+
+/// 分数的字号
+const HUD_FONT_SIZE: u16 = 25;
+
+/// 击毁方块时飘出的得分
+struct Popup {
+    text: String,
+    pos: Vec2,
+    /// 已经显示的时间
+    age: f32,
+}
+
+/// 界面, 显示分数、波次提示和得分飘字
+#[derive(Default)]
+pub struct Hud {
+    /// 波次提示和剩余显示时间
+    banner: Option<(String, f32)>,
+    popups: Vec<Popup>,
+}
+
+impl Hud {
+    pub fn update(&mut self, dt: f32) {
+        if let Some((_, remaining)) = self.banner.as_mut() {
+            *remaining -= dt;
+            if *remaining <= 0.0 {
+                self.banner = None;
+            }
+        }
+        for popup in self.popups.iter_mut() {
+            popup.age += dt;
+            popup.pos.y -= 40.0 * dt;
+        }
+        self.popups.retain(|popup| popup.age < POPUP_DURATION);
+    }
+
+    pub fn paint(&self, p: &mut impl Painter, view: &HudView) {
+        // region: 渲染分数和历史最高分数
+        // 渲染分数
+        p.anchored_text(
+            &format!("当前分数: {}", view.score),
+            Anchor::TopLeft,
+            HUD_FONT_SIZE,
+            WHITE,
+        );
+
+        // 渲染历史最高分数
+        p.anchored_text(
+            &format!("历史最高分: {}", view.high_score),
+            Anchor::TopRight,
+            HUD_FONT_SIZE,
+            YELLOW,
+        );
+        // endregion
+
+        // 限时模式显示剩余时间
+        if let Some(remaining) = view.remaining {
+            p.anchored_text(
+                &format!("{}:{:02}", remaining / 60, remaining % 60),
+                Anchor::TopCenter,
+                30,
+                if remaining <= 10 { RED } else { WHITE },
+            );
+        }
+
+        let mut y = 60.0;
+        for (text, color) in &view.lines {
+            p.text(text, 10.0, y, 20, *color);
+            y += 25.0;
+        }
+
+        for popup in &self.popups {
+            let alpha = 1.0 - popup.age / POPUP_DURATION;
+            p.text(
+                &popup.text,
+                popup.pos.x,
+                popup.pos.y,
+                20,
+                Color::new(1.0, 1.0, 1.0, alpha),
+            );
+        }
+        if let Some((text, remaining)) = &self.banner {
+            let alpha = remaining.min(0.5) * 2.0;
+            p.centered_text(
+                text,
+                VIRTUAL_HEIGHT / 3.0,
+                40,
+                Color::new(1.0, 0.8, 0.0, alpha),
+            );
+        }
+    }
+}
+
+/// 界面显示的游戏数据, 和 `Game` 分开以便在测试中直接构造
+#[derive(Debug, Default)]
+pub struct HudView {
+    pub score: u32,
+    pub high_score: u32,
+    /// 限时模式的剩余秒数
+    pub remaining: Option<u32>,
+    /// 分数下方的状态行
+    pub lines: Vec<(String, Color)>,
+}
+
+impl HudView {
+    pub fn new(game: &Game) -> Self {
+        let scoreboard = &game.scoreboard;
+        let remaining = game
+            .mode
+            .time_limit()
+            .map(|limit| (limit - game.world.run_time).max(0.0).ceil() as u32);
+
+        // 双人模式下显示各自的分数和剩余的备用生命
+        let mut lines = vec![];
+        if game.world.players.len() > 1 {
+            if game.coop.shared_lives {
+                lines.push((format!("共享生命: {}", game.world.shared_lives), WHITE));
+            }
+            for (index, player) in game.world.players.iter().enumerate() {
+                let mut text = format!("P{}", index + 1);
+                if !game.coop.shared_score {
+                    text += &format!(" 分数: {}", scoreboard.scores[index]);
+                }
+                if player.down {
+                    text += " 已倒下";
+                } else if !game.coop.shared_lives {
+                    text += &format!(" 生命: {}", player.lives);
+                }
+                lines.push((text, player.ship.color));
+            }
+        }
+
+        Self {
+            score: scoreboard.score,
+            high_score: scoreboard.high_score,
+            remaining,
+            lines,
+        }
+    }
+}
+
+impl Subscriber<GameEvent> for Hud {
+    fn on_event(&mut self, event: &GameEvent) {
+        match *event {
+            GameEvent::RunStarted => self.popups.clear(),
+            GameEvent::WaveStarted { wave } => {
+                self.banner = Some((format!("第 {} 波", wave), BANNER_DURATION));
+            }
+            GameEvent::EnemyDestroyed { x, y, size, .. } => self.popups.push(Popup {
+                text: format!("+{}", size.round() as u32),
+                pos: vec2(x, y),
+                age: 0.0,
+            }),
+            _ => {}
+        }
+    }
+}
+
+/// 成就解锁提示, 多个成就依次显示
+#[derive(Default)]
+pub struct Toasts {
+    queue: VecDeque<Achievement>,
+    /// 队首的提示已经显示的时间
+    age: f32,
+}
+
+impl Toasts {
+    pub fn update(&mut self, dt: f32) {
+        if self.queue.is_empty() {
+            return;
+        }
+        self.age += dt;
+        if self.age >= TOAST_DURATION {
+            self.queue.pop_front();
+            self.age = 0.0;
+        }
+    }
+
+    pub fn paint(&self, p: &mut impl Painter) {
+        let Some(achievement) = self.queue.front() else {
+            return;
+        };
+        let (w, h) = (320.0, 60.0);
+        // 从屏幕右侧滑入, 结束前滑出
+        let slide = (self.age.min(TOAST_DURATION - self.age) / TOAST_SLIDE).min(1.0);
+        let target = Anchor::TopRight.place(vec2(w, h), MARGIN);
+        let x = VIRTUAL_WIDTH + (target.x - VIRTUAL_WIDTH) * slide;
+        let y = target.y + 40.0;
+        p.rect(x, y, w, h, Color::new(0.0, 0.0, 0.0, 0.8));
+        p.rect_lines(x, y, w, h, 2.0, GOLD);
+        p.text(
+            &format!("成就解锁: {}", achievement.title()),
+            x + 10.0,
+            y + 25.0,
+            22,
+            GOLD,
+        );
+        p.text(achievement.description(), x + 10.0, y + 50.0, 18, WHITE);
+    }
+}
+
+impl Subscriber<GameEvent> for Toasts {
+    fn on_event(&mut self, event: &GameEvent) {
+        if let GameEvent::AchievementUnlocked(achievement) = *event {
+            self.queue.push_back(achievement);
+        }
+    }
+}
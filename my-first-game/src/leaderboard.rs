@@ -0,0 +1,116 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::mode::GameMode;
+
+/// 每个模式保留的记录条数
+pub const LEADERBOARD_SIZE: usize = 10;
+/// 排行榜在存档目录中的文件名
+pub const LEADERBOARDS_SAVE: &str = "leaderboards";
+
+/// 一条排行榜记录
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Entry {
+    /// 档案名
+    pub profile: String,
+    pub score: u32,
+    /// 创造记录的日期, 见 [`crate::mode::today`]
+    pub day: u32,
+}
+
+/// 各个模式独立的本地排行榜
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Leaderboards {
+    boards: BTreeMap<GameMode, Vec<Entry>>,
+}
+
+impl Leaderboards {
+    /// 提交一条记录, 返回它的名次(从 0 开始), 没有上榜返回 `None`
+    ///
+    /// 每日挑战只保留当天的记录
+    pub fn submit(&mut self, mode: GameMode, entry: Entry) -> Option<usize> {
+        let board = self.boards.entry(mode).or_default();
+        if mode == GameMode::DailyChallenge {
+            board.retain(|old| old.day == entry.day);
+        }
+        // 同分时先创造的记录排在前面
+        let rank = board.partition_point(|old| old.score >= entry.score);
+        if rank >= LEADERBOARD_SIZE {
+            return None;
+        }
+        board.insert(rank, entry);
+        board.truncate(LEADERBOARD_SIZE);
+        Some(rank)
+    }
+
+    /// `mode` 在 `day` 这一天有效的记录, 按分数从高到低排列
+    pub fn entries(&self, mode: GameMode, day: u32) -> &[Entry] {
+        match self.boards.get(&mode) {
+            Some(board)
+                if mode != GameMode::DailyChallenge
+                    || board.first().is_some_and(|entry| entry.day == day) =>
+            {
+                board
+            }
+            _ => &[],
+        }
+    }
+
+    /// `mode` 的最高分
+    pub fn best(&self, mode: GameMode, day: u32) -> u32 {
+        self.entries(mode, day)
+            .first()
+            .map_or(0, |entry| entry.score)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(score: u32, day: u32) -> Entry {
+        Entry {
+            profile: "player".to_string(),
+            score,
+            day,
+        }
+    }
+
+    #[test]
+    fn entries_are_sorted_and_truncated() {
+        let mut boards = Leaderboards::default();
+        for score in 0..15 {
+            boards.submit(GameMode::Endless, entry(score * 10, 0));
+        }
+        let scores: Vec<u32> = boards
+            .entries(GameMode::Endless, 0)
+            .iter()
+            .map(|entry| entry.score)
+            .collect();
+        assert_eq!(scores, [140, 130, 120, 110, 100, 90, 80, 70, 60, 50]);
+        assert_eq!(boards.submit(GameMode::Endless, entry(10, 0)), None);
+        assert_eq!(boards.submit(GameMode::Endless, entry(130, 0)), Some(2));
+    }
+
+    #[test]
+    fn modes_are_separate() {
+        let mut boards = Leaderboards::default();
+        boards.submit(GameMode::Endless, entry(100, 0));
+        boards.submit(GameMode::ScoreAttack, entry(50, 0));
+        assert_eq!(boards.best(GameMode::Endless, 0), 100);
+        assert_eq!(boards.best(GameMode::ScoreAttack, 0), 50);
+        assert_eq!(boards.best(GameMode::DailyChallenge, 0), 0);
+    }
+
+    #[test]
+    fn daily_board_only_keeps_today() {
+        let mut boards = Leaderboards::default();
+        boards.submit(GameMode::DailyChallenge, entry(100, 1));
+        assert_eq!(boards.best(GameMode::DailyChallenge, 1), 100);
+        assert_eq!(boards.best(GameMode::DailyChallenge, 2), 0);
+        boards.submit(GameMode::DailyChallenge, entry(20, 2));
+        assert_eq!(boards.entries(GameMode::DailyChallenge, 2), [entry(20, 2)]);
+    }
+}
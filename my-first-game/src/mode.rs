@@ -0,0 +1,84 @@
+use serde::{Deserialize, Serialize};
+
+/// 限时模式每局的时长(秒)
+const SCORE_ATTACK_DURATION: f32 = 180.0;
+
+/// 游戏模式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default, Serialize, Deserialize)]
+pub enum GameMode {
+    /// 无尽模式, 直到所有玩家倒下
+    #[default]
+    Endless,
+    /// 限时 3 分钟, 比拼分数
+    ScoreAttack,
+    /// 每日挑战, 同一天的方块生成顺序都相同
+    DailyChallenge,
+}
+
+impl GameMode {
+    pub const ALL: [GameMode; 3] = [
+        GameMode::Endless,
+        GameMode::ScoreAttack,
+        GameMode::DailyChallenge,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            GameMode::Endless => "ENDLESS",
+            GameMode::ScoreAttack => "SCORE ATTACK",
+            GameMode::DailyChallenge => "DAILY CHALLENGE",
+        }
+    }
+
+    /// 在所有模式之间循环切换
+    pub fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|mode| *mode == self).unwrap();
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    /// 每局的时长, 无尽模式没有限制
+    pub fn time_limit(self) -> Option<f32> {
+        match self {
+            GameMode::ScoreAttack => Some(SCORE_ATTACK_DURATION),
+            _ => None,
+        }
+    }
+}
+
+/// 今天是 1970-01-01 (UTC) 之后的第几天
+pub fn today() -> u32 {
+    (macroquad::miniquad::date::now() / 86400.0) as u32
+}
+
+/// 由日期得到每日挑战的种子, 同一天所有玩家相同
+pub fn daily_seed(day: u32) -> u64 {
+    // splitmix64, 让相邻日期的种子差异足够大
+    let mut z = (day as u64).wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn daily_seed_depends_only_on_day() {
+        assert_eq!(daily_seed(20_000), daily_seed(20_000));
+        assert_ne!(daily_seed(20_000), daily_seed(20_001));
+    }
+
+    #[test]
+    fn next_cycles_through_all_modes() {
+        let mut mode = GameMode::Endless;
+        for expected in [
+            GameMode::ScoreAttack,
+            GameMode::DailyChallenge,
+            GameMode::Endless,
+        ] {
+            mode = mode.next();
+            assert_eq!(mode, expected);
+        }
+    }
+}
@@ -0,0 +1,211 @@
+//! 绘制接口, 游戏画面既可以画到窗口, 也可以在测试中用软件渲染画到内存
+
+use macroquad::prelude::*;
+
+use crate::canvas::{Anchor, MARGIN, VIRTUAL_WIDTH};
+use crate::powerup::Pickup;
+use crate::settings::Accessibility;
+use crate::shape::Shape;
+
+/// 游戏用到的基本绘制操作, 坐标都是虚拟画布坐标
+pub trait Painter {
+    fn rect(&mut self, x: f32, y: f32, w: f32, h: f32, color: Color);
+    fn rect_lines(&mut self, x: f32, y: f32, w: f32, h: f32, thickness: f32, color: Color);
+    fn circle(&mut self, x: f32, y: f32, r: f32, color: Color);
+    fn circle_lines(&mut self, x: f32, y: f32, r: f32, thickness: f32, color: Color);
+    /// 正多边形, `rotation` 为角度
+    fn poly(&mut self, x: f32, y: f32, sides: u8, radius: f32, rotation: f32, color: Color);
+    #[allow(clippy::too_many_arguments)]
+    fn poly_lines(
+        &mut self,
+        x: f32,
+        y: f32,
+        sides: u8,
+        radius: f32,
+        rotation: f32,
+        thickness: f32,
+        color: Color,
+    );
+    /// 绘制文字, `y` 为基线位置
+    fn text(&mut self, text: &str, x: f32, y: f32, font_size: u16, color: Color);
+    fn measure_text(&self, text: &str, font_size: u16) -> TextDimensions;
+
+    /// 在画布水平居中的位置绘制文字, 返回文字尺寸
+    fn centered_text(
+        &mut self,
+        text: &str,
+        y: f32,
+        font_size: u16,
+        color: Color,
+    ) -> TextDimensions {
+        let dimensions = self.measure_text(text, font_size);
+        self.text(
+            text,
+            VIRTUAL_WIDTH / 2.0 - dimensions.width / 2.0,
+            y,
+            font_size,
+            color,
+        );
+        dimensions
+    }
+
+    /// 贴着画布边缘绘制一行文字
+    fn anchored_text(&mut self, text: &str, anchor: Anchor, font_size: u16, color: Color) {
+        let dimensions = self.measure_text(text, font_size);
+        let pos = anchor.place(vec2(dimensions.width, dimensions.height), MARGIN);
+        self.text(text, pos.x, pos.y + dimensions.offset_y, font_size, color);
+    }
+}
+
+/// 用 macroquad 绘制到当前的渲染目标, 字号按辅助功能设置缩放
+pub struct Screen<'a> {
+    pub font: Option<&'a Font>,
+    pub settings: &'a Accessibility,
+}
+
+impl Painter for Screen<'_> {
+    fn rect(&mut self, x: f32, y: f32, w: f32, h: f32, color: Color) {
+        draw_rectangle(x, y, w, h, color);
+    }
+
+    fn rect_lines(&mut self, x: f32, y: f32, w: f32, h: f32, thickness: f32, color: Color) {
+        draw_rectangle_lines(x, y, w, h, thickness, color);
+    }
+
+    fn circle(&mut self, x: f32, y: f32, r: f32, color: Color) {
+        draw_circle(x, y, r, color);
+    }
+
+    fn circle_lines(&mut self, x: f32, y: f32, r: f32, thickness: f32, color: Color) {
+        draw_circle_lines(x, y, r, thickness, color);
+    }
+
+    fn poly(&mut self, x: f32, y: f32, sides: u8, radius: f32, rotation: f32, color: Color) {
+        draw_poly(x, y, sides, radius, rotation, color);
+    }
+
+    fn poly_lines(
+        &mut self,
+        x: f32,
+        y: f32,
+        sides: u8,
+        radius: f32,
+        rotation: f32,
+        thickness: f32,
+        color: Color,
+    ) {
+        draw_poly_lines(x, y, sides, radius, rotation, thickness, color);
+    }
+
+    fn text(&mut self, text: &str, x: f32, y: f32, font_size: u16, color: Color) {
+        draw_text_ex(
+            text,
+            x,
+            y,
+            TextParams {
+                font: self.font,
+                font_size: self.settings.font_size(font_size),
+                color,
+                ..Default::default()
+            },
+        );
+    }
+
+    fn measure_text(&self, text: &str, font_size: u16) -> TextDimensions {
+        // font_scale: 缩放倍数
+        measure_text(text, self.font, self.settings.font_size(font_size), 1.0)
+    }
+}
+
+/// 一名玩家的飞船和子弹
+pub struct Ship<'a> {
+    pub shape: &'a Shape,
+    pub bullets: &'a [Shape],
+    /// 倒下和无敌闪烁时的透明度
+    pub alpha: f32,
+    pub shield: u32,
+}
+
+impl Ship<'_> {
+    fn paint(&self, p: &mut impl Painter, settings: &Accessibility) {
+        let ship = self.shape;
+        let color = settings.friendly(ship.color);
+        p.circle(
+            ship.x,
+            ship.y,
+            ship.size / 4.0,
+            Color {
+                a: self.alpha,
+                ..DARKBLUE
+            },
+        );
+        p.circle(
+            ship.x,
+            ship.y,
+            ship.size / 2.0,
+            Color {
+                a: color.a * self.alpha,
+                ..color
+            },
+        );
+        if let Some(outline) = settings.outline() {
+            p.circle_lines(ship.x, ship.y, ship.size / 2.0, 2.0, outline);
+        }
+        // 每层护盾一个圆环
+        for layer in 0..self.shield {
+            p.circle_lines(
+                ship.x,
+                ship.y,
+                ship.size / 2.0 + 4.0 + layer as f32 * 4.0,
+                2.0,
+                Color::new(0.4, 0.8, 1.0, 0.8 * self.alpha),
+            );
+        }
+    }
+}
+
+/// 场景中需要绘制的物体
+pub struct WorldView<'a> {
+    pub ships: Vec<Ship<'a>>,
+    pub pickups: &'a [Pickup],
+    pub squares: &'a [Shape],
+    pub settings: &'a Accessibility,
+}
+
+impl WorldView<'_> {
+    pub fn paint(&self, p: &mut impl Painter) {
+        let settings = self.settings;
+        // 渲染玩家
+        for ship in &self.ships {
+            ship.paint(p, settings);
+        }
+        // 渲染子弹
+        for bullet in self.ships.iter().flat_map(|ship| ship.bullets) {
+            // region: 这会放bullet每帧在圆圈和实心圆之间跳动
+            // if rand::gen_range(0, 99) > 50 {
+            //     draw_circle_lines(bullet.x, bullet.y, bullet.size / 2.0, 5.0, bullet.color);
+            // } else {
+            //     draw_circle(bullet.x, bullet.y, bullet.size / 2.0, bullet.color);
+            // }
+            // endregion
+            let color = settings.friendly(bullet.color);
+            p.circle_lines(bullet.x, bullet.y, bullet.size / 2.0, 5.0, color);
+            if let Some(outline) = settings.outline() {
+                p.circle_lines(bullet.x, bullet.y, bullet.size / 2.0 + 3.0, 1.0, outline);
+            }
+        }
+        // 渲染道具
+        for pickup in self.pickups {
+            pickup.paint(p);
+        }
+        // 渲染方块
+        for square in self.squares {
+            let (x, y) = (square.x - square.size / 2.0, square.y - square.size / 2.0);
+            let color = settings.hostile(square.color);
+            p.rect(x, y, square.size, square.size, color);
+            if let Some(outline) = settings.outline() {
+                p.rect_lines(x, y, square.size, square.size, 3.0, outline);
+            }
+        }
+    }
+}
@@ -0,0 +1,199 @@
+use macroquad::prelude::*;
+
+use crate::canvas::{VIRTUAL_HEIGHT, VIRTUAL_WIDTH};
+use crate::game::Game;
+use crate::paint::Ship;
+use crate::shape::Shape;
+use crate::shop::Upgrades;
+use crate::sim::Input;
+
+/// 最多支持的玩家数量
+pub const MAX_PLAYERS: usize = 2;
+/// 每名玩家的同屏子弹上限, 散射时按每次发射的颗数翻倍
+pub const BULLETS_PER_PLAYER: usize = 4;
+/// 双人模式下每名玩家的备用生命
+const COOP_LIVES: u32 = 1;
+/// 被撞后复活的无敌时间(秒)
+const RESPAWN_INVULNERABLE: f32 = 2.0;
+/// 护盾被击破后的无敌时间(秒)
+const SHIELD_INVULNERABLE: f32 = 1.0;
+
+/// 玩家的按键绑定
+pub struct Controls {
+    pub up: KeyCode,
+    pub down: KeyCode,
+    pub left: KeyCode,
+    pub right: KeyCode,
+    pub fire: KeyCode,
+}
+
+impl Controls {
+    /// 一号玩家: 方向键移动, 空格射击
+    pub const ARROWS: Controls = Controls {
+        up: KeyCode::Up,
+        down: KeyCode::Down,
+        left: KeyCode::Left,
+        right: KeyCode::Right,
+        fire: KeyCode::Space,
+    };
+
+    /// 二号玩家: WASD 移动, F 射击
+    pub const WASD: Controls = Controls {
+        up: KeyCode::W,
+        down: KeyCode::S,
+        left: KeyCode::A,
+        right: KeyCode::D,
+        fire: KeyCode::F,
+    };
+
+    /// 读取本帧的按键
+    pub fn input(&self, game: &Game) -> Input {
+        let direction = vec2(
+            if game.key_down(self.right) {
+                1.0
+            } else if game.key_down(self.left) {
+                -1.0
+            } else {
+                0.0
+            },
+            if game.key_down(self.down) {
+                1.0
+            } else if game.key_down(self.up) {
+                -1.0
+            } else {
+                0.0
+            },
+        );
+        Input {
+            direction,
+            fire: game.key_pressed(self.fire),
+        }
+    }
+}
+
+/// 双人模式的设置, 在主菜单中切换
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CoopSettings {
+    /// 两名玩家共用一个分数
+    pub shared_score: bool,
+    /// 两名玩家共用一个生命池
+    pub shared_lives: bool,
+}
+
+impl CoopSettings {
+    /// 开局时每名玩家和共享生命池的备用生命
+    pub fn lives(&self, players: usize) -> (u32, u32) {
+        if players < 2 {
+            (0, 0)
+        } else if self.shared_lives {
+            (0, COOP_LIVES * players as u32)
+        } else {
+            (COOP_LIVES, 0)
+        }
+    }
+}
+
+/// 玩家
+pub struct Player {
+    pub ship: Shape,
+    /// 该玩家发射的子弹
+    pub bullets: Vec<Shape>,
+    pub controls: Controls,
+    /// 本帧的移动方向, 用于调整尾焰
+    pub velocity: Vec2,
+    /// 同屏子弹上限, 不含散射的加成
+    pub bullet_cap: usize,
+    /// 剩余的护盾层数, 每层抵挡一次碰撞
+    pub shield: u32,
+    /// 道具吸附半径
+    pub magnet: f32,
+    /// 剩余的备用生命, 共享生命池时不使用
+    pub lives: u32,
+    /// 复活后剩余的无敌时间
+    pub invulnerable: f32,
+    /// 生命耗尽, 等待其他玩家
+    pub down: bool,
+    /// 出生点
+    spawn: Vec2,
+}
+
+impl Player {
+    /// 创建第 `index` 名玩家, 按玩家人数均匀分布在屏幕中间
+    /// 商店的升级在这里生效
+    pub fn new(index: usize, players: usize, lives: u32, upgrades: &Upgrades) -> Self {
+        let spawn = vec2(
+            VIRTUAL_WIDTH * (index + 1) as f32 / (players + 1) as f32,
+            VIRTUAL_HEIGHT / 2.0,
+        );
+        Self {
+            ship: Shape {
+                size: 32.0,
+                speed: upgrades.move_speed(),
+                drift: 0.0,
+                x: spawn.x,
+                y: spawn.y,
+                color: color_u8!(
+                    rand::gen_range(0, 255),
+                    rand::gen_range(0, 255),
+                    rand::gen_range(0, 255),
+                    rand::gen_range(0, 255)
+                ),
+                collided: false,
+            },
+            bullets: vec![],
+            controls: if index == 0 {
+                Controls::ARROWS
+            } else {
+                Controls::WASD
+            },
+            velocity: Vec2::ZERO,
+            bullet_cap: upgrades.bullet_cap(),
+            shield: upgrades.shield(),
+            magnet: upgrades.magnet_radius(),
+            lives,
+            invulnerable: 0.0,
+            down: false,
+            spawn,
+        }
+    }
+
+    /// 是否还能被方块撞到
+    pub fn vulnerable(&self) -> bool {
+        !self.down && self.invulnerable <= 0.0
+    }
+
+    /// 有护盾时消耗一层并短暂无敌, 返回是否抵挡了这次碰撞
+    pub fn absorb_hit(&mut self) -> bool {
+        if self.shield == 0 {
+            return false;
+        }
+        self.shield -= 1;
+        self.invulnerable = SHIELD_INVULNERABLE;
+        true
+    }
+
+    /// 回到出生点并短暂无敌
+    pub fn respawn(&mut self) {
+        self.ship.x = self.spawn.x;
+        self.ship.y = self.spawn.y;
+        self.invulnerable = RESPAWN_INVULNERABLE;
+    }
+
+    /// 绘制用的飞船数据
+    pub fn ship(&self) -> Ship<'_> {
+        // 倒下的玩家半透明, 无敌时闪烁
+        let alpha = if self.down {
+            0.3
+        } else if self.invulnerable > 0.0 && ((self.invulnerable * 10.0) as u32).is_multiple_of(2) {
+            0.4
+        } else {
+            1.0
+        };
+        Ship {
+            shape: &self.ship,
+            bullets: &self.bullets,
+            alpha,
+            shield: self.shield,
+        }
+    }
+}
@@ -0,0 +1,85 @@
+use macroquad::prelude::*;
+
+use crate::paint::Painter;
+
+/// 道具持续时间（秒）
+const POWERUP_DURATION: f32 = 10.0;
+/// 大方块被击毁时掉落道具的概率
+pub const DROP_CHANCE: f32 = 0.3;
+/// 掉落道具的大小
+pub const PICKUP_SIZE: f32 = 20.0;
+/// 掉落道具的下落速度
+const PICKUP_FALL_SPEED: f32 = 80.0;
+/// 被磁铁吸引时飞向玩家的速度
+const MAGNET_SPEED: f32 = 300.0;
+
+/// 道具种类
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PowerUp {
+    /// 散射, 一次发射三颗子弹
+    Spread,
+}
+
+impl PowerUp {
+    /// 解析控制台中的道具名
+    #[cfg(debug_assertions)]
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "spread" => Some(PowerUp::Spread),
+            _ => None,
+        }
+    }
+}
+
+/// 玩家身上生效中的道具及其剩余时间
+#[derive(Default)]
+pub struct PowerUps {
+    active: Vec<(PowerUp, f32)>,
+}
+
+impl PowerUps {
+    /// 获得道具, 已有同类道具时刷新持续时间
+    pub fn give(&mut self, kind: PowerUp) {
+        match self.active.iter_mut().find(|(k, _)| *k == kind) {
+            Some((_, remaining)) => *remaining = POWERUP_DURATION,
+            None => self.active.push((kind, POWERUP_DURATION)),
+        }
+    }
+
+    pub fn is_active(&self, kind: PowerUp) -> bool {
+        self.active.iter().any(|(k, _)| *k == kind)
+    }
+
+    pub fn update(&mut self, delta_time: f32) {
+        for (_, remaining) in self.active.iter_mut() {
+            *remaining -= delta_time;
+        }
+        self.active.retain(|(_, remaining)| *remaining > 0.0);
+    }
+}
+
+/// 掉落在场景中等待拾取的道具
+pub struct Pickup {
+    pub kind: PowerUp,
+    pub pos: Vec2,
+}
+
+impl Pickup {
+    /// 向下掉落, `target` 为磁铁范围内最近的玩家位置
+    pub fn update(&mut self, delta_time: f32, target: Option<Vec2>) {
+        match target {
+            Some(target) => {
+                let step = MAGNET_SPEED * delta_time;
+                let offset = target - self.pos;
+                self.pos += offset.clamp_length_max(step);
+            }
+            None => self.pos.y += PICKUP_FALL_SPEED * delta_time,
+        }
+    }
+
+    pub fn paint(&self, p: &mut impl Painter) {
+        let half = PICKUP_SIZE / 2.0;
+        p.poly(self.pos.x, self.pos.y, 4, half, 45.0, GOLD);
+        p.poly_lines(self.pos.x, self.pos.y, 4, half, 45.0, 2.0, WHITE);
+    }
+}
@@ -0,0 +1,514 @@
+use std::collections::BTreeMap;
+#[cfg(not(target_arch = "wasm32"))]
+use std::{fs, time::SystemTime};
+
+use macroquad::prelude::*;
+use macroquad_particles::{
+    AtlasConfig, BlendMode, ColorCurve, Curve, EmissionShape, EmitterConfig, Interpolation,
+    ParticleMaterial, ParticleShape, PostProcessing,
+};
+use serde::Deserialize;
+
+/// 粒子预设文件路径
+pub const PRESETS_PATH: &str = "my-first-game/assets/particles.ron";
+
+/// 编译期内置的默认预设，资源文件读取失败时兜底
+const BUILTIN_PRESETS: &str = include_str!("../assets/particles.ron");
+
+/// 热重载检查间隔（秒）
+#[cfg(not(target_arch = "wasm32"))]
+const HOT_RELOAD_INTERVAL: f64 = 0.5;
+
+/// 颜色, 各通道取值 0.0 ~ 1.0
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct ColorDef(pub f32, pub f32, pub f32, pub f32);
+
+impl From<ColorDef> for Color {
+    fn from(c: ColorDef) -> Self {
+        Color::new(c.0, c.1, c.2, c.3)
+    }
+}
+
+impl From<Color> for ColorDef {
+    fn from(c: Color) -> Self {
+        ColorDef(c.r, c.g, c.b, c.a)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct ColorCurveDef {
+    pub start: ColorDef,
+    pub mid: ColorDef,
+    pub end: ColorDef,
+}
+
+impl From<ColorCurveDef> for ColorCurve {
+    fn from(c: ColorCurveDef) -> Self {
+        ColorCurve {
+            start: c.start.into(),
+            mid: c.mid.into(),
+            end: c.end.into(),
+        }
+    }
+}
+
+impl From<&ColorCurve> for ColorCurveDef {
+    fn from(c: &ColorCurve) -> Self {
+        ColorCurveDef {
+            start: c.start.into(),
+            mid: c.mid.into(),
+            end: c.end.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub enum EmissionShapeDef {
+    Point,
+    Rect { width: f32, height: f32 },
+    Sphere { radius: f32 },
+}
+
+impl From<EmissionShapeDef> for EmissionShape {
+    fn from(s: EmissionShapeDef) -> Self {
+        match s {
+            EmissionShapeDef::Point => EmissionShape::Point,
+            EmissionShapeDef::Rect { width, height } => EmissionShape::Rect { width, height },
+            EmissionShapeDef::Sphere { radius } => EmissionShape::Sphere { radius },
+        }
+    }
+}
+
+impl From<&EmissionShape> for EmissionShapeDef {
+    fn from(s: &EmissionShape) -> Self {
+        match *s {
+            EmissionShape::Point => EmissionShapeDef::Point,
+            EmissionShape::Rect { width, height } => EmissionShapeDef::Rect { width, height },
+            EmissionShape::Sphere { radius } => EmissionShapeDef::Sphere { radius },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub enum ParticleShapeDef {
+    Rectangle {
+        aspect_ratio: f32,
+    },
+    Circle {
+        subdivisions: u32,
+    },
+    CustomMesh {
+        vertices: Vec<f32>,
+        indices: Vec<u16>,
+    },
+}
+
+impl From<ParticleShapeDef> for ParticleShape {
+    fn from(s: ParticleShapeDef) -> Self {
+        match s {
+            ParticleShapeDef::Rectangle { aspect_ratio } => {
+                ParticleShape::Rectangle { aspect_ratio }
+            }
+            ParticleShapeDef::Circle { subdivisions } => ParticleShape::Circle { subdivisions },
+            ParticleShapeDef::CustomMesh { vertices, indices } => {
+                ParticleShape::CustomMesh { vertices, indices }
+            }
+        }
+    }
+}
+
+impl From<&ParticleShape> for ParticleShapeDef {
+    fn from(s: &ParticleShape) -> Self {
+        match s {
+            ParticleShape::Rectangle { aspect_ratio } => ParticleShapeDef::Rectangle {
+                aspect_ratio: *aspect_ratio,
+            },
+            ParticleShape::Circle { subdivisions } => ParticleShapeDef::Circle {
+                subdivisions: *subdivisions,
+            },
+            ParticleShape::CustomMesh { vertices, indices } => ParticleShapeDef::CustomMesh {
+                vertices: vertices.clone(),
+                indices: indices.clone(),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub enum BlendModeDef {
+    Alpha,
+    Additive,
+}
+
+impl From<BlendModeDef> for BlendMode {
+    fn from(b: BlendModeDef) -> Self {
+        match b {
+            BlendModeDef::Alpha => BlendMode::Alpha,
+            BlendModeDef::Additive => BlendMode::Additive,
+        }
+    }
+}
+
+impl From<&BlendMode> for BlendModeDef {
+    fn from(b: &BlendMode) -> Self {
+        match b {
+            BlendMode::Alpha => BlendModeDef::Alpha,
+            BlendMode::Additive => BlendModeDef::Additive,
+        }
+    }
+}
+
+/// 尺寸曲线, 目前 macroquad-particles 只实现了线性插值
+#[derive(Debug, Clone, Deserialize)]
+pub struct CurveDef {
+    pub points: Vec<(f32, f32)>,
+    #[serde(default = "default_curve_resolution")]
+    pub resolution: usize,
+}
+
+fn default_curve_resolution() -> usize {
+    20
+}
+
+impl From<CurveDef> for Curve {
+    fn from(c: CurveDef) -> Self {
+        Curve {
+            points: c.points,
+            interpolation: Interpolation::Linear,
+            resolution: c.resolution,
+        }
+    }
+}
+
+impl From<&Curve> for CurveDef {
+    fn from(c: &Curve) -> Self {
+        CurveDef {
+            points: c.points.clone(),
+            resolution: c.resolution,
+        }
+    }
+}
+
+/// 贴图集配置, `start_index..end_index` 为播放的帧范围
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct AtlasDef {
+    pub n: u16,
+    pub m: u16,
+    pub start_index: u16,
+    pub end_index: u16,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct MaterialDef {
+    pub vertex: String,
+    pub fragment: String,
+}
+
+/// 一个粒子预设, 字段与 `EmitterConfig` 一一对应
+///
+/// 未写出的字段使用 `EmitterConfig::default()` 的值
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ParticlePreset {
+    pub local_coords: bool,
+    pub emission_shape: EmissionShapeDef,
+    pub one_shot: bool,
+    pub lifetime: f32,
+    pub lifetime_randomness: f32,
+    pub explosiveness: f32,
+    pub amount: u32,
+    pub shape: ParticleShapeDef,
+    pub emitting: bool,
+    pub initial_direction: (f32, f32),
+    pub initial_direction_spread: f32,
+    pub initial_velocity: f32,
+    pub initial_velocity_randomness: f32,
+    pub linear_accel: f32,
+    pub initial_rotation: f32,
+    pub initial_rotation_randomness: f32,
+    pub initial_angular_velocity: f32,
+    pub initial_angular_velocity_randomness: f32,
+    pub angular_accel: f32,
+    pub angular_damping: f32,
+    pub size: f32,
+    pub size_randomness: f32,
+    pub size_curve: Option<CurveDef>,
+    pub blend_mode: BlendModeDef,
+    pub colors_curve: ColorCurveDef,
+    pub gravity: (f32, f32),
+    /// 贴图路径, 相对于工作目录
+    pub texture: Option<String>,
+    pub atlas: Option<AtlasDef>,
+    pub material: Option<MaterialDef>,
+    pub post_processing: bool,
+}
+
+impl Default for ParticlePreset {
+    fn default() -> Self {
+        (&EmitterConfig::default()).into()
+    }
+}
+
+/// 贴图、贴图集和材质无法从发射器配置还原, 总是留空
+impl From<&EmitterConfig> for ParticlePreset {
+    fn from(c: &EmitterConfig) -> Self {
+        Self {
+            local_coords: c.local_coords,
+            emission_shape: (&c.emission_shape).into(),
+            one_shot: c.one_shot,
+            lifetime: c.lifetime,
+            lifetime_randomness: c.lifetime_randomness,
+            explosiveness: c.explosiveness,
+            amount: c.amount,
+            shape: (&c.shape).into(),
+            emitting: c.emitting,
+            initial_direction: c.initial_direction.into(),
+            initial_direction_spread: c.initial_direction_spread,
+            initial_velocity: c.initial_velocity,
+            initial_velocity_randomness: c.initial_velocity_randomness,
+            linear_accel: c.linear_accel,
+            initial_rotation: c.initial_rotation,
+            initial_rotation_randomness: c.initial_rotation_randomness,
+            initial_angular_velocity: c.initial_angular_velocity,
+            initial_angular_velocity_randomness: c.initial_angular_velocity_randomness,
+            angular_accel: c.angular_accel,
+            angular_damping: c.angular_damping,
+            size: c.size,
+            size_randomness: c.size_randomness,
+            size_curve: c.size_curve.as_ref().map(CurveDef::from),
+            blend_mode: (&c.blend_mode).into(),
+            colors_curve: (&c.colors_curve).into(),
+            gravity: c.gravity.into(),
+            texture: None,
+            atlas: None,
+            material: None,
+            post_processing: c.post_processing.is_some(),
+        }
+    }
+}
+
+impl ParticlePreset {
+    /// 转换为发射器配置, 贴图由调用者提供
+    pub fn to_config(&self, texture: Option<Texture2D>) -> EmitterConfig {
+        EmitterConfig {
+            local_coords: self.local_coords,
+            emission_shape: self.emission_shape.into(),
+            one_shot: self.one_shot,
+            lifetime: self.lifetime,
+            lifetime_randomness: self.lifetime_randomness,
+            explosiveness: self.explosiveness,
+            amount: self.amount,
+            shape: self.shape.clone().into(),
+            emitting: self.emitting,
+            initial_direction: vec2(self.initial_direction.0, self.initial_direction.1),
+            initial_direction_spread: self.initial_direction_spread,
+            initial_velocity: self.initial_velocity,
+            initial_velocity_randomness: self.initial_velocity_randomness,
+            linear_accel: self.linear_accel,
+            initial_rotation: self.initial_rotation,
+            initial_rotation_randomness: self.initial_rotation_randomness,
+            initial_angular_velocity: self.initial_angular_velocity,
+            initial_angular_velocity_randomness: self.initial_angular_velocity_randomness,
+            angular_accel: self.angular_accel,
+            angular_damping: self.angular_damping,
+            size: self.size,
+            size_randomness: self.size_randomness,
+            size_curve: self.size_curve.clone().map(Curve::from),
+            blend_mode: self.blend_mode.into(),
+            colors_curve: self.colors_curve.into(),
+            gravity: vec2(self.gravity.0, self.gravity.1),
+            texture,
+            atlas: self
+                .atlas
+                .map(|a| AtlasConfig::new(a.n, a.m, a.start_index..a.end_index)),
+            material: self
+                .material
+                .as_ref()
+                .map(|m| ParticleMaterial::new(&m.vertex, &m.fragment)),
+            post_processing: self.post_processing.then_some(PostProcessing),
+        }
+    }
+}
+
+/// 按名字索引的粒子预设库
+pub struct ParticleLibrary {
+    presets: BTreeMap<String, ParticlePreset>,
+    textures: BTreeMap<String, Texture2D>,
+    /// 最近一次加载失败的原因, 用于预览界面显示
+    pub last_error: Option<String>,
+    #[cfg(not(target_arch = "wasm32"))]
+    modified: Option<SystemTime>,
+    #[cfg(not(target_arch = "wasm32"))]
+    last_check: f64,
+}
+
+impl ParticleLibrary {
+    /// 加载预设文件, 读取或解析失败时使用内置的默认预设
+    pub async fn load() -> Self {
+        let mut library = Self {
+            presets: BTreeMap::new(),
+            textures: BTreeMap::new(),
+            last_error: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            modified: file_modified(),
+            #[cfg(not(target_arch = "wasm32"))]
+            last_check: 0.0,
+        };
+
+        let source = load_string(PRESETS_PATH).await.ok();
+        let presets = source
+            .as_deref()
+            .map(parse_presets)
+            .unwrap_or_else(|| Err(format!("无法读取 {}", PRESETS_PATH)));
+        match presets {
+            Ok(presets) => library.presets = presets,
+            Err(err) => {
+                library.last_error = Some(err);
+                library.presets = parse_presets(BUILTIN_PRESETS).expect("内置粒子预设无效");
+            }
+        }
+
+        for path in library.texture_paths() {
+            match load_texture(&path).await {
+                Ok(texture) => {
+                    library.textures.insert(path, texture);
+                }
+                Err(err) => library.last_error = Some(format!("无法加载贴图 {path}: {err}")),
+            }
+        }
+
+        library
+    }
+
+    /// 按名字获取发射器配置, 未知名字返回默认配置
+    pub fn get(&self, name: &str) -> EmitterConfig {
+        match self.presets.get(name) {
+            Some(preset) => {
+                let texture = preset
+                    .texture
+                    .as_ref()
+                    .and_then(|path| self.textures.get(path))
+                    .cloned();
+                preset.to_config(texture)
+            }
+            None => EmitterConfig::default(),
+        }
+    }
+
+    /// 按名字获取原始预设
+    pub fn preset(&self, name: &str) -> Option<&ParticlePreset> {
+        self.presets.get(name)
+    }
+
+    /// 所有预设的名字, 按字母排序
+    pub fn names(&self) -> Vec<&str> {
+        self.presets.keys().map(String::as_str).collect()
+    }
+
+    fn texture_paths(&self) -> Vec<String> {
+        self.presets
+            .values()
+            .filter_map(|preset| preset.texture.clone())
+            .filter(|path| !self.textures.contains_key(path))
+            .collect()
+    }
+
+    /// 文件发生变化时重新加载, 返回是否重新加载过
+    ///
+    /// 只在原生平台生效, 解析失败时保留旧的预设
+    pub fn hot_reload(&mut self) -> bool {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let now = get_time();
+            if now - self.last_check < HOT_RELOAD_INTERVAL {
+                return false;
+            }
+            self.last_check = now;
+
+            let modified = file_modified();
+            if modified.is_none() || modified == self.modified {
+                return false;
+            }
+            self.modified = modified;
+
+            match fs::read_to_string(PRESETS_PATH)
+                .map_err(|err| err.to_string())
+                .and_then(|source| parse_presets(&source))
+            {
+                Ok(presets) => {
+                    self.presets = presets;
+                    self.last_error = None;
+                    for path in self.texture_paths() {
+                        // 贴图损坏时只报告错误, 不能让一次编辑中断游戏
+                        match fs::read(&path)
+                            .map_err(|err| err.to_string())
+                            .and_then(|bytes| {
+                                Image::from_file_with_format(&bytes, None)
+                                    .map_err(|err| err.to_string())
+                            }) {
+                            Ok(image) => {
+                                self.textures.insert(path, Texture2D::from_image(&image));
+                            }
+                            Err(err) => {
+                                self.last_error = Some(format!("无法加载贴图 {path}: {err}"));
+                            }
+                        }
+                    }
+                    true
+                }
+                Err(err) => {
+                    self.last_error = Some(err);
+                    false
+                }
+            }
+        }
+        #[cfg(target_arch = "wasm32")]
+        {
+            false
+        }
+    }
+}
+
+/// 解析 RON 格式的预设表
+fn parse_presets(source: &str) -> Result<BTreeMap<String, ParticlePreset>, String> {
+    ron::from_str(source).map_err(|err| err.to_string())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn file_modified() -> Option<SystemTime> {
+    fs::metadata(PRESETS_PATH).and_then(|m| m.modified()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_presets_parse() {
+        let presets = parse_presets(BUILTIN_PRESETS).unwrap();
+        for name in ["explosion", "rocket_exhaust", "rocket_exhaust_boost"] {
+            assert!(presets.contains_key(name), "missing preset {name}");
+        }
+
+        let explosion = &presets["explosion"];
+        let config = explosion.to_config(None);
+        let curve = explosion.colors_curve;
+        assert_eq!(config.colors_curve.start, Color::from(curve.start));
+        assert_eq!(config.colors_curve.mid, Color::from(curve.mid));
+        assert_eq!(config.colors_curve.end, Color::from(curve.end));
+        let back = ColorCurveDef::from(&config.colors_curve);
+        assert_eq!(Color::from(back.end), Color::from(curve.end));
+    }
+
+    #[test]
+    fn missing_fields_use_emitter_defaults() {
+        let defaults = EmitterConfig::default();
+        let presets = parse_presets("{\"empty\": ()}").unwrap();
+        let config = presets["empty"].to_config(None);
+        assert_eq!(config.amount, defaults.amount);
+        assert_eq!(config.lifetime, defaults.lifetime);
+        assert_eq!(config.initial_direction, defaults.initial_direction);
+        assert_eq!(config.colors_curve.start, defaults.colors_curve.start);
+        assert!(config.size_curve.is_none());
+    }
+}
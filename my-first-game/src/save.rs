@@ -0,0 +1,66 @@
+//! 存档, 原生平台保存在 `assets/data` 目录下, 网页版暂不保存
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::fs;
+
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+/// 存档目录
+#[cfg(not(target_arch = "wasm32"))]
+const SAVE_DIR: &str = "my-first-game/assets/data";
+
+/// 读取旧版本保存的最高分, 之后迁移到排行榜
+///
+/// 根据当前不同的平台有不同的实现
+pub fn load_high_score() -> u32 {
+    #[cfg(target_arch = "wasm32")]
+    {
+        0
+        // #[wasm_bindgen]
+        // extern "C" {
+        //     #[wasm_bindgen(js_namespace = window)]
+        //     fn loadHighScore() -> u32;
+        // }
+
+        // loadHighScore()
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        fs::read_to_string(format!("{}/highscore.dat", SAVE_DIR))
+            .map_or(Ok(0), |s| s.parse::<u32>())
+            .unwrap_or(0)
+    }
+}
+
+/// 读取 RON 格式的存档 `name.ron`, 不存在或损坏时返回默认值
+pub fn load<T: DeserializeOwned + Default>(name: &str) -> T {
+    #[cfg(target_arch = "wasm32")]
+    {
+        let _ = name;
+        T::default()
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        fs::read_to_string(format!("{}/{}.ron", SAVE_DIR, name))
+            .ok()
+            .and_then(|s| ron::from_str(&s).ok())
+            .unwrap_or_default()
+    }
+}
+
+/// 以 RON 格式写入存档 `name.ron`
+pub fn store<T: Serialize>(name: &str, value: &T) {
+    #[cfg(target_arch = "wasm32")]
+    {
+        let _ = (name, value);
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let Ok(text) = ron::ser::to_string_pretty(value, Default::default()) else {
+            return;
+        };
+        fs::create_dir_all(SAVE_DIR).ok();
+        fs::write(format!("{}/{}.ron", SAVE_DIR, name), text).ok();
+    }
+}
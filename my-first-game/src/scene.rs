@@ -0,0 +1,512 @@
+//! 场景栈: 每个游戏状态是一个场景, 暂停这样的覆盖层可以压在其他场景之上
+//!
+//! 这里不调用任何 macroquad 接口, 场景切换可以在没有窗口的情况下测试
+
+use crate::state::GameState;
+
+/// 场景, `C` 为场景之间共享的游戏上下文
+pub trait Scene<C> {
+    /// 场景对应的游戏状态, 用于调试显示和测试
+    fn state(&self) -> GameState;
+
+    /// 覆盖层会先绘制下面的场景, 再绘制自己
+    fn is_overlay(&self) -> bool {
+        false
+    }
+
+    /// 场景入栈时调用
+    fn enter(&mut self, _ctx: &mut C) {}
+
+    /// 场景出栈时调用
+    fn exit(&mut self, _ctx: &mut C) {}
+
+    /// 每帧只更新栈顶的场景
+    fn update(&mut self, ctx: &mut C) -> Transition<C>;
+
+    /// `focused` 表示该场景是否在栈顶, 被覆盖的场景只绘制不推进
+    fn draw(&mut self, _ctx: &mut C, _focused: bool) {}
+}
+
+/// 场景切换操作
+pub enum SceneOp<C> {
+    /// 把新场景压在当前场景之上
+    Push(Box<dyn Scene<C>>),
+    /// 弹出当前场景
+    Pop,
+    /// 用新场景替换当前场景
+    Replace(Box<dyn Scene<C>>),
+    /// 清空场景栈, 退出游戏
+    Quit,
+}
+
+/// 切换动画
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Animation {
+    /// 立即切换
+    Cut,
+    /// 淡出到黑屏再淡入
+    Fade(f32),
+    /// 黑幕从左到右扫过
+    Wipe(f32),
+}
+
+/// 场景 `update` 的返回值
+pub struct Transition<C> {
+    op: Option<SceneOp<C>>,
+    animation: Animation,
+}
+
+impl<C> Transition<C> {
+    pub fn none() -> Self {
+        Self {
+            op: None,
+            animation: Animation::Cut,
+        }
+    }
+
+    pub fn push(scene: impl Scene<C> + 'static) -> Self {
+        Self::from_op(SceneOp::Push(Box::new(scene)))
+    }
+
+    pub fn pop() -> Self {
+        Self::from_op(SceneOp::Pop)
+    }
+
+    pub fn replace(scene: impl Scene<C> + 'static) -> Self {
+        Self::from_op(SceneOp::Replace(Box::new(scene)))
+    }
+
+    pub fn quit() -> Self {
+        Self::from_op(SceneOp::Quit)
+    }
+
+    fn from_op(op: SceneOp<C>) -> Self {
+        Self {
+            op: Some(op),
+            animation: Animation::Cut,
+        }
+    }
+
+    /// 以淡入淡出的方式切换, `duration` 为总时长
+    pub fn fade(mut self, duration: f32) -> Self {
+        self.animation = Animation::Fade(duration);
+        self
+    }
+
+    /// 以擦除的方式切换, `duration` 为总时长
+    pub fn wipe(mut self, duration: f32) -> Self {
+        self.animation = Animation::Wipe(duration);
+        self
+    }
+}
+
+/// 正在播放的切换动画, 前半段遮住旧场景, 后半段露出新场景
+struct ActiveTransition<C> {
+    animation: Animation,
+    elapsed: f32,
+    /// 动画过半时执行的操作, 执行后为 `None`
+    pending: Option<SceneOp<C>>,
+}
+
+/// 切换动画的遮罩, 由调用者负责绘制
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Cover {
+    pub animation: Animation,
+    /// 遮挡程度, 0.0 完全可见, 1.0 完全遮住
+    pub amount: f32,
+    /// 是否处于后半段
+    pub revealing: bool,
+}
+
+/// 场景栈
+pub struct SceneStack<C> {
+    scenes: Vec<Box<dyn Scene<C>>>,
+    transition: Option<ActiveTransition<C>>,
+}
+
+impl<C> SceneStack<C> {
+    pub fn new(ctx: &mut C, mut root: Box<dyn Scene<C>>) -> Self {
+        root.enter(ctx);
+        Self {
+            scenes: vec![root],
+            transition: None,
+        }
+    }
+
+    /// 场景全部出栈后游戏结束
+    pub fn is_empty(&self) -> bool {
+        self.scenes.is_empty()
+    }
+
+    /// 从栈底到栈顶的所有状态
+    #[cfg_attr(not(debug_assertions), allow(dead_code))]
+    pub fn states(&self) -> Vec<GameState> {
+        self.scenes.iter().map(|scene| scene.state()).collect()
+    }
+
+    /// 推进切换动画并更新栈顶场景
+    ///
+    /// 动画的前半段不更新任何场景, 避免旧场景在淡出时继续响应输入
+    pub fn update(&mut self, ctx: &mut C, delta_time: f32) {
+        if let Some(mut transition) = self.transition.take() {
+            transition.elapsed += delta_time;
+            let duration = match transition.animation {
+                Animation::Cut => 0.0,
+                Animation::Fade(duration) | Animation::Wipe(duration) => duration,
+            };
+            if transition.elapsed >= duration / 2.0
+                && let Some(op) = transition.pending.take()
+            {
+                self.apply(ctx, op);
+            }
+            if transition.elapsed < duration {
+                let waiting = transition.pending.is_some();
+                self.transition = Some(transition);
+                if waiting {
+                    return;
+                }
+            }
+        }
+
+        let Some(scene) = self.scenes.last_mut() else {
+            return;
+        };
+        let Transition { op, animation } = scene.update(ctx);
+        let Some(op) = op else {
+            return;
+        };
+        match animation {
+            Animation::Cut => self.apply(ctx, op),
+            _ => {
+                self.transition = Some(ActiveTransition {
+                    animation,
+                    elapsed: 0.0,
+                    pending: Some(op),
+                })
+            }
+        }
+    }
+
+    /// 从最上层的非覆盖层场景开始向上绘制
+    pub fn draw(&mut self, ctx: &mut C) {
+        let base = self
+            .scenes
+            .iter()
+            .rposition(|scene| !scene.is_overlay())
+            .unwrap_or(0);
+        let top = self.scenes.len().saturating_sub(1);
+        for (i, scene) in self.scenes.iter_mut().enumerate().skip(base) {
+            scene.draw(ctx, i == top);
+        }
+    }
+
+    /// 当前切换动画的遮罩
+    pub fn cover(&self) -> Option<Cover> {
+        let transition = self.transition.as_ref()?;
+        let duration = match transition.animation {
+            Animation::Cut => return None,
+            Animation::Fade(duration) | Animation::Wipe(duration) => duration,
+        };
+        let half = duration / 2.0;
+        let revealing = transition.pending.is_none();
+        let amount = if half <= 0.0 {
+            0.0
+        } else if revealing {
+            1.0 - ((transition.elapsed - half) / half).clamp(0.0, 1.0)
+        } else {
+            (transition.elapsed / half).clamp(0.0, 1.0)
+        };
+        Some(Cover {
+            animation: transition.animation,
+            amount,
+            revealing,
+        })
+    }
+
+    fn apply(&mut self, ctx: &mut C, op: SceneOp<C>) {
+        match op {
+            SceneOp::Push(mut scene) => {
+                scene.enter(ctx);
+                self.scenes.push(scene);
+            }
+            SceneOp::Pop => {
+                if let Some(mut scene) = self.scenes.pop() {
+                    scene.exit(ctx);
+                }
+            }
+            SceneOp::Replace(mut scene) => {
+                if let Some(mut old) = self.scenes.pop() {
+                    old.exit(ctx);
+                }
+                scene.enter(ctx);
+                self.scenes.push(scene);
+            }
+            SceneOp::Quit => {
+                while let Some(mut scene) = self.scenes.pop() {
+                    scene.exit(ctx);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 测试用的上下文: 记录场景回调, 并指定下一次 update 的切换
+    #[derive(Default)]
+    struct Log {
+        calls: Vec<String>,
+        next: Vec<(GameState, Command)>,
+    }
+
+    #[derive(Clone, Copy)]
+    enum Command {
+        Push(GameState, bool),
+        Pop,
+        Replace(GameState, bool),
+        Quit,
+    }
+
+    struct Mock {
+        state: GameState,
+        overlay: bool,
+        animation: Animation,
+    }
+
+    fn mock(state: GameState) -> Mock {
+        Mock {
+            state,
+            overlay: false,
+            animation: Animation::Cut,
+        }
+    }
+
+    impl Scene<Log> for Mock {
+        fn state(&self) -> GameState {
+            self.state
+        }
+
+        fn is_overlay(&self) -> bool {
+            self.overlay
+        }
+
+        fn enter(&mut self, ctx: &mut Log) {
+            ctx.calls.push(format!("enter {:?}", self.state));
+        }
+
+        fn exit(&mut self, ctx: &mut Log) {
+            ctx.calls.push(format!("exit {:?}", self.state));
+        }
+
+        fn update(&mut self, ctx: &mut Log) -> Transition<Log> {
+            ctx.calls.push(format!("update {:?}", self.state));
+            let Some(i) = ctx.next.iter().position(|(s, _)| *s == self.state) else {
+                return Transition::none();
+            };
+            let (_, command) = ctx.next.remove(i);
+            let transition = match command {
+                Command::Push(state, overlay) => Transition::push(Mock {
+                    overlay,
+                    ..mock(state)
+                }),
+                Command::Pop => Transition::pop(),
+                Command::Replace(state, overlay) => Transition::replace(Mock {
+                    overlay,
+                    ..mock(state)
+                }),
+                Command::Quit => Transition::quit(),
+            };
+            match self.animation {
+                Animation::Cut => transition,
+                Animation::Fade(duration) => transition.fade(duration),
+                Animation::Wipe(duration) => transition.wipe(duration),
+            }
+        }
+
+        fn draw(&mut self, ctx: &mut Log, focused: bool) {
+            ctx.calls.push(format!("draw {:?} {}", self.state, focused));
+        }
+    }
+
+    fn stack(log: &mut Log, root: Mock) -> SceneStack<Log> {
+        let stack = SceneStack::new(log, Box::new(root));
+        log.calls.clear();
+        stack
+    }
+
+    #[test]
+    fn root_scene_is_entered() {
+        let mut log = Log::default();
+        let stack = SceneStack::new(&mut log, Box::new(mock(GameState::MainMenu)));
+        assert_eq!(log.calls, ["enter MainMenu"]);
+        assert_eq!(stack.states().last(), Some(&GameState::MainMenu));
+    }
+
+    #[test]
+    fn only_top_scene_is_updated() {
+        let mut log = Log::default();
+        let mut stack = stack(&mut log, mock(GameState::Playing));
+        log.next
+            .push((GameState::Playing, Command::Push(GameState::Paused, true)));
+        stack.update(&mut log, 0.016);
+        stack.update(&mut log, 0.016);
+        assert_eq!(
+            log.calls,
+            ["update Playing", "enter Paused", "update Paused"]
+        );
+        assert_eq!(stack.states(), [GameState::Playing, GameState::Paused]);
+    }
+
+    #[test]
+    fn overlay_draws_scene_below_unfocused() {
+        let mut log = Log::default();
+        let mut stack = stack(&mut log, mock(GameState::Playing));
+        log.next
+            .push((GameState::Playing, Command::Push(GameState::Paused, true)));
+        stack.update(&mut log, 0.016);
+        log.calls.clear();
+        stack.draw(&mut log);
+        assert_eq!(log.calls, ["draw Playing false", "draw Paused true"]);
+    }
+
+    #[test]
+    fn opaque_scene_hides_scenes_below() {
+        let mut log = Log::default();
+        let mut stack = stack(&mut log, mock(GameState::Playing));
+        log.next.push((
+            GameState::Playing,
+            Command::Push(GameState::GameOver, false),
+        ));
+        stack.update(&mut log, 0.016);
+        log.calls.clear();
+        stack.draw(&mut log);
+        assert_eq!(log.calls, ["draw GameOver true"]);
+    }
+
+    #[test]
+    fn pop_returns_to_scene_below_without_reentering() {
+        let mut log = Log::default();
+        let mut stack = stack(&mut log, mock(GameState::Playing));
+        log.next
+            .push((GameState::Playing, Command::Push(GameState::Paused, true)));
+        log.next.push((GameState::Paused, Command::Pop));
+        stack.update(&mut log, 0.016);
+        stack.update(&mut log, 0.016);
+        assert_eq!(
+            log.calls,
+            [
+                "update Playing",
+                "enter Paused",
+                "update Paused",
+                "exit Paused"
+            ]
+        );
+        assert_eq!(stack.states().last(), Some(&GameState::Playing));
+    }
+
+    #[test]
+    fn replace_exits_old_scene_before_entering_new() {
+        let mut log = Log::default();
+        let mut stack = stack(&mut log, mock(GameState::MainMenu));
+        log.next.push((
+            GameState::MainMenu,
+            Command::Replace(GameState::Playing, false),
+        ));
+        stack.update(&mut log, 0.016);
+        assert_eq!(
+            log.calls,
+            ["update MainMenu", "exit MainMenu", "enter Playing"]
+        );
+        assert_eq!(stack.states(), [GameState::Playing]);
+    }
+
+    #[test]
+    fn quit_exits_every_scene() {
+        let mut log = Log::default();
+        let mut stack = stack(&mut log, mock(GameState::Playing));
+        log.next
+            .push((GameState::Playing, Command::Push(GameState::Paused, true)));
+        log.next.push((GameState::Paused, Command::Quit));
+        stack.update(&mut log, 0.016);
+        stack.update(&mut log, 0.016);
+        assert!(stack.is_empty());
+        assert_eq!(&log.calls[3..], ["exit Paused", "exit Playing"]);
+    }
+
+    #[test]
+    fn fade_switches_scene_at_half_time() {
+        let mut log = Log::default();
+        let mut stack = stack(
+            &mut log,
+            Mock {
+                animation: Animation::Fade(1.0),
+                ..mock(GameState::MainMenu)
+            },
+        );
+        log.next.push((
+            GameState::MainMenu,
+            Command::Replace(GameState::Playing, false),
+        ));
+
+        stack.update(&mut log, 0.0);
+        assert_eq!(stack.states().last(), Some(&GameState::MainMenu));
+        assert_eq!(
+            stack.cover(),
+            Some(Cover {
+                animation: Animation::Fade(1.0),
+                amount: 0.0,
+                revealing: false
+            })
+        );
+
+        // 淡出过程中不更新场景
+        stack.update(&mut log, 0.25);
+        assert_eq!(stack.states().last(), Some(&GameState::MainMenu));
+        assert_eq!(stack.cover().unwrap().amount, 0.5);
+        assert_eq!(log.calls, ["update MainMenu"]);
+
+        // 过半时切换, 新场景开始更新
+        stack.update(&mut log, 0.25);
+        assert_eq!(stack.states().last(), Some(&GameState::Playing));
+        let cover = stack.cover().unwrap();
+        assert!(cover.revealing);
+        assert_eq!(cover.amount, 1.0);
+        assert_eq!(
+            log.calls,
+            [
+                "update MainMenu",
+                "exit MainMenu",
+                "enter Playing",
+                "update Playing"
+            ]
+        );
+
+        stack.update(&mut log, 0.25);
+        assert_eq!(stack.cover().unwrap().amount, 0.5);
+
+        stack.update(&mut log, 0.25);
+        assert_eq!(stack.cover(), None);
+    }
+
+    #[test]
+    fn long_frame_finishes_wipe_at_once() {
+        let mut log = Log::default();
+        let mut stack = stack(
+            &mut log,
+            Mock {
+                animation: Animation::Wipe(0.5),
+                ..mock(GameState::Playing)
+            },
+        );
+        log.next.push((
+            GameState::Playing,
+            Command::Replace(GameState::GameOver, false),
+        ));
+        stack.update(&mut log, 0.016);
+        stack.update(&mut log, 2.0);
+        assert_eq!(stack.states().last(), Some(&GameState::GameOver));
+        assert_eq!(stack.cover(), None);
+    }
+}
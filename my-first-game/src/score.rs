@@ -0,0 +1,86 @@
+use crate::events::{GameEvent, Subscriber};
+use crate::player::MAX_PLAYERS;
+
+/// 计分板, 根据击毁事件累计分数
+pub struct Scoreboard {
+    /// 当前分数, 双人模式下为两人的总分
+    pub score: u32,
+    /// 每名玩家各自的分数
+    pub scores: [u32; MAX_PLAYERS],
+    /// 历史最高分
+    pub high_score: u32,
+}
+
+impl Scoreboard {
+    pub fn new(high_score: u32) -> Self {
+        Self {
+            score: 0,
+            scores: [0; MAX_PLAYERS],
+            high_score,
+        }
+    }
+
+    /// 本局分数是否达到了历史最高分
+    pub fn is_record(&self) -> bool {
+        self.score == self.high_score
+    }
+}
+
+impl Subscriber<GameEvent> for Scoreboard {
+    fn on_event(&mut self, event: &GameEvent) {
+        match *event {
+            GameEvent::RunStarted => {
+                self.score = 0;
+                self.scores = [0; MAX_PLAYERS];
+            }
+            GameEvent::EnemyDestroyed { player, size, .. } => {
+                let points = size.round() as u32;
+                self.score += points;
+                self.scores[player] += points;
+                self.high_score = self.high_score.max(self.score);
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::EventBus;
+
+    #[test]
+    fn kills_add_rounded_size() {
+        let mut bus = EventBus::new();
+        let mut scoreboard = Scoreboard::new(50);
+        bus.publish(GameEvent::RunStarted);
+        for (player, size) in [(0, 20.4), (1, 40.6)] {
+            bus.publish(GameEvent::EnemyDestroyed {
+                player,
+                x: 0.0,
+                y: 0.0,
+                size,
+            });
+        }
+        bus.dispatch(&mut [&mut scoreboard]);
+        assert_eq!(scoreboard.score, 61);
+        assert_eq!(scoreboard.scores, [20, 41]);
+        assert_eq!(scoreboard.high_score, 61);
+        assert!(scoreboard.is_record());
+    }
+
+    #[test]
+    fn new_run_resets_score_but_keeps_record() {
+        let mut scoreboard = Scoreboard::new(0);
+        scoreboard.on_event(&GameEvent::EnemyDestroyed {
+            player: 0,
+            x: 0.0,
+            y: 0.0,
+            size: 30.0,
+        });
+        scoreboard.on_event(&GameEvent::RunStarted);
+        assert_eq!(scoreboard.score, 0);
+        assert_eq!(scoreboard.high_score, 30);
+        assert!(!scoreboard.is_record());
+    }
+}
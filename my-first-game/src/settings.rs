@@ -0,0 +1,201 @@
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::canvas::Scaling;
+
+/// 设置在存档目录中的文件名
+pub const SETTINGS_SAVE: &str = "settings";
+
+/// 配色方案
+///
+/// 方块和子弹生成时的随机颜色保持不变, 绘制时再按方案映射, 切换方案立即生效
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Palette {
+    /// 原始的随机颜色
+    #[default]
+    Random,
+    /// Okabe-Ito 配色, 对各类色盲都友好
+    OkabeIto,
+    /// 敌方橙色系, 我方蓝色系, 适合红绿色盲
+    BlueOrange,
+    /// 灰度, 敌方浅灰, 我方白色
+    Grayscale,
+}
+
+impl Palette {
+    pub const ALL: [Palette; 4] = [
+        Palette::Random,
+        Palette::OkabeIto,
+        Palette::BlueOrange,
+        Palette::Grayscale,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Palette::Random => "随机",
+            Palette::OkabeIto => "Okabe-Ito",
+            Palette::BlueOrange => "蓝橙",
+            Palette::Grayscale => "灰度",
+        }
+    }
+
+    /// 敌方和我方可用的颜色, 随机配色返回 `None`
+    fn colors(self) -> Option<(&'static [u32], &'static [u32])> {
+        match self {
+            Palette::Random => None,
+            Palette::OkabeIto => Some((
+                &[0xE69F00, 0xD55E00, 0xF0E442, 0xCC79A7],
+                &[0x56B4E9, 0x009E73, 0x0072B2],
+            )),
+            Palette::BlueOrange => Some((&[0xFF8C00, 0xFFB000, 0xFE6100], &[0x648FFF, 0x00C0FF])),
+            Palette::Grayscale => Some((&[0xBBBBBB, 0x888888, 0xDDDDDD], &[0xFFFFFF])),
+        }
+    }
+}
+
+/// 辅助模式, 调整游戏速度
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Assist {
+    Off,
+    /// 按住 A 时减速, 双人模式下 A 是二号玩家的按键所以不生效
+    #[default]
+    Hold,
+    /// 一直减速
+    Always,
+}
+
+impl Assist {
+    pub fn name(self) -> &'static str {
+        match self {
+            Assist::Off => "关闭",
+            Assist::Hold => "按住 A",
+            Assist::Always => "始终",
+        }
+    }
+}
+
+/// 辅助功能设置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Accessibility {
+    pub palette: Palette,
+    /// 高对比度: 纯黑背景, 所有物体加描边
+    pub high_contrast: bool,
+    /// 敌方物体的最低不透明度
+    pub min_alpha: f32,
+    pub assist: Assist,
+    /// 辅助模式生效时的游戏速度
+    pub assist_speed: f32,
+    /// 界面文字缩放
+    pub text_scale: f32,
+    /// 画布缩放到窗口的方式
+    pub scaling: Scaling,
+}
+
+impl Default for Accessibility {
+    fn default() -> Self {
+        Self {
+            palette: Palette::Random,
+            high_contrast: false,
+            min_alpha: 0.4,
+            assist: Assist::Hold,
+            assist_speed: 0.3,
+            text_scale: 1.0,
+            scaling: Scaling::Letterbox,
+        }
+    }
+}
+
+impl Accessibility {
+    /// 敌方物体实际绘制的颜色
+    pub fn hostile(&self, color: Color) -> Color {
+        let color = self.recolor(color, true);
+        Color {
+            a: color.a.max(self.min_alpha),
+            ..color
+        }
+    }
+
+    /// 我方物体实际绘制的颜色
+    pub fn friendly(&self, color: Color) -> Color {
+        self.recolor(color, false)
+    }
+
+    /// 用原始颜色挑选方案中的颜色, 同一个物体始终得到同一种颜色
+    fn recolor(&self, color: Color, hostile: bool) -> Color {
+        let Some((hostile_colors, friendly_colors)) = self.palette.colors() else {
+            return color;
+        };
+        let colors = if hostile {
+            hostile_colors
+        } else {
+            friendly_colors
+        };
+        let [r, g, b, _]: [u8; 4] = color.into();
+        let hash = r as usize * 31 * 31 + g as usize * 31 + b as usize;
+        Color::from_hex(colors[hash % colors.len()])
+    }
+
+    /// 描边颜色, 未开启高对比度时返回 `None`
+    pub fn outline(&self) -> Option<Color> {
+        self.high_contrast.then_some(WHITE)
+    }
+
+    /// 本帧的游戏速度, `holding` 为是否按住了减速键
+    pub fn game_speed(&self, holding: bool) -> f32 {
+        match self.assist {
+            Assist::Always => self.assist_speed,
+            Assist::Hold if holding => self.assist_speed,
+            _ => 1.0,
+        }
+    }
+
+    /// 按缩放调整字号
+    pub fn font_size(&self, font_size: u16) -> u16 {
+        (font_size as f32 * self.text_scale).round() as u16
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hostile_objects_respect_min_alpha() {
+        let settings = Accessibility::default();
+        let faint = Color::new(0.5, 0.2, 0.9, 0.05);
+        assert_eq!(settings.hostile(faint).a, settings.min_alpha);
+        assert_eq!(settings.hostile(faint).r, faint.r);
+        // 我方物体不受影响
+        assert_eq!(settings.friendly(faint), faint);
+    }
+
+    #[test]
+    fn palettes_keep_hostile_and_friendly_apart() {
+        let settings = Accessibility {
+            palette: Palette::BlueOrange,
+            ..Default::default()
+        };
+        let (hostile, friendly) = Palette::BlueOrange.colors().unwrap();
+        for i in 0..50 {
+            let color = Color::from_rgba(i * 5, 255 - i * 3, i * 2, 10);
+            let enemy = settings.hostile(color);
+            assert!(hostile.iter().any(|c| Color::from_hex(*c).r == enemy.r));
+            assert_eq!(enemy, settings.hostile(color));
+            let ally = settings.friendly(color);
+            assert!(friendly.iter().any(|c| Color::from_hex(*c) == ally));
+        }
+    }
+
+    #[test]
+    fn assist_mode_scales_game_speed() {
+        let mut settings = Accessibility::default();
+        assert_eq!(settings.game_speed(false), 1.0);
+        assert_eq!(settings.game_speed(true), 0.3);
+        settings.assist = Assist::Always;
+        settings.assist_speed = 0.6;
+        assert_eq!(settings.game_speed(false), 0.6);
+        settings.assist = Assist::Off;
+        assert_eq!(settings.game_speed(true), 1.0);
+    }
+}
@@ -13,4 +13,4 @@ void main() {
     gl_Position = Projection * Model * vec4(position, 1);
     iTime = _Time.x;
 }
-";
\ No newline at end of file
+";
@@ -1,8 +1,13 @@
-use macroquad::{color::Color, math::{Circle, Rect}};
+use macroquad::{
+    color::Color,
+    math::{Circle, Rect},
+};
 
 pub struct Shape {
     pub size: f32,
     pub speed: f32,
+    /// 水平方向的速度, 散射子弹使用
+    pub drift: f32,
     pub x: f32,
     pub y: f32,
     pub color: Color,
@@ -35,3 +40,44 @@ impl Shape {
         }
     }
 }
+
+/// 敌人(方块)的体型分级
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnemySize {
+    Small,
+    Medium,
+    Big,
+}
+
+impl EnemySize {
+    /// 根据方块边长分级, 方块边长在 16 ~ 64 之间
+    pub fn from_size(size: f32) -> Self {
+        if size > 48.0 {
+            EnemySize::Big
+        } else if size > 32.0 {
+            EnemySize::Medium
+        } else {
+            EnemySize::Small
+        }
+    }
+
+    /// 该分级的典型边长
+    pub fn size(&self) -> f32 {
+        match self {
+            EnemySize::Small => 24.0,
+            EnemySize::Medium => 40.0,
+            EnemySize::Big => 56.0,
+        }
+    }
+
+    /// 解析控制台中的体型名
+    #[cfg(debug_assertions)]
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "small" => Some(EnemySize::Small),
+            "medium" => Some(EnemySize::Medium),
+            "big" => Some(EnemySize::Big),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,161 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::MOVEMENT_SPEED;
+use crate::player::BULLETS_PER_PLAYER;
+
+/// 每多少分兑换一枚金币
+const SCORE_PER_COIN: u32 = 10;
+
+/// 可购买的永久升级
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Upgrade {
+    /// 移动速度, 每级 +10%
+    Speed,
+    /// 同屏子弹上限, 每级 +1
+    BulletCap,
+    /// 开局护盾, 每级可抵挡一次碰撞
+    Shield,
+    /// 道具吸附半径, 每级 +60
+    Magnet,
+}
+
+impl Upgrade {
+    pub const ALL: [Upgrade; 4] = [
+        Upgrade::Speed,
+        Upgrade::BulletCap,
+        Upgrade::Shield,
+        Upgrade::Magnet,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Upgrade::Speed => "引擎",
+            Upgrade::BulletCap => "弹匣",
+            Upgrade::Shield => "护盾",
+            Upgrade::Magnet => "磁铁",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Upgrade::Speed => "移动速度 +10%",
+            Upgrade::BulletCap => "同屏子弹上限 +1",
+            Upgrade::Shield => "开局获得一层护盾",
+            Upgrade::Magnet => "吸附附近的道具",
+        }
+    }
+
+    pub fn max_level(self) -> u32 {
+        match self {
+            Upgrade::Speed | Upgrade::Magnet => 5,
+            Upgrade::BulletCap => 4,
+            Upgrade::Shield => 3,
+        }
+    }
+
+    /// 从 `level` 级升到下一级的价格
+    pub fn cost(self, level: u32) -> u32 {
+        let base = match self {
+            Upgrade::Speed | Upgrade::Magnet => 50,
+            Upgrade::BulletCap => 80,
+            Upgrade::Shield => 150,
+        };
+        base * (level + 1)
+    }
+}
+
+/// 一局的得分能兑换的金币
+pub fn coins_for(score: u32) -> u32 {
+    score / SCORE_PER_COIN
+}
+
+/// 已购买的升级等级, 开局时应用到每名玩家
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Upgrades {
+    levels: BTreeMap<Upgrade, u32>,
+}
+
+impl Upgrades {
+    pub fn level(&self, upgrade: Upgrade) -> u32 {
+        self.levels.get(&upgrade).copied().unwrap_or(0)
+    }
+
+    /// 下一级的价格, 已满级返回 `None`
+    pub fn next_cost(&self, upgrade: Upgrade) -> Option<u32> {
+        let level = self.level(upgrade);
+        (level < upgrade.max_level()).then(|| upgrade.cost(level))
+    }
+
+    /// 用 `coins` 购买一级升级, 成功时扣除金币
+    pub fn buy(&mut self, upgrade: Upgrade, coins: &mut u32) -> bool {
+        match self.next_cost(upgrade) {
+            Some(cost) if cost <= *coins => {
+                *coins -= cost;
+                *self.levels.entry(upgrade).or_default() += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn move_speed(&self) -> f32 {
+        MOVEMENT_SPEED * (1.0 + 0.1 * self.level(Upgrade::Speed) as f32)
+    }
+
+    pub fn bullet_cap(&self) -> usize {
+        BULLETS_PER_PLAYER + self.level(Upgrade::BulletCap) as usize
+    }
+
+    pub fn shield(&self) -> u32 {
+        self.level(Upgrade::Shield)
+    }
+
+    pub fn magnet_radius(&self) -> f32 {
+        60.0 * self.level(Upgrade::Magnet) as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn buying_spends_coins_and_raises_level() {
+        let mut upgrades = Upgrades::default();
+        let mut coins = 120;
+        assert!(upgrades.buy(Upgrade::Speed, &mut coins));
+        assert_eq!((upgrades.level(Upgrade::Speed), coins), (1, 70));
+        // 第二级需要 100
+        assert!(!upgrades.buy(Upgrade::Speed, &mut coins));
+        assert_eq!((upgrades.level(Upgrade::Speed), coins), (1, 70));
+    }
+
+    #[test]
+    fn maxed_upgrades_cannot_be_bought() {
+        let mut upgrades = Upgrades::default();
+        let mut coins = u32::MAX;
+        for _ in 0..Upgrade::Shield.max_level() {
+            assert!(upgrades.buy(Upgrade::Shield, &mut coins));
+        }
+        assert_eq!(upgrades.next_cost(Upgrade::Shield), None);
+        assert!(!upgrades.buy(Upgrade::Shield, &mut coins));
+        assert_eq!(upgrades.shield(), 3);
+    }
+
+    #[test]
+    fn levels_apply_to_ship_stats() {
+        let mut upgrades = Upgrades::default();
+        assert_eq!(upgrades.bullet_cap(), BULLETS_PER_PLAYER);
+        assert_eq!(upgrades.magnet_radius(), 0.0);
+        let mut coins = 1000;
+        upgrades.buy(Upgrade::BulletCap, &mut coins);
+        upgrades.buy(Upgrade::Magnet, &mut coins);
+        upgrades.buy(Upgrade::Speed, &mut coins);
+        assert_eq!(upgrades.bullet_cap(), BULLETS_PER_PLAYER + 1);
+        assert_eq!(upgrades.magnet_radius(), 60.0);
+        assert!((upgrades.move_speed() - MOVEMENT_SPEED * 1.1).abs() < 1e-3);
+    }
+}
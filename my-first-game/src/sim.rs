@@ -0,0 +1,547 @@
+//! 一局游戏的模拟, 不依赖窗口和 GPU 资源, 可以在无窗口时加速运行
+
+use macroquad::prelude::*;
+use macroquad::rand::RandGenerator;
+
+use crate::canvas::{VIRTUAL_HEIGHT, VIRTUAL_WIDTH};
+use crate::events::{EventBus, GameEvent};
+use crate::player::{CoopSettings, Player};
+use crate::powerup::{DROP_CHANCE, PICKUP_SIZE, Pickup, PowerUp, PowerUps};
+use crate::shape::{EnemySize, Shape};
+use crate::shop::Upgrades;
+use crate::wave::{Spawner, Waves};
+
+/// 慢动作每秒恢复的时间缩放
+const SLOW_MOTION_RECOVERY: f32 = 0.6;
+
+/// 一名玩家本帧的操作, 来自键盘或自动驾驶
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Input {
+    /// 移动方向, 每个分量为 -1、0 或 1
+    pub direction: Vec2,
+    /// 本帧按下了射击键
+    pub fire: bool,
+}
+
+/// 一局结束的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ending {
+    /// 限时模式时间到
+    TimeUp,
+    /// 所有玩家都倒下, 慢动作播放完毕
+    AllDown,
+}
+
+/// 一局游戏的全部模拟状态
+pub struct World {
+    pub players: Vec<Player>,
+    pub coop: CoopSettings,
+    /// 共享生命池中剩余的备用生命
+    pub shared_lives: u32,
+    /// 方块容器
+    pub squares: Vec<Shape>,
+    /// 掉落的道具
+    pub pickups: Vec<Pickup>,
+    /// 生效中的道具
+    pub powerups: PowerUps,
+    pub waves: Waves,
+    /// 方块生成器
+    pub spawner: Spawner,
+    /// 子弹和掉落使用的随机数, 和方块生成分开
+    rng: RandGenerator,
+    /// 本局已经进行的时间
+    pub run_time: f32,
+    /// 所有玩家都已倒下, 正在播放结束前的慢动作
+    pub collides: bool,
+    /// 慢动作的时间缩放, 所有玩家倒下后先放慢再逐渐恢复
+    pub time_scale: f32,
+    /// 无敌模式, 由调试控制台开关
+    pub god: bool,
+}
+
+impl Default for World {
+    fn default() -> Self {
+        Self::new(0, CoopSettings::default(), &Upgrades::default(), 0)
+    }
+}
+
+impl World {
+    /// 开始新的一局, 相同的种子和操作得到相同的结果
+    pub fn new(players: usize, coop: CoopSettings, upgrades: &Upgrades, seed: u64) -> Self {
+        let (lives, shared_lives) = coop.lives(players);
+        let rng = RandGenerator::new();
+        rng.srand(seed ^ 0x5DEE_CE66);
+        Self {
+            players: (0..players)
+                .map(|index| Player::new(index, players, lives, upgrades))
+                .collect(),
+            coop,
+            shared_lives,
+            squares: vec![],
+            pickups: vec![],
+            powerups: PowerUps::default(),
+            waves: Waves::new(),
+            spawner: Spawner::new(seed),
+            rng,
+            run_time: 0.0,
+            collides: false,
+            time_scale: 1.0,
+            god: false,
+        }
+    }
+
+    /// 推进 `game_time`, `inputs` 按玩家顺序排列, 一局结束时返回结束的原因
+    ///
+    /// 慢动作在这里缩放, 调用者只需传入游戏时钟的时间
+    pub fn step(
+        &mut self,
+        game_time: f32,
+        inputs: &[Input],
+        time_limit: Option<f32>,
+        events: &mut EventBus<GameEvent>,
+    ) -> Option<Ending> {
+        let delta_time = game_time * self.time_scale;
+        if self.waves.update(delta_time) {
+            events.publish(GameEvent::WaveStarted {
+                wave: self.waves.number,
+            });
+        }
+        self.powerups.update(delta_time);
+
+        // 限时模式时间到直接结束
+        if let Some(limit) = time_limit
+            && self.run_time >= limit
+        {
+            return Some(Ending::TimeUp);
+        }
+
+        // 每存活满一分钟发布一次
+        let minutes = self.run_time as u32 / 60;
+        self.run_time += delta_time;
+        if self.run_time as u32 / 60 > minutes {
+            events.publish(GameEvent::Survived {
+                seconds: (minutes + 1) * 60,
+            });
+        }
+
+        // 生成方块的逻辑, 波次越高生成越频繁
+        let spawned = self
+            .spawner
+            .update(delta_time, self.waves.spawn_threshold(), VIRTUAL_WIDTH);
+        self.squares.extend(spawned);
+
+        // 方块移动
+        for square in self.squares.iter_mut().filter(|square| !square.collided) {
+            square.y += square.speed * delta_time;
+        }
+        for player in self.players.iter_mut() {
+            // 子弹射击
+            for bullet in &mut player.bullets {
+                bullet.y -= bullet.speed * delta_time;
+                bullet.x += bullet.drift * delta_time;
+            }
+            // 移除超出屏幕和击中方块的子弹
+            player
+                .bullets
+                .retain(|bullet| bullet.y > 0.0 - bullet.size / 2.0 && !bullet.collided);
+            player.invulnerable -= delta_time;
+        }
+
+        // 移除超出屏幕的方块
+        self.squares
+            .retain(|square| square.y < VIRTUAL_HEIGHT + square.size);
+
+        self.update_pickups(delta_time, events);
+
+        if !self.collides {
+            self.move_players(delta_time, inputs, events);
+            // 移除被子弹和玩家撞到的方块
+            self.squares.retain(|square| !square.collided);
+        }
+
+        // 判断玩家与方块的碰撞, 先消耗护盾, 还有备用生命时原地复活, 否则倒下
+        if !self.god {
+            self.collide_players(events);
+        }
+
+        // 所有玩家都倒下后放慢时间, 再逐渐恢复正常速度后结束
+        if !self.collides && self.players.iter().all(|player| player.down) {
+            self.collides = true;
+            self.time_scale = 0.3;
+        }
+        if self.collides {
+            self.time_scale += SLOW_MOTION_RECOVERY * game_time;
+            if self.time_scale > 1.0 {
+                self.time_scale = 1.0;
+                return Some(Ending::AllDown);
+            }
+        }
+
+        self.collide_bullets(events);
+        None
+    }
+
+    /// 一局结束时发布 `GameOver`, `score` 是结算时的分数
+    pub fn finish(&self, score: u32, events: &mut EventBus<GameEvent>) {
+        events.publish(GameEvent::GameOver {
+            score,
+            duration: self.run_time,
+        });
+    }
+
+    /// 道具下落, 进入磁铁范围时飞向最近的玩家, 碰到玩家时拾取
+    fn update_pickups(&mut self, delta_time: f32, events: &mut EventBus<GameEvent>) {
+        let (players, powerups) = (&self.players, &mut self.powerups);
+        self.pickups.retain_mut(|pickup| {
+            let nearest = players
+                .iter()
+                .enumerate()
+                .filter(|(_, player)| !player.down)
+                .map(|(index, player)| {
+                    let ship = vec2(player.ship.x, player.ship.y);
+                    (index, player, ship, ship.distance(pickup.pos))
+                })
+                .min_by(|a, b| a.3.total_cmp(&b.3));
+            let Some((index, player, ship, distance)) = nearest else {
+                pickup.update(delta_time, None);
+                return pickup.pos.y < VIRTUAL_HEIGHT + PICKUP_SIZE;
+            };
+            if distance <= (player.ship.size + PICKUP_SIZE) / 2.0 {
+                powerups.give(pickup.kind);
+                events.publish(GameEvent::PowerUpCollected {
+                    player: index,
+                    kind: pickup.kind,
+                });
+                return false;
+            }
+            pickup.update(delta_time, (distance <= player.magnet).then_some(ship));
+            pickup.pos.y < VIRTUAL_HEIGHT + PICKUP_SIZE
+        });
+    }
+
+    fn move_players(
+        &mut self,
+        delta_time: f32,
+        inputs: &[Input],
+        events: &mut EventBus<GameEvent>,
+    ) {
+        // 同屏子弹每人4颗(可在商店升级), 散射时每次发射三颗
+        let drifts: &[f32] = if self.powerups.is_active(PowerUp::Spread) {
+            &[-0.25, 0.0, 0.25]
+        } else {
+            &[0.0]
+        };
+        let rng = &self.rng;
+        for (index, (player, input)) in self.players.iter_mut().zip(inputs).enumerate() {
+            if player.down {
+                player.velocity = Vec2::ZERO;
+                continue;
+            }
+            let velocity = input.direction;
+            player.velocity = velocity;
+            player.ship.x += velocity.x * player.ship.speed * delta_time;
+            player.ship.y += velocity.y * player.ship.speed * delta_time;
+
+            if input.fire && player.bullets.len() < player.bullet_cap * drifts.len() {
+                let size = rng.gen_range(10.0, 25.0);
+                let color = color_u8!(
+                    rng.gen_range(0, 255),
+                    rng.gen_range(0, 255),
+                    rng.gen_range(0, 255),
+                    rng.gen_range(0, 255)
+                );
+
+                let ship = &player.ship;
+                for drift in drifts {
+                    player.bullets.push(Shape {
+                        size,
+                        x: ship.x,
+                        y: ship.y,
+                        speed: ship.speed * 2.5,
+                        drift: ship.speed * 2.5 * drift,
+                        color,
+                        collided: false,
+                    });
+                }
+                events.publish(GameEvent::BulletFired {
+                    player: index,
+                    x: ship.x,
+                    y: ship.y,
+                    count: drifts.len(),
+                });
+            }
+
+            // 限制移动范围在屏幕内
+            player.ship.x = clamp(player.ship.x, 0f32, VIRTUAL_WIDTH);
+            player.ship.y = clamp(player.ship.y, 0f32, VIRTUAL_HEIGHT);
+        }
+    }
+
+    fn collide_players(&mut self, events: &mut EventBus<GameEvent>) {
+        for (index, player) in self.players.iter_mut().enumerate() {
+            if !player.vulnerable() {
+                continue;
+            }
+            let Some(square) = self
+                .squares
+                .iter_mut()
+                .find(|square| player.ship.circle_collides_with(square))
+            else {
+                continue;
+            };
+            square.collided = true;
+            if player.absorb_hit() {
+                continue;
+            }
+            events.publish(GameEvent::PlayerHit {
+                player: index,
+                x: player.ship.x,
+                y: player.ship.y,
+                size: square.size,
+            });
+            let lives = if self.coop.shared_lives {
+                &mut self.shared_lives
+            } else {
+                &mut player.lives
+            };
+            if *lives > 0 {
+                *lives -= 1;
+                player.respawn();
+            } else {
+                player.down = true;
+            }
+        }
+    }
+
+    /// 判断子弹与方块的碰撞
+    fn collide_bullets(&mut self, events: &mut EventBus<GameEvent>) {
+        for square in self.squares.iter_mut() {
+            for (index, player) in self.players.iter_mut().enumerate() {
+                for bullet in player.bullets.iter_mut() {
+                    if bullet.circle_collides_with(square) {
+                        square.collided = true;
+                        bullet.collided = true;
+
+                        events.publish(GameEvent::EnemyDestroyed {
+                            player: index,
+                            x: square.x,
+                            y: square.y,
+                            size: square.size,
+                        });
+                        // 大方块有几率掉落道具
+                        if EnemySize::from_size(square.size) == EnemySize::Big
+                            && self.rng.gen_range(0.0, 1.0) < DROP_CHANCE
+                        {
+                            self.pickups.push(Pickup {
+                                kind: PowerUp::Spread,
+                                pos: vec2(square.x, square.y),
+                            });
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::player::BULLETS_PER_PLAYER;
+
+    const STEP: f32 = 1.0 / 60.0;
+
+    /// 推进一帧, 先去掉从屏幕上方随机生成的方块, 只留下测试放置的方块
+    fn step(
+        world: &mut World,
+        inputs: &[Input],
+        events: &mut EventBus<GameEvent>,
+    ) -> Option<Ending> {
+        world.squares.retain(|square| square.y >= 0.0);
+        world.step(STEP, inputs, None, events)
+    }
+
+    /// 在玩家正上方 `above` 处放一个静止的方块, 为 0 时直接压在玩家身上
+    fn place_square(world: &mut World, player: usize, above: f32) {
+        let ship = &world.players[player].ship;
+        world.squares.push(Shape {
+            size: 20.0,
+            speed: 0.0,
+            drift: 0.0,
+            x: ship.x,
+            y: ship.y - above,
+            color: WHITE,
+            collided: false,
+        });
+    }
+
+    fn coop(shared_lives: bool) -> World {
+        let coop = CoopSettings {
+            shared_lives,
+            ..Default::default()
+        };
+        World::new(2, coop, &Upgrades::default(), 7)
+    }
+
+    /// 等 `player` 的无敌时间结束后让一个方块撞上去
+    fn hit(world: &mut World, player: usize, events: &mut EventBus<GameEvent>) {
+        let idle = [Input::default(); 2];
+        while !world.players[player].vulnerable() {
+            step(world, &idle, events);
+        }
+        place_square(world, player, 0.0);
+        step(world, &idle, events);
+    }
+
+    #[test]
+    fn shared_lives_come_from_one_pool() {
+        let mut events = EventBus::new();
+        let mut world = coop(true);
+        assert_eq!(world.shared_lives, 2);
+        assert_eq!(world.players[0].lives, 0);
+        // 一号玩家用掉了整个生命池, 二号玩家再被撞就倒下
+        hit(&mut world, 0, &mut events);
+        hit(&mut world, 0, &mut events);
+        assert_eq!(world.shared_lives, 0);
+        assert!(!world.players[0].down);
+        hit(&mut world, 1, &mut events);
+        assert!(world.players[1].down);
+
+        let mut world = coop(false);
+        assert_eq!(world.shared_lives, 0);
+        hit(&mut world, 0, &mut events);
+        hit(&mut world, 0, &mut events);
+        assert!(world.players[0].down);
+        assert_eq!(world.players[1].lives, 1);
+        assert!(!world.players[1].down);
+    }
+
+    #[test]
+    fn game_over_waits_for_both_players() {
+        let mut events = EventBus::new();
+        let mut world = coop(false);
+        let idle = [Input::default(); 2];
+        hit(&mut world, 0, &mut events);
+        hit(&mut world, 0, &mut events);
+        assert!(world.players[0].down);
+        for _ in 0..300 {
+            assert_eq!(step(&mut world, &idle, &mut events), None);
+        }
+        assert!(!world.collides);
+
+        hit(&mut world, 1, &mut events);
+        hit(&mut world, 1, &mut events);
+        assert!(world.collides);
+        let ending = loop {
+            if let Some(ending) = step(&mut world, &idle, &mut events) {
+                break ending;
+            }
+        };
+        assert_eq!(ending, Ending::AllDown);
+    }
+
+    #[test]
+    fn bullet_caps_are_per_player() {
+        let mut events = EventBus::new();
+        let mut world = coop(false);
+        let fire = Input {
+            fire: true,
+            ..Default::default()
+        };
+        for _ in 0..6 {
+            step(&mut world, &[fire, Input::default()], &mut events);
+        }
+        assert_eq!(world.players[0].bullets.len(), BULLETS_PER_PLAYER);
+        // 一号玩家打满了上限, 不影响二号玩家
+        for _ in 0..6 {
+            step(&mut world, &[Input::default(), fire], &mut events);
+        }
+        assert_eq!(world.players[1].bullets.len(), BULLETS_PER_PLAYER);
+    }
+
+    #[test]
+    fn shield_absorbs_a_hit_before_respawning() {
+        let mut events = EventBus::new();
+        events.record();
+        let mut world = coop(false);
+        world.players[0].shield = 1;
+        let spawn = vec2(world.players[0].ship.x, world.players[0].ship.y);
+
+        hit(&mut world, 0, &mut events);
+        assert_eq!(world.players[0].shield, 0);
+        assert_eq!(world.players[0].lives, 1);
+        assert!(world.players[0].invulnerable > 0.0);
+        assert!(events.recorded().is_empty());
+
+        // 离开出生点后被撞, 用掉一条命回到出生点
+        let left = Input {
+            direction: vec2(-1.0, 0.0),
+            ..Default::default()
+        };
+        for _ in 0..10 {
+            step(&mut world, &[left, Input::default()], &mut events);
+        }
+        assert_ne!(world.players[0].ship.x, spawn.x);
+        hit(&mut world, 0, &mut events);
+        assert_eq!(world.players[0].lives, 0);
+        assert!(!world.players[0].down);
+        assert_eq!(
+            vec2(world.players[0].ship.x, world.players[0].ship.y),
+            spawn
+        );
+        assert!(world.players[0].invulnerable > 0.0);
+        assert!(matches!(
+            events.recorded(),
+            [GameEvent::PlayerHit { player: 0, .. }]
+        ));
+    }
+
+    #[test]
+    fn gameplay_publishes_kills_hits_and_game_over() {
+        let mut world = World::new(1, CoopSettings::default(), &Upgrades::default(), 7);
+        let mut events = EventBus::new();
+        events.record();
+
+        place_square(&mut world, 0, 100.0);
+        let fire = Input {
+            fire: true,
+            ..Default::default()
+        };
+        step(&mut world, &[fire], &mut events);
+        for _ in 0..10 {
+            step(&mut world, &[Input::default()], &mut events);
+        }
+        place_square(&mut world, 0, 0.0);
+        let ending = loop {
+            if let Some(ending) = step(&mut world, &[Input::default()], &mut events) {
+                break ending;
+            }
+        };
+        assert_eq!(ending, Ending::AllDown);
+        world.finish(10, &mut events);
+
+        let recorded: Vec<&GameEvent> = events
+            .recorded()
+            .iter()
+            .filter(|event| {
+                matches!(
+                    event,
+                    GameEvent::EnemyDestroyed { .. }
+                        | GameEvent::PlayerHit { .. }
+                        | GameEvent::GameOver { .. }
+                )
+            })
+            .collect();
+        assert!(
+            matches!(
+                recorded[..],
+                [
+                    GameEvent::EnemyDestroyed { player: 0, .. },
+                    GameEvent::PlayerHit { player: 0, .. },
+                    GameEvent::GameOver { score: 10, .. },
+                ]
+            ),
+            "{recorded:?}"
+        );
+    }
+}
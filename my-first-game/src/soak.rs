@@ -0,0 +1,355 @@
+//! 压力测试, 用自动驾驶在无窗口时加速跑很多局, 输出存活时间、分数分布和死因统计
+//!
+//! `cargo run -p my-first-game --release -- soak --runs 200 --seed 1 --out target/soak`
+//! 会在输出目录中生成每局一行的 `runs.csv` 和汇总的 `summary.json`
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::PathBuf;
+
+use serde::Serialize;
+
+use crate::bot::Bot;
+use crate::events::{EventBus, GameEvent};
+use crate::player::{CoopSettings, MAX_PLAYERS};
+use crate::score::Scoreboard;
+use crate::shape::EnemySize;
+use crate::shop::Upgrades;
+use crate::sim::{Ending, Input, World};
+
+/// 模拟的时间步长, 和 60 FPS 的一帧相同
+const TIME_STEP: f32 = 1.0 / 60.0;
+
+const USAGE: &str = "usage: my-first-game soak [--runs N] [--seed S] [--players 1|2] [--max-time SECONDS] [--out DIR]";
+
+/// 命令行参数
+#[derive(Debug, Clone, PartialEq)]
+pub struct Options {
+    /// 局数, 第 i 局使用种子 `seed.wrapping_add(i)`
+    pub runs: u32,
+    pub seed: u64,
+    pub players: usize,
+    /// 单局最长的游戏时间, 超过后记为 `timeout`
+    pub max_time: f32,
+    /// 输出目录
+    pub out: PathBuf,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            runs: 100,
+            seed: 1,
+            players: 1,
+            max_time: 600.0,
+            out: PathBuf::from("target/soak"),
+        }
+    }
+}
+
+impl Options {
+    /// 解析 `soak` 之后的参数
+    pub fn parse(args: &[String]) -> Result<Self, String> {
+        fn value<T: std::str::FromStr>(flag: &str, value: Option<&String>) -> Result<T, String> {
+            let value = value.ok_or_else(|| format!("missing value for {flag}"))?;
+            value
+                .parse()
+                .map_err(|_| format!("invalid value for {flag}: {value}"))
+        }
+
+        let mut options = Options::default();
+        let mut args = args.iter();
+        while let Some(flag) = args.next() {
+            match flag.as_str() {
+                "--runs" => options.runs = value(flag, args.next())?,
+                "--seed" => options.seed = value(flag, args.next())?,
+                "--players" => options.players = value(flag, args.next())?,
+                "--max-time" => options.max_time = value(flag, args.next())?,
+                "--out" => options.out = value(flag, args.next())?,
+                _ => return Err(format!("unknown argument: {flag}\n{USAGE}")),
+            }
+        }
+        if !(1..=MAX_PLAYERS).contains(&options.players) {
+            return Err(format!("--players must be between 1 and {MAX_PLAYERS}"));
+        }
+        Ok(options)
+    }
+}
+
+/// 一局结束的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Cause {
+    /// 被小方块撞倒
+    Small,
+    Medium,
+    Big,
+    /// 活到了单局的时间上限
+    Timeout,
+}
+
+impl Cause {
+    pub fn name(self) -> &'static str {
+        match self {
+            Cause::Small => "small",
+            Cause::Medium => "medium",
+            Cause::Big => "big",
+            Cause::Timeout => "timeout",
+        }
+    }
+}
+
+/// 一局的结果
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RunResult {
+    pub seed: u64,
+    /// 存活时间(秒)
+    pub survival: f32,
+    pub score: u32,
+    pub wave: u32,
+    pub kills: u32,
+    /// 被撞到的次数, 不含护盾抵挡的
+    pub hits: u32,
+    pub cause: Cause,
+}
+
+/// 用自动驾驶玩一局
+pub fn play(seed: u64, options: &Options) -> RunResult {
+    let bot = Bot::default();
+    let mut world = World::new(
+        options.players,
+        CoopSettings::default(),
+        &Upgrades::default(),
+        seed,
+    );
+    let mut events = EventBus::new();
+    let mut scoreboard = Scoreboard::new(0);
+    let (mut kills, mut hits, mut last_hit) = (0, 0, 0.0);
+
+    let ending = loop {
+        if world.run_time >= options.max_time {
+            break None;
+        }
+        let inputs: Vec<Input> = (0..options.players)
+            .map(|index| bot.input(&world, index))
+            .collect();
+        let ending = world.step(TIME_STEP, &inputs, None, &mut events);
+        let mut tally = |event: &GameEvent| match *event {
+            GameEvent::EnemyDestroyed { .. } => kills += 1,
+            GameEvent::PlayerHit { size, .. } => {
+                hits += 1;
+                last_hit = size;
+            }
+            _ => {}
+        };
+        events.dispatch(&mut [&mut scoreboard, &mut tally]);
+        if ending.is_some() {
+            break ending;
+        }
+    };
+
+    let cause = match ending {
+        Some(Ending::AllDown) => match EnemySize::from_size(last_hit) {
+            EnemySize::Small => Cause::Small,
+            EnemySize::Medium => Cause::Medium,
+            EnemySize::Big => Cause::Big,
+        },
+        // 没有限时, 不会因为时间到结束
+        Some(Ending::TimeUp) | None => Cause::Timeout,
+    };
+    RunResult {
+        seed,
+        survival: world.run_time,
+        score: scoreboard.score,
+        wave: world.waves.number,
+        kills,
+        hits,
+        cause,
+    }
+}
+
+/// 一组数值的分布
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Distribution {
+    pub min: f32,
+    pub mean: f32,
+    pub p10: f32,
+    pub median: f32,
+    pub p90: f32,
+    pub max: f32,
+}
+
+impl Distribution {
+    pub fn new(mut values: Vec<f32>) -> Self {
+        if values.is_empty() {
+            values.push(0.0);
+        }
+        values.sort_by(f32::total_cmp);
+        let percentile = |p: f32| values[((values.len() - 1) as f32 * p).round() as usize];
+        Self {
+            min: values[0],
+            mean: values.iter().sum::<f32>() / values.len() as f32,
+            p10: percentile(0.1),
+            median: percentile(0.5),
+            p90: percentile(0.9),
+            max: values[values.len() - 1],
+        }
+    }
+}
+
+/// 所有局的汇总
+#[derive(Debug, Serialize)]
+pub struct Summary {
+    pub runs: usize,
+    pub players: usize,
+    pub max_time: f32,
+    pub survival: Distribution,
+    pub score: Distribution,
+    /// 每种结束原因的局数
+    pub causes: BTreeMap<Cause, usize>,
+    pub results: Vec<RunResult>,
+}
+
+impl Summary {
+    pub fn new(options: &Options, results: Vec<RunResult>) -> Self {
+        let mut causes = BTreeMap::new();
+        for result in &results {
+            *causes.entry(result.cause).or_default() += 1;
+        }
+        Self {
+            runs: results.len(),
+            players: options.players,
+            max_time: options.max_time,
+            survival: Distribution::new(results.iter().map(|r| r.survival).collect()),
+            score: Distribution::new(results.iter().map(|r| r.score as f32).collect()),
+            causes,
+            results,
+        }
+    }
+
+    /// 每局一行的 CSV
+    pub fn csv(&self) -> String {
+        let mut csv = String::from("seed,survival,score,wave,kills,hits,cause\n");
+        for r in &self.results {
+            let _ = writeln!(
+                csv,
+                "{},{:.2},{},{},{},{},{}",
+                r.seed,
+                r.survival,
+                r.score,
+                r.wave,
+                r.kills,
+                r.hits,
+                r.cause.name()
+            );
+        }
+        csv
+    }
+}
+
+/// 跑完所有局并写入输出目录
+pub fn run(options: &Options) -> std::io::Result<Summary> {
+    let results = (0..options.runs as u64)
+        .map(|i| play(options.seed.wrapping_add(i), options))
+        .collect();
+    let summary = Summary::new(options, results);
+    fs::create_dir_all(&options.out)?;
+    fs::write(options.out.join("runs.csv"), summary.csv())?;
+    let json = serde_json::to_string_pretty(&summary).map_err(std::io::Error::other)?;
+    fs::write(options.out.join("summary.json"), json)?;
+    Ok(summary)
+}
+
+/// `soak` 子命令的入口
+pub fn main(args: &[String]) {
+    let options = match Options::parse(args) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("{err}");
+            std::process::exit(2);
+        }
+    };
+    match run(&options) {
+        Ok(summary) => {
+            println!(
+                "{} runs, survival median {:.1}s (p10 {:.1}s, p90 {:.1}s), score median {:.0}",
+                summary.runs,
+                summary.survival.median,
+                summary.survival.p10,
+                summary.survival.p90,
+                summary.score.median
+            );
+            for (cause, count) in &summary.causes {
+                println!("  {}: {}", cause.name(), count);
+            }
+            println!("written to {}", options.out.display());
+        }
+        Err(err) => {
+            eprintln!("failed to write results: {err}");
+            std::process::exit(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn parses_options() {
+        let options = Options::parse(&args("--runs 5 --seed 42 --players 2 --out x")).unwrap();
+        assert_eq!(options.runs, 5);
+        assert_eq!(options.seed, 42);
+        assert_eq!(options.players, 2);
+        assert_eq!(options.out, PathBuf::from("x"));
+        assert!(Options::parse(&args("--players 3")).is_err());
+        assert!(Options::parse(&args("--runs")).is_err());
+        assert!(Options::parse(&args("--fast")).is_err());
+    }
+
+    #[test]
+    fn distribution_uses_nearest_rank() {
+        let distribution = Distribution::new((1..=11).map(|i| i as f32).collect());
+        assert_eq!(distribution.min, 1.0);
+        assert_eq!(distribution.p10, 2.0);
+        assert_eq!(distribution.median, 6.0);
+        assert_eq!(distribution.p90, 10.0);
+        assert_eq!(distribution.mean, 6.0);
+    }
+
+    #[test]
+    fn summary_counts_causes() {
+        let options = Options {
+            runs: 3,
+            max_time: 20.0,
+            ..Default::default()
+        };
+        let results: Vec<RunResult> = (0..3).map(|seed| play(seed, &options)).collect();
+        assert!(results.iter().all(|r| r.survival <= 20.0 + TIME_STEP));
+        let summary = Summary::new(&options, results);
+        assert_eq!(summary.causes.values().sum::<usize>(), 3);
+        let csv = summary.csv();
+        assert_eq!(csv.lines().count(), 4);
+        assert!(csv.starts_with("seed,survival,score"));
+    }
+
+    #[test]
+    fn seeds_wrap_around() {
+        let options = Options {
+            runs: 2,
+            seed: u64::MAX,
+            max_time: 1.0,
+            out: std::env::temp_dir().join("my-first-game-soak-test"),
+            ..Default::default()
+        };
+        let summary = run(&options).unwrap();
+        let seeds: Vec<u64> = summary.results.iter().map(|r| r.seed).collect();
+        assert_eq!(seeds, [u64::MAX, 0]);
+        fs::remove_dir_all(&options.out).unwrap();
+    }
+}
@@ -1,6 +1,690 @@
+use macroquad::prelude::*;
+use macroquad_particles::Emitter;
+
+use crate::achievements::{Achievement, Profile};
+use crate::bot::Bot;
+use crate::canvas::{Anchor, Scaling, VIRTUAL_HEIGHT, VIRTUAL_WIDTH};
+use crate::events::GameEvent;
+use crate::game::Game;
+use crate::mode::{self, GameMode};
+use crate::paint::Painter;
+use crate::player::{CoopSettings, MAX_PLAYERS};
+use crate::save;
+use crate::scene::{Animation, Cover, Scene, Transition};
+use crate::settings::{Assist, Palette, SETTINGS_SAVE};
+use crate::shop::{self, Upgrade};
+use crate::sim::Input;
+use crate::tune_rocket_exhaust;
+
+/// 开始和结束一局时的淡入淡出时长
+const FADE_DURATION: f32 = 0.5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum GameState {
     MainMenu,
     Playing,
     Paused,
     GameOver,
-}
\ No newline at end of file
+    /// 粒子预设预览, 用于调试粒子效果
+    ParticlePreview,
+    /// 成就和统计数据
+    Achievements,
+    /// 各模式的排行榜
+    Leaderboard,
+    /// 升级商店
+    Shop,
+    /// 辅助功能和画面设置
+    Options,
+}
+
+/// 主菜单
+pub struct MainMenu;
+
+impl Scene<Game> for MainMenu {
+    fn state(&self) -> GameState {
+        GameState::MainMenu
+    }
+
+    fn update(&mut self, game: &mut Game) -> Transition<Game> {
+        if game.key_pressed(KeyCode::Escape) {
+            return Transition::quit();
+        }
+        if game.key_pressed(KeyCode::P) {
+            return Transition::push(ParticlePreview::default());
+        }
+        if game.key_pressed(KeyCode::A) {
+            return Transition::push(AchievementList);
+        }
+        if game.key_pressed(KeyCode::B) {
+            return Transition::push(LeaderboardList { mode: game.mode });
+        }
+        if game.key_pressed(KeyCode::U) {
+            return Transition::push(Shop::default());
+        }
+        if game.key_pressed(KeyCode::O) {
+            return Transition::push(Options::default());
+        }
+        if game.key_pressed(KeyCode::M) {
+            game.mode = game.mode.next();
+        }
+        // 双人模式的设置
+        if game.key_pressed(KeyCode::S) {
+            game.coop.shared_score = !game.coop.shared_score;
+        }
+        if game.key_pressed(KeyCode::L) {
+            game.coop.shared_lives = !game.coop.shared_lives;
+        }
+        if game.confirm_pressed() {
+            game.player_count = 1;
+            return Transition::replace(Playing).fade(FADE_DURATION);
+        }
+        if game.key_pressed(KeyCode::Key2) {
+            game.player_count = MAX_PLAYERS;
+            return Transition::replace(Playing).fade(FADE_DURATION);
+        }
+        Transition::none()
+    }
+
+    fn draw(&mut self, game: &mut Game, _focused: bool) {
+        Self::paint(&mut game.screen(), game.mode, game.coop);
+    }
+}
+
+impl MainMenu {
+    /// 绘制菜单文字, 只依赖菜单显示的设置, 方便无窗口测试
+    pub fn paint(p: &mut impl Painter, mode: GameMode, coop: CoopSettings) {
+        let text_dimensions =
+            p.centered_text("PRESS SPACE TO START!", VIRTUAL_HEIGHT / 2.0, 50, RED);
+        let on_off = |on: bool| if on { "ON" } else { "OFF" };
+        let lines = [
+            format!("M: MODE {}", mode.name()),
+            "PRESS 2 FOR CO-OP (P2: WASD + F)".to_string(),
+            format!(
+                "S: SHARED SCORE {}  L: SHARED LIVES {}",
+                on_off(coop.shared_score),
+                on_off(coop.shared_lives)
+            ),
+            "U: SHOP  A: ACHIEVEMENTS  B: LEADERBOARDS".to_string(),
+            "O: OPTIONS  P: PARTICLES".to_string(),
+        ];
+        let mut y = VIRTUAL_HEIGHT / 2.0 + text_dimensions.height + 20.0;
+        for line in lines {
+            p.centered_text(&line, y, 25, GRAY);
+            y += 30.0;
+        }
+    }
+}
+
+/// 游戏进行中
+pub struct Playing;
+
+impl Scene<Game> for Playing {
+    fn state(&self) -> GameState {
+        GameState::Playing
+    }
+
+    fn enter(&mut self, game: &mut Game) {
+        game.reset();
+        game.events.publish(GameEvent::RunStarted);
+        game.events.publish(GameEvent::WaveStarted {
+            wave: game.world.waves.number,
+        });
+    }
+
+    fn update(&mut self, game: &mut Game) -> Transition<Game> {
+        // Pause, 窗口失去焦点时自动暂停
+        if game.key_pressed(KeyCode::Escape) || game.focus_lost {
+            return Transition::push(Paused);
+        }
+
+        let delta_time = game.clock.delta();
+        game.hud.update(delta_time);
+
+        // 先读取所有玩家的按键, 再统一模拟
+        let inputs: Vec<Input> = game
+            .world
+            .players
+            .iter()
+            .enumerate()
+            .map(|(index, player)| {
+                if game.autopilot && index == 0 {
+                    Bot::default().input(&game.world, index)
+                } else {
+                    player.controls.input(game)
+                }
+            })
+            .collect();
+        #[cfg(not(target_arch = "wasm32"))]
+        if !game.world.collides {
+            for (player, input) in game.world.players.iter().zip(&inputs) {
+                if !player.down && input.direction.x != 0.0 {
+                    game.direction_modifier += 0.05 * delta_time;
+                }
+            }
+        }
+
+        if game
+            .world
+            .step(
+                delta_time,
+                &inputs,
+                game.mode.time_limit(),
+                &mut game.events,
+            )
+            .is_some()
+        {
+            game.world.finish(game.scoreboard.score, &mut game.events);
+            return Transition::replace(GameOver).wipe(FADE_DURATION);
+        }
+
+        Transition::none()
+    }
+
+    fn draw(&mut self, game: &mut Game, focused: bool) {
+        // 尾焰在 draw 中按 macroquad 的帧时间推进, 不受游戏时钟的缩放和顿帧影响;
+        // 被暂停覆盖时不绘制
+        if focused {
+            let players = game.world.players.iter().zip(game.exhausts.iter_mut());
+            for (player, exhaust) in players.filter(|(player, _)| !player.down) {
+                tune_rocket_exhaust(&mut exhaust.config, &game.presets, player.velocity);
+                let exhaust_pos = vec2(player.ship.x, player.ship.y);
+                exhaust.draw(exhaust_pos);
+            }
+        }
+        game.draw_world();
+    }
+}
+
+/// 暂停, 覆盖在游戏画面之上
+pub struct Paused;
+
+impl Scene<Game> for Paused {
+    fn state(&self) -> GameState {
+        GameState::Paused
+    }
+
+    fn is_overlay(&self) -> bool {
+        true
+    }
+
+    fn enter(&mut self, game: &mut Game) {
+        game.clock.pause();
+    }
+
+    fn exit(&mut self, game: &mut Game) {
+        game.clock.resume();
+    }
+
+    fn update(&mut self, game: &mut Game) -> Transition<Game> {
+        if game.confirm_pressed() {
+            return Transition::pop();
+        }
+        Transition::none()
+    }
+
+    fn draw(&mut self, game: &mut Game, _focused: bool) {
+        // 游戏时间停止, 提示文字按界面时间闪烁
+        let pulse = (game.clock.ui_time() * 4.0).sin() as f32 * 0.5 + 0.5;
+        game.draw_centered_text(
+            "PAUSED! PRESS SPACE TO CONTINUE",
+            VIRTUAL_HEIGHT / 2.0,
+            30,
+            Color {
+                a: 0.4 + 0.6 * pulse,
+                ..PINK
+            },
+        );
+    }
+}
+
+/// 游戏结束
+pub struct GameOver;
+
+impl Scene<Game> for GameOver {
+    fn state(&self) -> GameState {
+        GameState::GameOver
+    }
+
+    fn update(&mut self, game: &mut Game) -> Transition<Game> {
+        // 游戏结束重开
+        if game.confirm_pressed() {
+            return Transition::replace(MainMenu).fade(FADE_DURATION);
+        }
+        Transition::none()
+    }
+
+    fn draw(&mut self, game: &mut Game, _focused: bool) {
+        let hafl_window_height = VIRTUAL_HEIGHT / 2.0;
+        let text_dimensions = game.draw_centered_text(
+            "Game Over! Press Space to Restart",
+            hafl_window_height,
+            30,
+            PINK,
+        );
+        if game.scoreboard.is_record() {
+            game.draw_centered_text(
+                "Congratulations!",
+                hafl_window_height + text_dimensions.height + 10.0,
+                30,
+                PINK,
+            );
+        }
+        game.draw_centered_text(
+            &format!("获得金币 +{}", shop::coins_for(game.scoreboard.score)),
+            hafl_window_height + (text_dimensions.height + 10.0) * 3.0,
+            25,
+            GOLD,
+        );
+        if let Some(rank) = game.last_rank {
+            game.draw_centered_text(
+                &format!("{} 排行榜第 {} 名", game.mode.name(), rank + 1),
+                hafl_window_height + (text_dimensions.height + 10.0) * 2.0,
+                25,
+                YELLOW,
+            );
+        }
+    }
+}
+
+/// 粒子预设预览
+#[derive(Default)]
+pub struct ParticlePreview {
+    /// 当前选中的预设
+    index: usize,
+    emitter: Option<Emitter>,
+}
+
+impl Scene<Game> for ParticlePreview {
+    fn state(&self) -> GameState {
+        GameState::ParticlePreview
+    }
+
+    fn update(&mut self, game: &mut Game) -> Transition<Game> {
+        let count = game.presets.names().len();
+        if game.key_pressed(KeyCode::Escape) || count == 0 {
+            return Transition::pop();
+        }
+        if game.presets_reloaded {
+            self.emitter = None;
+        }
+        if game.key_pressed(KeyCode::Right) {
+            self.index += 1;
+            self.emitter = None;
+        }
+        if game.key_pressed(KeyCode::Left) {
+            self.index += count - 1;
+            self.emitter = None;
+        }
+        self.index %= count;
+
+        // 一次性的效果播放完毕后自动重播, 方便调参
+        let finished = self
+            .emitter
+            .as_ref()
+            .is_none_or(|emitter| emitter.config.one_shot && !emitter.config.emitting);
+        if game.key_pressed(KeyCode::Space) || finished {
+            let name = game.presets.names()[self.index];
+            self.emitter = Some(Emitter::new(game.presets.get(name)));
+        }
+        Transition::none()
+    }
+
+    fn draw(&mut self, game: &mut Game, _focused: bool) {
+        if let Some(emitter) = self.emitter.as_mut() {
+            emitter.draw(vec2(VIRTUAL_WIDTH / 2.0, VIRTUAL_HEIGHT / 2.0));
+        }
+
+        let names = game.presets.names();
+        let Some(name) = names.get(self.index) else {
+            return;
+        };
+        let stats = game.effects.stats();
+        let lines = [
+            format!("PRESET: {} ({}/{})", name, self.index + 1, names.len()),
+            "LEFT/RIGHT: SWITCH  SPACE: REPLAY  ESC: BACK".to_string(),
+            format!(
+                "EFFECTS LIVE: {} POOLED: {} DROPPED: {} EVICTED: {}",
+                stats.live, stats.pooled, stats.dropped, stats.evicted
+            ),
+        ];
+        for (i, line) in lines.iter().enumerate() {
+            game.draw_text(line, 10.0, 35.0 + i as f32 * 30.0, 25, WHITE);
+        }
+        if let Some(err) = &game.presets.last_error {
+            game.draw_anchored(err, Anchor::BottomLeft, 20, RED);
+        }
+    }
+}
+
+/// 成就列表和统计数据
+pub struct AchievementList;
+
+impl Scene<Game> for AchievementList {
+    fn state(&self) -> GameState {
+        GameState::Achievements
+    }
+
+    fn update(&mut self, game: &mut Game) -> Transition<Game> {
+        if game.key_pressed(KeyCode::Escape) {
+            return Transition::pop();
+        }
+        Transition::none()
+    }
+
+    fn draw(&mut self, game: &mut Game, _focused: bool) {
+        let profile = &game.profile;
+        game.draw_text(
+            &format!(
+                "成就 ({}/{})  档案: {}",
+                profile.unlocked.len(),
+                Achievement::ALL.len(),
+                game.profile_name
+            ),
+            20.0,
+            40.0,
+            30,
+            WHITE,
+        );
+
+        let mut y = 90.0;
+        for achievement in Achievement::ALL {
+            let color = if profile.unlocked.contains(&achievement) {
+                GOLD
+            } else {
+                DARKGRAY
+            };
+            game.draw_text(achievement.title(), 20.0, y, 25, color);
+            game.draw_text(achievement.description(), 200.0, y, 20, color);
+            y += 35.0;
+        }
+
+        let stats = &profile.stats;
+        let lines = [
+            format!("游玩局数: {}", stats.runs),
+            format!(
+                "击毁方块: {} (小 {} / 中 {} / 大 {})",
+                stats.total_kills(),
+                stats.kills[0],
+                stats.kills[1],
+                stats.kills[2]
+            ),
+            format!(
+                "发射子弹: {}  命中率: {:.0}%",
+                stats.shots_fired,
+                stats.accuracy() * 100.0
+            ),
+            format!(
+                "最长一局: {:.0} 秒  总游玩时间: {:.0} 分钟",
+                stats.longest_run,
+                stats.play_time / 60.0
+            ),
+        ];
+        y += 20.0;
+        for line in lines {
+            game.draw_text(&line, 20.0, y, 22, WHITE);
+            y += 30.0;
+        }
+        game.draw_anchored("ESC: BACK", Anchor::BottomLeft, 20, GRAY);
+    }
+}
+
+/// 排行榜, 左右键切换模式
+pub struct LeaderboardList {
+    mode: GameMode,
+}
+
+impl Scene<Game> for LeaderboardList {
+    fn state(&self) -> GameState {
+        GameState::Leaderboard
+    }
+
+    fn update(&mut self, game: &mut Game) -> Transition<Game> {
+        if game.key_pressed(KeyCode::Escape) {
+            return Transition::pop();
+        }
+        if game.key_pressed(KeyCode::Right) {
+            self.mode = self.mode.next();
+        }
+        if game.key_pressed(KeyCode::Left) {
+            self.mode = self.mode.next().next();
+        }
+        Transition::none()
+    }
+
+    fn draw(&mut self, game: &mut Game, _focused: bool) {
+        game.draw_centered_text(self.mode.name(), 50.0, 35, WHITE);
+        // 每日挑战只显示今天的记录
+        let entries = game.leaderboards.entries(self.mode, mode::today());
+        if entries.is_empty() {
+            game.draw_centered_text("暂无记录", 120.0, 25, DARKGRAY);
+        }
+        for (rank, entry) in entries.iter().enumerate() {
+            let y = 110.0 + rank as f32 * 32.0;
+            let color = if entry.profile == game.profile_name {
+                GOLD
+            } else {
+                WHITE
+            };
+            let x = VIRTUAL_WIDTH / 2.0 - 200.0;
+            game.draw_text(&format!("{:>2}.", rank + 1), x, y, 25, color);
+            game.draw_text(&entry.profile, x + 60.0, y, 25, color);
+            game.draw_text(&entry.score.to_string(), x + 320.0, y, 25, color);
+        }
+        game.draw_anchored(
+            "LEFT/RIGHT: SWITCH MODE  ESC: BACK",
+            Anchor::BottomLeft,
+            20,
+            GRAY,
+        );
+    }
+}
+
+/// 升级商店, 上下键选择, 空格购买
+#[derive(Default)]
+pub struct Shop {
+    /// 当前选中的升级
+    index: usize,
+}
+
+impl Scene<Game> for Shop {
+    fn state(&self) -> GameState {
+        GameState::Shop
+    }
+
+    fn update(&mut self, game: &mut Game) -> Transition<Game> {
+        if game.key_pressed(KeyCode::Escape) {
+            return Transition::pop();
+        }
+        let count = Upgrade::ALL.len();
+        if game.key_pressed(KeyCode::Down) {
+            self.index = (self.index + 1) % count;
+        }
+        if game.key_pressed(KeyCode::Up) {
+            self.index = (self.index + count - 1) % count;
+        }
+        if game.key_pressed(KeyCode::Space) {
+            let profile = &mut game.profile;
+            if profile
+                .upgrades
+                .buy(Upgrade::ALL[self.index], &mut profile.coins)
+            {
+                save::store(&Profile::save_name(&game.profile_name), &game.profile);
+            }
+        }
+        Transition::none()
+    }
+
+    fn draw(&mut self, game: &mut Game, _focused: bool) {
+        let upgrades = &game.profile.upgrades;
+        game.draw_text(
+            &format!("商店  金币: {}", game.profile.coins),
+            20.0,
+            40.0,
+            30,
+            GOLD,
+        );
+        for (i, upgrade) in Upgrade::ALL.into_iter().enumerate() {
+            let y = 100.0 + i as f32 * 50.0;
+            let level = upgrades.level(upgrade);
+            let price = match upgrades.next_cost(upgrade) {
+                Some(cost) => format!("{} 金币", cost),
+                None => "已满级".to_string(),
+            };
+            let color = if i == self.index { YELLOW } else { WHITE };
+            if i == self.index {
+                game.draw_text(">", 20.0, y, 25, color);
+            }
+            game.draw_text(
+                &format!("{} Lv.{}/{}", upgrade.name(), level, upgrade.max_level()),
+                45.0,
+                y,
+                25,
+                color,
+            );
+            game.draw_text(upgrade.description(), 250.0, y, 20, color);
+            game.draw_text(&price, 480.0, y, 20, color);
+        }
+        game.draw_anchored(
+            "UP/DOWN: SELECT  SPACE: BUY  ESC: BACK",
+            Anchor::BottomLeft,
+            20,
+            GRAY,
+        );
+    }
+}
+
+/// 辅助功能和画面设置, 上下键选择, 左右键调整
+#[derive(Default)]
+pub struct Options {
+    /// 当前选中的设置项
+    index: usize,
+}
+
+impl Options {
+    const ITEMS: usize = 7;
+}
+
+impl Scene<Game> for Options {
+    fn state(&self) -> GameState {
+        GameState::Options
+    }
+
+    fn update(&mut self, game: &mut Game) -> Transition<Game> {
+        if game.key_pressed(KeyCode::Escape) {
+            return Transition::pop();
+        }
+        if game.key_pressed(KeyCode::Down) {
+            self.index = (self.index + 1) % Self::ITEMS;
+        }
+        if game.key_pressed(KeyCode::Up) {
+            self.index = (self.index + Self::ITEMS - 1) % Self::ITEMS;
+        }
+        let step = if game.key_pressed(KeyCode::Right) {
+            1
+        } else if game.key_pressed(KeyCode::Left) {
+            -1
+        } else {
+            return Transition::none();
+        };
+
+        // 在列表中循环选择
+        fn cycle<T: Copy + PartialEq>(all: &[T], current: T, step: i32) -> T {
+            let index = all.iter().position(|item| *item == current).unwrap_or(0);
+            all[(index as i32 + step).rem_euclid(all.len() as i32) as usize]
+        }
+        let settings = &mut game.settings;
+        match self.index {
+            0 => settings.palette = cycle(&Palette::ALL, settings.palette, step),
+            1 => settings.high_contrast = !settings.high_contrast,
+            2 => settings.min_alpha = (settings.min_alpha + 0.1 * step as f32).clamp(0.0, 1.0),
+            3 => {
+                let all = [Assist::Off, Assist::Hold, Assist::Always];
+                settings.assist = cycle(&all, settings.assist, step);
+            }
+            4 => {
+                settings.assist_speed = (settings.assist_speed + 0.1 * step as f32).clamp(0.2, 1.0)
+            }
+            5 => settings.text_scale = (settings.text_scale + 0.25 * step as f32).clamp(0.75, 2.0),
+            _ => {
+                let all = [Scaling::Letterbox, Scaling::Integer];
+                settings.scaling = cycle(&all, settings.scaling, step);
+            }
+        }
+        save::store(SETTINGS_SAVE, &game.settings);
+        Transition::none()
+    }
+
+    fn draw(&mut self, game: &mut Game, _focused: bool) {
+        let settings = &game.settings;
+        game.draw_text("设置", 20.0, 40.0, 30, WHITE);
+        let on_off = |on: bool| if on { "开" } else { "关" };
+        let items = [
+            ("配色方案", settings.palette.name().to_string()),
+            ("高对比度", on_off(settings.high_contrast).to_string()),
+            (
+                "敌方最低不透明度",
+                format!("{:.0}%", settings.min_alpha * 100.0),
+            ),
+            ("辅助模式", settings.assist.name().to_string()),
+            (
+                "辅助模式速度",
+                format!("{:.0}%", settings.assist_speed * 100.0),
+            ),
+            ("文字缩放", format!("{:.0}%", settings.text_scale * 100.0)),
+            ("画面缩放", settings.scaling.name().to_string()),
+        ];
+        let mut y = 100.0;
+        for (i, (name, value)) in items.iter().enumerate() {
+            let color = if i == self.index { YELLOW } else { WHITE };
+            if i == self.index {
+                game.draw_text(">", 20.0, y, 25, color);
+            }
+            game.draw_text(name, 45.0, y, 25, color);
+            game.draw_text(&format!("< {} >", value), 320.0, y, 25, color);
+            y += game.measure_text(name, 25).height.max(25.0) + 20.0;
+        }
+
+        // 预览敌方和我方的颜色
+        let sample = [
+            Color::new(0.9, 0.2, 0.3, 0.1),
+            Color::new(0.2, 0.8, 0.4, 0.6),
+            Color::new(0.3, 0.3, 0.9, 1.0),
+        ];
+        for (i, color) in sample.into_iter().enumerate() {
+            let x = 45.0 + i as f32 * 60.0;
+            draw_rectangle(x, y, 40.0, 40.0, settings.hostile(color));
+            draw_circle(x + 20.0, y + 80.0, 16.0, settings.friendly(color));
+            if let Some(outline) = settings.outline() {
+                draw_rectangle_lines(x, y, 40.0, 40.0, 3.0, outline);
+                draw_circle_lines(x + 20.0, y + 80.0, 16.0, 2.0, outline);
+            }
+        }
+
+        game.draw_anchored(
+            "UP/DOWN: SELECT  LEFT/RIGHT: CHANGE  ESC: BACK",
+            Anchor::BottomLeft,
+            20,
+            GRAY,
+        );
+    }
+}
+
+/// 绘制场景切换的遮罩
+pub fn draw_cover(cover: Cover) {
+    let (w, h) = (VIRTUAL_WIDTH, VIRTUAL_HEIGHT);
+    match cover.animation {
+        Animation::Cut => {}
+        Animation::Fade(_) => {
+            draw_rectangle(0.0, 0.0, w, h, Color::new(0.0, 0.0, 0.0, cover.amount))
+        }
+        Animation::Wipe(_) => {
+            // 前半段从左侧盖住, 后半段从左侧露出
+            let x = if cover.revealing {
+                w * (1.0 - cover.amount)
+            } else {
+                0.0
+            };
+            draw_rectangle(x, 0.0, w * cover.amount, h, BLACK);
+        }
+    }
+}
@@ -0,0 +1,165 @@
+use macroquad::prelude::*;
+use macroquad::rand::RandGenerator;
+
+use crate::shape::Shape;
+
+/// 每一波持续的时间（秒）
+const WAVE_DURATION: f32 = 30.0;
+/// 方块生成阈值, `gen_range(0, 99)` 不小于它时生成方块
+const SPAWN_THRESHOLD: i32 = 95;
+/// 控制台强制跳波后生成阈值的下限, 防止刷怪过于密集
+#[cfg(debug_assertions)]
+const MIN_THRESHOLD: i32 = 80;
+/// 控制台可以跳到的最大波次
+#[cfg(debug_assertions)]
+const MAX_WAVE: u32 = 9999;
+/// 生成方块的判定间隔, 与帧率无关, 保证相同种子生成相同的方块序列
+const SPAWN_TICK: f32 = 1.0 / 60.0;
+
+/// 波次, 随时间推进
+///
+/// 正常游戏中生成频率不随波次变化, 只有从控制台强制跳波后才会逐波提高, 便于测试高强度的场面.
+pub struct Waves {
+    pub number: u32,
+    elapsed: f32,
+    /// 是否从控制台强制跳过波次
+    #[cfg(debug_assertions)]
+    forced: bool,
+}
+
+impl Waves {
+    pub fn new() -> Self {
+        Self {
+            number: 1,
+            elapsed: 0.0,
+            #[cfg(debug_assertions)]
+            forced: false,
+        }
+    }
+
+    /// 推进波次计时, 进入新的一波时返回 true
+    pub fn update(&mut self, delta_time: f32) -> bool {
+        self.elapsed += delta_time;
+        if self.elapsed >= WAVE_DURATION {
+            self.elapsed -= WAVE_DURATION;
+            self.number = self.number.saturating_add(1);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// 直接跳到第 `number` 波, 之后生成频率随波次提高
+    #[cfg(debug_assertions)]
+    pub fn set(&mut self, number: u32) {
+        self.number = number.clamp(1, MAX_WAVE);
+        self.elapsed = 0.0;
+        self.forced = true;
+    }
+
+    /// 当前波次的方块生成阈值
+    pub fn spawn_threshold(&self) -> i32 {
+        #[cfg(debug_assertions)]
+        if self.forced {
+            let ramp = i32::try_from(self.number - 1).unwrap_or(i32::MAX);
+            return SPAWN_THRESHOLD.saturating_sub(ramp).max(MIN_THRESHOLD);
+        }
+        SPAWN_THRESHOLD
+    }
+}
+
+/// 方块生成器, 使用独立的随机数生成器, 不受玩家操作影响
+pub struct Spawner {
+    rng: RandGenerator,
+    /// 距离下一次判定还未消耗的时间
+    accumulator: f32,
+}
+
+impl Spawner {
+    pub fn new(seed: u64) -> Self {
+        let rng = RandGenerator::new();
+        rng.srand(seed);
+        Self {
+            rng,
+            accumulator: 0.0,
+        }
+    }
+
+    /// 推进 `delta_time`, 返回这段时间内生成的方块
+    pub fn update(&mut self, delta_time: f32, threshold: i32, width: f32) -> Vec<Shape> {
+        let rng = &self.rng;
+        let mut squares = vec![];
+        self.accumulator += delta_time;
+        while self.accumulator >= SPAWN_TICK {
+            self.accumulator -= SPAWN_TICK;
+            if rng.gen_range(0, 99) < threshold {
+                continue;
+            }
+            let size = rng.gen_range(16.0, 64.0);
+            squares.push(Shape {
+                size,
+                speed: rng.gen_range(16.0, 64.0),
+                drift: 0.0,
+                x: rng.gen_range(size / 2.0, width - size / 2.0),
+                y: -size,
+                color: color_u8!(
+                    rng.gen_range(0, 255),
+                    rng.gen_range(0, 255),
+                    rng.gen_range(0, 255),
+                    rng.gen_range(0, 255)
+                ),
+                collided: false,
+            });
+        }
+        squares
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spawn_positions(seed: u64, frame: f32) -> Vec<f32> {
+        let mut spawner = Spawner::new(seed);
+        let mut xs = vec![];
+        for _ in 0..(10.0 / frame) as usize {
+            xs.extend(
+                spawner
+                    .update(frame, 90, 800.0)
+                    .iter()
+                    .map(|square| square.x),
+            );
+        }
+        xs
+    }
+
+    #[test]
+    fn same_seed_spawns_same_sequence_at_any_frame_rate() {
+        let at_60 = spawn_positions(7, 1.0 / 60.0);
+        let at_30 = spawn_positions(7, 1.0 / 30.0);
+        assert!(!at_60.is_empty());
+        let common = at_60.len().min(at_30.len());
+        assert_eq!(at_60[..common], at_30[..common]);
+        assert_ne!(at_60, spawn_positions(8, 1.0 / 60.0));
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    fn only_forced_waves_raise_the_spawn_rate() {
+        let mut waves = Waves::new();
+        for _ in 0..10 {
+            waves.update(WAVE_DURATION);
+        }
+        assert_eq!(waves.number, 11);
+        assert_eq!(waves.spawn_threshold(), SPAWN_THRESHOLD);
+
+        waves.set(3);
+        assert_eq!(waves.spawn_threshold(), SPAWN_THRESHOLD - 2);
+        // 超大的波次不会溢出
+        waves.set(u32::MAX);
+        assert_eq!(waves.number, MAX_WAVE);
+        assert_eq!(waves.spawn_threshold(), MIN_THRESHOLD);
+        waves.set(0);
+        assert_eq!(waves.number, 1);
+    }
+}
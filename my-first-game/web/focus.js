@@ -0,0 +1,13 @@
+// 页面隐藏或失去焦点时通知游戏自动暂停, 需要在 mq_js_bundle.js 之后、load 之前引入
+miniquad_add_plugin({
+    name: "focus",
+    version: 1,
+    on_init: function () {
+        var notify = function () {
+            wasm_exports.focus_changed(!document.hidden && document.hasFocus());
+        };
+        document.addEventListener("visibilitychange", notify);
+        window.addEventListener("blur", notify);
+        window.addEventListener("focus", notify);
+    }
+});
@@ -0,0 +1,104 @@
+//! `Pool` 和 `DensePool` 的对比, `cargo bench -p pool`
+
+use criterion::{BenchmarkId, Criterion, black_box, criterion_group, criterion_main};
+use pool::{DensePool, Handle, Pool};
+
+const SLOTS: usize = 10_000;
+
+/// 填满 `SLOTS` 个槽位, 再按 `live` 的比例保留
+fn filled<P>(
+    live: f32,
+    pool: &mut P,
+    spawn: impl Fn(&mut P, u64) -> Handle<u64>,
+    despawn: impl Fn(&mut P, Handle<u64>),
+) -> Vec<Handle<u64>> {
+    let handles: Vec<_> = (0..SLOTS as u64).map(|i| spawn(pool, i)).collect();
+    let keep_every = (1.0 / live).round() as usize;
+    let mut kept = vec![];
+    for (i, handle) in handles.into_iter().enumerate() {
+        if i % keep_every == 0 {
+            kept.push(handle);
+        } else {
+            despawn(pool, handle);
+        }
+    }
+    kept
+}
+
+fn iterate(c: &mut Criterion) {
+    let mut group = c.benchmark_group("iterate");
+    for live in [1.0, 0.5, 0.1] {
+        let mut pool = Pool::new();
+        filled(live, &mut pool, Pool::spawn, |p, h| {
+            p.despawn(h);
+        });
+        let mut dense = DensePool::new();
+        filled(live, &mut dense, DensePool::spawn, |p, h| {
+            p.despawn(h);
+        });
+
+        group.bench_with_input(BenchmarkId::new("Pool", live), &pool, |b, pool| {
+            b.iter(|| pool.iter().map(|(_, v)| *v).sum::<u64>())
+        });
+        group.bench_with_input(BenchmarkId::new("DensePool", live), &dense, |b, dense| {
+            b.iter(|| dense.as_slice().iter().sum::<u64>())
+        });
+    }
+    group.finish();
+}
+
+fn lookup(c: &mut Criterion) {
+    let mut group = c.benchmark_group("lookup");
+    let mut pool = Pool::new();
+    let handles = filled(0.5, &mut pool, Pool::spawn, |p, h| {
+        p.despawn(h);
+    });
+    let mut dense = DensePool::new();
+    let dense_handles = filled(0.5, &mut dense, DensePool::spawn, |p, h| {
+        p.despawn(h);
+    });
+
+    group.bench_function("Pool", |b| {
+        b.iter(|| {
+            handles
+                .iter()
+                .map(|h| *pool.borrow(black_box(*h)).unwrap())
+                .sum::<u64>()
+        })
+    });
+    group.bench_function("DensePool", |b| {
+        b.iter(|| {
+            dense_handles
+                .iter()
+                .map(|h| *dense.borrow(black_box(*h)).unwrap())
+                .sum::<u64>()
+        })
+    });
+    group.finish();
+}
+
+fn churn(c: &mut Criterion) {
+    let mut group = c.benchmark_group("spawn_despawn");
+    group.bench_function("Pool", |b| {
+        let mut pool = Pool::new();
+        b.iter(|| {
+            let handles: Vec<_> = (0..1000).map(|i| pool.spawn(i)).collect();
+            for handle in handles {
+                pool.despawn(handle);
+            }
+        })
+    });
+    group.bench_function("DensePool", |b| {
+        let mut pool = DensePool::new();
+        b.iter(|| {
+            let handles: Vec<_> = (0..1000).map(|i| pool.spawn(i)).collect();
+            for handle in handles {
+                pool.despawn(handle);
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, iterate, lookup, churn);
+criterion_main!(benches);
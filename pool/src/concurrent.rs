@@ -0,0 +1,329 @@
+//! 可以在多个线程中同时创建值的对象池
+//!
+//! 工作线程通过 `&self` 无锁地预留句柄, 值先放进待提交队列, 在同步点调用
+//! [`ConcurrentPool::commit`] 后才真正进入池中. 预留时从空闲列表的末尾往前取,
+//! 取完后再按顺序分配新的槽位, 所以只需要一个原子计数器.
+
+#[cfg(loom)]
+use loom::sync::{Mutex, atomic::AtomicIsize};
+use std::sync::atomic::Ordering;
+#[cfg(not(loom))]
+use std::sync::{Mutex, atomic::AtomicIsize};
+
+use crate::{Entry, Generation, Handle};
+
+/// 支持并发创建的世代对象池, 句柄的语义和 [`Pool`](crate::Pool) 相同
+pub struct ConcurrentPool<T> {
+    entries: Vec<Entry<T>>,
+    free_indices: Vec<u32>,
+    /// 为正时是空闲列表中还没被预留的数量, 为负时是需要新增的槽位数
+    cursor: AtomicIsize,
+    /// 已经预留并给出值, 等待提交的句柄
+    pending: Mutex<Vec<(Handle<T>, T)>>,
+    len: usize,
+    retired: usize,
+}
+
+impl<T> Default for ConcurrentPool<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> ConcurrentPool<T> {
+    pub fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+            free_indices: Vec::new(),
+            cursor: AtomicIsize::new(0),
+            pending: Mutex::new(Vec::new()),
+            len: 0,
+            retired: 0,
+        }
+    }
+
+    /// 已提交的存活值的数量
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// 无锁地预留一个句柄, 提交前要用 [`insert`](Self::insert) 给出值,
+    /// 否则提交时句柄作废
+    pub fn reserve(&self) -> Handle<T> {
+        let n = self.cursor.fetch_sub(1, Ordering::Relaxed);
+        if n > 0 {
+            let index = self.free_indices[n as usize - 1];
+            Handle::new(index, self.entries[index as usize].generation)
+        } else {
+            // 空闲列表取完后, 第 -n 个新槽位
+            let index = self.entries.len() + n.unsigned_abs();
+            Handle::new(index as u32, Generation::MIN)
+        }
+    }
+
+    /// 给预留的句柄放入值, 提交后生效
+    pub fn insert(&self, handle: Handle<T>, value: T) {
+        self.pending.lock().unwrap().push((handle, value));
+    }
+
+    /// 预留句柄并放入值, 提交后生效
+    pub fn spawn(&self, value: T) -> Handle<T> {
+        let handle = self.reserve();
+        self.insert(handle, value);
+        handle
+    }
+
+    /// 同步点, 把预留的句柄和待提交的值写入池中
+    ///
+    /// 没有放入值的预留句柄会作废, 不是本轮预留的句柄对应的值会被丢弃
+    pub fn commit(&mut self) {
+        let cursor = self.cursor.load(Ordering::Relaxed);
+        let first_free = cursor.clamp(0, self.free_indices.len() as isize) as usize;
+        let mut reserved: Vec<u32> = self.free_indices.drain(first_free..).collect();
+        let first_new = self.entries.len() as u32;
+        let added = if cursor < 0 { cursor.unsigned_abs() } else { 0 };
+        self.entries.extend((0..added).map(|_| Entry {
+            generation: Generation::MIN,
+            value: None,
+        }));
+        reserved.extend(first_new..self.entries.len() as u32);
+        reserved.sort_unstable();
+
+        for (handle, value) in self.pending.lock().unwrap().drain(..) {
+            if reserved.binary_search(&handle.index()).is_err() {
+                continue;
+            }
+            let entry = &mut self.entries[handle.index() as usize];
+            if entry.generation == handle.generation() {
+                if entry.value.is_none() {
+                    self.len += 1;
+                }
+                entry.value = Some(value);
+            }
+        }
+        for index in reserved {
+            if self.entries[index as usize].value.is_none() {
+                self.release(index);
+            }
+        }
+        self.cursor
+            .store(self.free_indices.len() as isize, Ordering::Relaxed);
+    }
+
+    /// 销毁已提交的值, 有未提交的预留时先提交
+    pub fn despawn(&mut self, handle: Handle<T>) -> Option<T> {
+        self.commit();
+        if !self.contains(handle) {
+            return None;
+        }
+        let value = self.entries[handle.index() as usize].value.take();
+        self.release(handle.index());
+        self.len -= 1;
+        self.cursor
+            .store(self.free_indices.len() as isize, Ordering::Relaxed);
+        value
+    }
+
+    /// 句柄是否指向已提交的存活值
+    pub fn contains(&self, handle: Handle<T>) -> bool {
+        handle.is_some()
+            && self
+                .entries
+                .get(handle.index() as usize)
+                .is_some_and(|entry| {
+                    entry.generation == handle.generation() && entry.value.is_some()
+                })
+    }
+
+    /// 借用已提交的值
+    pub fn borrow(&self, handle: Handle<T>) -> Option<&T> {
+        if !self.contains(handle) {
+            return None;
+        }
+        self.entries[handle.index() as usize].value.as_ref()
+    }
+
+    pub fn borrow_mut(&mut self, handle: Handle<T>) -> Option<&mut T> {
+        if !self.contains(handle) {
+            return None;
+        }
+        self.entries[handle.index() as usize].value.as_mut()
+    }
+
+    /// 迭代已提交的值
+    pub fn iter(&self) -> impl Iterator<Item = (Handle<T>, &T)> {
+        self.entries.iter().enumerate().filter_map(|(i, entry)| {
+            entry
+                .value
+                .as_ref()
+                .map(|value| (Handle::new(i as u32, entry.generation), value))
+        })
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (Handle<T>, &mut T)> {
+        self.entries
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(i, entry)| {
+                entry
+                    .value
+                    .as_mut()
+                    .map(|value| (Handle::new(i as u32, entry.generation), value))
+            })
+    }
+
+    /// 世代数用完后不再复用的槽位数
+    pub fn retired(&self) -> usize {
+        self.retired
+    }
+
+    /// 让空槽位的句柄失效并放回空闲列表, 世代数用完时退役
+    fn release(&mut self, index: u32) {
+        let entry = &mut self.entries[index as usize];
+        match entry.generation.checked_add(1) {
+            Some(generation) => {
+                entry.generation = generation;
+                self.free_indices.push(index);
+            }
+            None => self.retired += 1,
+        }
+    }
+}
+
+#[cfg(feature = "rayon")]
+mod parallel {
+    use rayon::prelude::*;
+
+    use super::ConcurrentPool;
+    use crate::Handle;
+
+    impl<T: Sync> ConcurrentPool<T> {
+        /// 并行迭代已提交的值
+        pub fn par_iter(&self) -> impl ParallelIterator<Item = (Handle<T>, &T)> {
+            self.entries
+                .par_iter()
+                .enumerate()
+                .filter_map(|(i, entry)| {
+                    entry
+                        .value
+                        .as_ref()
+                        .map(|value| (Handle::new(i as u32, entry.generation), value))
+                })
+        }
+    }
+
+    impl<T: Send> ConcurrentPool<T> {
+        pub fn par_iter_mut(&mut self) -> impl ParallelIterator<Item = (Handle<T>, &mut T)> {
+            self.entries
+                .par_iter_mut()
+                .enumerate()
+                .filter_map(|(i, entry)| {
+                    entry
+                        .value
+                        .as_mut()
+                        .map(|value| (Handle::new(i as u32, entry.generation), value))
+                })
+        }
+    }
+}
+
+#[cfg(all(test, not(loom)))]
+mod tests {
+    use std::collections::HashSet;
+    use std::thread;
+
+    use super::*;
+
+    #[test]
+    fn spawned_values_appear_after_commit() {
+        let mut pool = ConcurrentPool::new();
+        let a = pool.spawn(1);
+        assert_eq!(pool.borrow(a), None);
+        pool.commit();
+        assert_eq!(pool.borrow(a), Some(&1));
+        assert_eq!(pool.len(), 1);
+
+        assert_eq!(pool.despawn(a), Some(1));
+        let b = pool.spawn(2);
+        pool.commit();
+        assert_eq!(b.index(), a.index());
+        assert_eq!(pool.borrow(a), None);
+        assert_eq!(pool.borrow(b), Some(&2));
+    }
+
+    #[test]
+    fn unfilled_and_foreign_reservations_are_dropped() {
+        let mut pool = ConcurrentPool::new();
+        let kept = pool.spawn("kept");
+        let unfilled = pool.reserve();
+        // 不是本轮预留的句柄
+        pool.insert(Handle::new(7, Generation::MIN), "foreign");
+        pool.commit();
+        assert_eq!(pool.len(), 1);
+        assert_eq!(pool.borrow(kept), Some(&"kept"));
+        assert_eq!(pool.borrow(unfilled), None);
+
+        // 作废的句柄在槽位复用后也不会生效
+        let next = pool.spawn("next");
+        pool.commit();
+        assert_eq!(next.index(), unfilled.index());
+        assert_eq!(pool.borrow(unfilled), None);
+    }
+
+    #[test]
+    fn parallel_spawns_get_distinct_handles() {
+        const THREADS: usize = 8;
+        const PER_THREAD: usize = 2000;
+        let mut pool = ConcurrentPool::new();
+        // 先制造一些空闲槽位, 让预留同时用到空闲列表和新槽位
+        let old: Vec<_> = (0..1000).map(|i| pool.spawn(i)).collect();
+        pool.commit();
+        for handle in old.iter().step_by(2) {
+            pool.despawn(*handle);
+        }
+
+        let handles: Vec<Vec<_>> = thread::scope(|scope| {
+            let workers: Vec<_> = (0..THREADS)
+                .map(|t| {
+                    let pool = &pool;
+                    scope.spawn(move || {
+                        (0..PER_THREAD)
+                            .map(|i| (pool.spawn(t * PER_THREAD + i), t * PER_THREAD + i))
+                            .collect()
+                    })
+                })
+                .collect();
+            workers.into_iter().map(|w| w.join().unwrap()).collect()
+        });
+        pool.commit();
+
+        let handles: Vec<_> = handles.into_iter().flatten().collect();
+        let unique: HashSet<_> = handles.iter().map(|(h, _)| *h).collect();
+        assert_eq!(unique.len(), THREADS * PER_THREAD);
+        assert_eq!(pool.len(), 500 + THREADS * PER_THREAD);
+        for (handle, value) in handles {
+            assert_eq!(pool.borrow(handle), Some(&value));
+        }
+        assert!(old.iter().step_by(2).all(|h| !pool.contains(*h)));
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn rayon_spawn_and_iterate() {
+        use rayon::prelude::*;
+
+        let mut pool = ConcurrentPool::new();
+        (0..10_000u64).into_par_iter().for_each(|i| {
+            pool.spawn(i);
+        });
+        pool.commit();
+        assert_eq!(pool.len(), 10_000);
+        pool.par_iter_mut().for_each(|(_, value)| *value *= 2);
+        let sum: u64 = pool.par_iter().map(|(_, value)| *value).sum();
+        assert_eq!(sum, (0..10_000u64).sum::<u64>() * 2);
+    }
+}
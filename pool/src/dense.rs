@@ -0,0 +1,223 @@
+//! 紧凑存储的对象池
+//!
+//! 值连续存放在一个数组中, 句柄通过槽位表间接找到值, 销毁时把最后一个值移到空位.
+//! 迭代只访问存活的值, 代价是值在数组中的位置会变化, 访问多一次间接寻址.
+
+use crate::{Generation, Handle};
+
+/// 槽位表中的一项
+#[derive(Clone)]
+struct Slot {
+    generation: Generation,
+    /// 值在 `values` 中的位置, 空槽位为 `None`
+    dense: Option<u32>,
+}
+
+/// 紧凑存储的世代对象池, 句柄的语义和 [`Pool`](crate::Pool) 相同
+#[derive(Clone)]
+pub struct DensePool<T> {
+    slots: Vec<Slot>,
+    free_indices: Vec<u32>,
+    values: Vec<T>,
+    /// `values[i]` 所在的槽位
+    owners: Vec<u32>,
+    retired: usize,
+}
+
+impl<T> Default for DensePool<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> DensePool<T> {
+    pub fn new() -> Self {
+        Self {
+            slots: Vec::new(),
+            free_indices: Vec::new(),
+            values: Vec::new(),
+            owners: Vec::new(),
+            retired: 0,
+        }
+    }
+
+    /// 存活的值的数量
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// 向池中添加一个值，返回其句柄
+    pub fn spawn(&mut self, value: T) -> Handle<T> {
+        let dense = Some(self.values.len() as u32);
+        let index = match self.free_indices.pop() {
+            Some(index) => {
+                self.slots[index as usize].dense = dense;
+                index
+            }
+            None => {
+                self.slots.push(Slot {
+                    generation: Generation::MIN,
+                    dense,
+                });
+                self.slots.len() as u32 - 1
+            }
+        };
+        self.values.push(value);
+        self.owners.push(index);
+        Handle::new(index, self.slots[index as usize].generation)
+    }
+
+    /// 销毁池中的值，使其句柄失效, 最后一个值会移到它的位置
+    pub fn despawn(&mut self, handle: Handle<T>) -> Option<T> {
+        let dense = self.dense(handle)? as usize;
+        let slot = &mut self.slots[handle.index() as usize];
+        slot.dense = None;
+        match slot.generation.checked_add(1) {
+            Some(generation) => {
+                slot.generation = generation;
+                self.free_indices.push(handle.index());
+            }
+            None => self.retired += 1,
+        }
+
+        let value = self.values.swap_remove(dense);
+        self.owners.swap_remove(dense);
+        if let Some(&moved) = self.owners.get(dense) {
+            self.slots[moved as usize].dense = Some(dense as u32);
+        }
+        Some(value)
+    }
+
+    /// 借用池中的值
+    pub fn borrow(&self, handle: Handle<T>) -> Option<&T> {
+        let dense = self.dense(handle)?;
+        Some(&self.values[dense as usize])
+    }
+
+    /// 可变的借用池中的值
+    pub fn borrow_mut(&mut self, handle: Handle<T>) -> Option<&mut T> {
+        let dense = self.dense(handle)?;
+        Some(&mut self.values[dense as usize])
+    }
+
+    /// 按存放顺序迭代所有值和它们的句柄
+    pub fn iter(&self) -> impl Iterator<Item = (Handle<T>, &T)> {
+        self.owners
+            .iter()
+            .zip(&self.values)
+            .map(|(&index, value)| (self.handle(index), value))
+    }
+
+    /// 可变迭代所有值和它们的句柄
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (Handle<T>, &mut T)> {
+        let slots = &self.slots;
+        self.owners
+            .iter()
+            .zip(&mut self.values)
+            .map(|(&index, value)| (Handle::new(index, slots[index as usize].generation), value))
+    }
+
+    /// 所有存活的值, 顺序在销毁后会改变
+    pub fn as_slice(&self) -> &[T] {
+        &self.values
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        &mut self.values
+    }
+
+    /// 世代数用完后不再复用的槽位数
+    pub fn retired(&self) -> usize {
+        self.retired
+    }
+
+    fn handle(&self, index: u32) -> Handle<T> {
+        Handle::new(index, self.slots[index as usize].generation)
+    }
+
+    /// 句柄对应的值在 `values` 中的位置
+    fn dense(&self, handle: Handle<T>) -> Option<u32> {
+        if handle.is_none() {
+            return None;
+        }
+        let slot = self.slots.get(handle.index() as usize)?;
+        if slot.generation != handle.generation() {
+            return None;
+        }
+        slot.dense
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn despawn_keeps_values_packed() {
+        let mut pool = DensePool::new();
+        let handles: Vec<_> = (0..5).map(|i| pool.spawn(i)).collect();
+        assert_eq!(pool.despawn(handles[1]), Some(1));
+        assert_eq!(pool.len(), 4);
+        // 最后一个值移到了空位
+        assert_eq!(pool.as_slice(), &[0, 4, 2, 3]);
+        for (i, handle) in handles.iter().enumerate() {
+            let expected = (i != 1).then_some(i);
+            assert_eq!(pool.borrow(*handle).copied(), expected);
+        }
+
+        pool.as_mut_slice()[1] = 40;
+        assert_eq!(pool.borrow(handles[4]), Some(&40));
+        assert_eq!(pool.despawn(handles[4]), Some(40));
+        assert_eq!(pool.despawn(handles[3]), Some(3));
+        assert_eq!(pool.as_slice(), &[0, 2]);
+    }
+
+    #[test]
+    fn stale_handles_are_rejected_after_reuse() {
+        let mut pool = DensePool::new();
+        let old = pool.spawn("old");
+        pool.despawn(old);
+        let new = pool.spawn("new");
+        assert_eq!(new.index(), old.index());
+        assert_eq!(pool.borrow(old), None);
+        assert_eq!(pool.borrow_mut(old), None);
+        assert_eq!(pool.despawn(old), None);
+        assert_eq!(pool.borrow(new), Some(&"new"));
+        assert_eq!(pool.borrow(Handle::NONE), None);
+    }
+
+    #[test]
+    fn iteration_visits_live_values_with_their_handles() {
+        let mut pool = DensePool::new();
+        let handles: Vec<_> = (0..4).map(|i| pool.spawn(i)).collect();
+        pool.despawn(handles[0]);
+        for (handle, value) in pool.iter_mut() {
+            *value *= 10;
+            assert_ne!(handle, handles[0]);
+        }
+        let mut live: Vec<_> = pool.iter().map(|(h, v)| (h, *v)).collect();
+        live.sort_by_key(|(_, v)| *v);
+        assert_eq!(
+            live,
+            vec![(handles[1], 10), (handles[2], 20), (handles[3], 30)]
+        );
+    }
+
+    #[test]
+    fn exhausted_slots_are_retired() {
+        let mut pool = DensePool::new();
+        let first = pool.spawn(1);
+        pool.despawn(first);
+        pool.slots[0].generation = Generation::MAX;
+        let last = pool.spawn(2);
+        assert_eq!(pool.despawn(last), Some(2));
+        assert_eq!(pool.retired(), 1);
+        assert_eq!(pool.spawn(3).index(), 1);
+        assert_eq!(pool.borrow(first), None);
+        assert_eq!(pool.len(), 1);
+    }
+}
@@ -0,0 +1,191 @@
+//! 句柄类型
+
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+#[cfg(not(feature = "wide-generation"))]
+use std::num::NonZeroU32;
+#[cfg(feature = "wide-generation")]
+use std::num::NonZeroU64;
+
+/// 世代数, 开启 `wide-generation` 特性后为 64 位, 槽位实际上不会再用完
+#[cfg(not(feature = "wide-generation"))]
+pub type Generation = NonZeroU32;
+#[cfg(feature = "wide-generation")]
+pub type Generation = NonZeroU64;
+
+/// [`RawHandle::to_bits`] 的类型, 能放下索引和世代数
+#[cfg(not(feature = "wide-generation"))]
+pub type HandleBits = u64;
+#[cfg(feature = "wide-generation")]
+pub type HandleBits = u128;
+
+/// 无类型的世代句柄, 包含索引和世代数
+///
+/// 可以和整数互相转换, 方便交给脚本保存
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RawHandle {
+    pub(crate) index: u32,
+    pub(crate) generation: Generation,
+}
+
+impl RawHandle {
+    pub const NONE: Self = Self {
+        index: u32::MAX,
+        generation: Generation::MIN,
+    };
+
+    pub fn is_some(&self) -> bool {
+        *self != Self::NONE
+    }
+
+    pub fn is_none(&self) -> bool {
+        *self == Self::NONE
+    }
+
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    pub fn generation(&self) -> Generation {
+        self.generation
+    }
+
+    /// 高位为世代数, 低 32 位为索引
+    pub fn to_bits(self) -> HandleBits {
+        (self.generation.get() as HandleBits) << 32 | self.index as HandleBits
+    }
+
+    /// 世代数为 0 或超出范围时不是有效的句柄
+    pub fn from_bits(bits: HandleBits) -> Option<Self> {
+        Some(Self {
+            index: bits as u32,
+            generation: Generation::new((bits >> 32).try_into().ok()?)?,
+        })
+    }
+}
+
+/// 指向 `Pool<T>` 中一个值的句柄
+///
+/// 不同类型的池的句柄不能混用:
+///
+/// ```compile_fail
+/// use pool::Pool;
+///
+/// let mut bullets: Pool<u8> = Pool::new();
+/// let enemies: Pool<u16> = Pool::new();
+/// let bullet = bullets.spawn(1);
+/// enemies.borrow(bullet);
+/// ```
+pub struct Handle<T> {
+    raw: RawHandle,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Handle<T> {
+    pub const NONE: Self = Self::from_raw(RawHandle::NONE);
+
+    /// 把无类型的句柄当作 `T` 的句柄使用, 类型不对时只会在池中找不到值或找到别的值
+    pub const fn from_raw(raw: RawHandle) -> Self {
+        Self {
+            raw,
+            _marker: PhantomData,
+        }
+    }
+
+    pub(crate) fn new(index: u32, generation: Generation) -> Self {
+        Self::from_raw(RawHandle { index, generation })
+    }
+
+    /// 去掉类型信息
+    pub fn raw(self) -> RawHandle {
+        self.raw
+    }
+
+    pub fn is_some(&self) -> bool {
+        self.raw.is_some()
+    }
+
+    pub fn is_none(&self) -> bool {
+        self.raw.is_none()
+    }
+
+    pub fn index(&self) -> u32 {
+        self.raw.index
+    }
+
+    pub fn generation(&self) -> Generation {
+        self.raw.generation
+    }
+}
+
+// 手动实现, 派生会要求 `T` 也实现这些 trait
+
+impl<T> Clone for Handle<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Handle<T> {}
+
+impl<T> PartialEq for Handle<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.raw == other.raw
+    }
+}
+
+impl<T> Eq for Handle<T> {}
+
+impl<T> Hash for Handle<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.raw.hash(state);
+    }
+}
+
+impl<T> fmt::Debug for Handle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Handle")
+            .field("index", &self.raw.index)
+            .field("generation", &self.raw.generation)
+            .finish()
+    }
+}
+
+impl<T> From<Handle<T>> for RawHandle {
+    fn from(handle: Handle<T>) -> Self {
+        handle.raw
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn raw_handles_round_trip_through_bits() {
+        let raw = RawHandle {
+            index: 7,
+            generation: Generation::new(3).unwrap(),
+        };
+        assert_eq!(raw.to_bits(), 3 << 32 | 7);
+        assert_eq!(RawHandle::from_bits(raw.to_bits()), Some(raw));
+        assert_eq!(RawHandle::from_bits(7), None);
+        assert!(RawHandle::NONE.is_none());
+        assert!(raw.is_some());
+
+        let handle: Handle<()> = Handle::from_raw(raw);
+        assert_eq!(RawHandle::from(handle), raw);
+        assert_eq!(handle.raw(), raw);
+    }
+
+    #[test]
+    fn handles_do_not_require_traits_on_the_value() {
+        struct Opaque;
+        let handle: Handle<Opaque> = Handle::new(0, Generation::MIN);
+        let copy = handle;
+        assert_eq!(handle, copy);
+        assert_eq!(format!("{handle:?}"), "Handle { index: 0, generation: 1 }");
+    }
+}
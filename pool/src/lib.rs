@@ -0,0 +1,576 @@
+//! 世代对象池
+//!
+//! 池中的值用 [`Handle<T>`] 引用, 句柄带有世代数, 值被销毁后旧的句柄会失效,
+//! 即使槽位被新的值复用也不会访问到错误的对象. 句柄还带有值的类型,
+//! `Pool<Bullet>` 的句柄不能用在 `Pool<Enemy>` 上; 脚本等需要无类型句柄的地方可以使用
+//! [`RawHandle`].
+//!
+//! 一个槽位的世代数用完后不再复用, 避免很久以前的句柄重新生效;
+//! 开启 `wide-generation` 特性可以把世代数扩大到 64 位.
+//!
+//! 需要频繁遍历、空位又多时可以改用值连续存放的 [`DensePool`];
+//! 需要在多个线程中同时创建值时使用 [`ConcurrentPool`].
+//!
+//! [`Pool::snapshot`] 和 [`Pool::restore`] 用于回滚, 开启 `serde` 特性后池和句柄都可以序列化.
+
+mod concurrent;
+mod dense;
+mod handle;
+mod snapshot;
+mod track;
+
+use std::ops::{Index, IndexMut};
+use std::panic::Location;
+
+pub use concurrent::ConcurrentPool;
+pub use dense::DensePool;
+pub use handle::{Generation, Handle, HandleBits, RawHandle};
+pub use snapshot::Snapshot;
+use track::Tracker;
+pub use track::{Origin, StaleAccess};
+
+#[derive(Clone)]
+struct Entry<T> {
+    generation: Generation,
+    value: Option<T>,
+}
+
+pub struct Pool<T> {
+    entries: Vec<Entry<T>>,
+    free_indices: Vec<u32>,
+    /// 存活的值的数量
+    len: usize,
+    /// 世代数用完后不再复用的槽位数
+    retired: usize,
+    /// 开启失效句柄追踪时才有
+    tracker: Option<Box<Tracker>>,
+}
+
+impl<T> Default for Pool<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Pool<T> {
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    /// 预先分配 `capacity` 个槽位的空间
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            entries: Vec::with_capacity(capacity),
+            free_indices: Vec::new(),
+            len: 0,
+            retired: 0,
+            tracker: None,
+        }
+    }
+
+    /// 存活的值的数量
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// 不用重新分配就能容纳的值的数量
+    pub fn capacity(&self) -> usize {
+        self.entries.capacity() - self.entries.len() + self.free_indices.len()
+    }
+
+    /// 向池中添加一个值，返回其句柄
+    #[track_caller]
+    pub fn spawn(&mut self, value: T) -> Handle<T> {
+        self.spawn_with(|_| value)
+    }
+
+    /// 先确定句柄再构造值, 值可以在构造时保存自己的句柄
+    #[track_caller]
+    pub fn spawn_with(&mut self, make: impl FnOnce(Handle<T>) -> T) -> Handle<T> {
+        // 先算出句柄, `make` panic 时池不受影响
+        let handle = match self.free_indices.last() {
+            Some(&index) => Handle::new(index, self.entries[index as usize].generation),
+            // 从1开始，因为0不是有效的NonZeroU32
+            None => Handle::new(self.entries.len() as u32, Generation::MIN),
+        };
+        let value = make(handle);
+
+        if self.free_indices.pop().is_some() {
+            self.entries[handle.index() as usize].value = Some(value);
+        } else {
+            self.entries.push(Entry {
+                generation: handle.generation(),
+                value: Some(value),
+            });
+        }
+        self.len += 1;
+        if let Some(tracker) = &mut self.tracker {
+            tracker.spawned(handle.raw(), Location::caller());
+        }
+        handle
+    }
+
+    /// 销毁池中的值，使其句柄失效
+    ///
+    /// 世代数已经用完的槽位不再放回空闲列表, 保证旧句柄永远不会重新生效
+    #[track_caller]
+    pub fn despawn(&mut self, handle: Handle<T>) -> Option<T> {
+        if !self.contains(handle) {
+            self.stale(handle);
+            return None;
+        }
+        if let Some(tracker) = &mut self.tracker {
+            tracker.despawned(handle.raw(), Location::caller());
+        }
+        self.remove_at(handle.index())
+    }
+
+    /// 句柄是否指向存活的值, 不算作失效句柄的访问
+    pub fn contains(&self, handle: Handle<T>) -> bool {
+        handle.is_some()
+            && self
+                .entries
+                .get(handle.index() as usize)
+                .is_some_and(|entry| {
+                    entry.generation == handle.generation() && entry.value.is_some()
+                })
+    }
+
+    /// 借用池中的值
+    #[track_caller]
+    pub fn borrow(&self, handle: Handle<T>) -> Option<&T> {
+        if !self.contains(handle) {
+            self.stale(handle);
+            return None;
+        }
+        self.entries[handle.index() as usize].value.as_ref()
+    }
+
+    /// 可变的借用池中的值
+    #[track_caller]
+    pub fn borrow_mut(&mut self, handle: Handle<T>) -> Option<&mut T> {
+        if !self.contains(handle) {
+            self.stale(handle);
+            return None;
+        }
+        self.entries[handle.index() as usize].value.as_mut()
+    }
+
+    /// 同时可变借用多个值, 例如处理两两碰撞
+    ///
+    /// 任意一个句柄失效, 或者有两个句柄指向同一个值时返回 `None`
+    #[track_caller]
+    pub fn get_disjoint_mut<const N: usize>(
+        &mut self,
+        handles: [Handle<T>; N],
+    ) -> Option<[&mut T; N]> {
+        for handle in handles {
+            if !self.contains(handle) {
+                self.stale(handle);
+                return None;
+            }
+        }
+        let indices = handles.map(|handle| handle.index() as usize);
+        let entries = self.entries.get_disjoint_mut(indices).ok()?;
+        Some(entries.map(|entry| entry.value.as_mut().unwrap()))
+    }
+
+    /// 销毁所有值, 之前的句柄全部失效
+    pub fn clear(&mut self) {
+        for index in 0..self.entries.len() as u32 {
+            self.remove_at(index);
+        }
+    }
+
+    /// 只保留 `keep` 返回 `true` 的值
+    pub fn retain(&mut self, mut keep: impl FnMut(Handle<T>, &mut T) -> bool) {
+        for index in 0..self.entries.len() as u32 {
+            let entry = &mut self.entries[index as usize];
+            let handle = Handle::new(index, entry.generation);
+            if let Some(value) = &mut entry.value
+                && !keep(handle, value)
+            {
+                self.remove_at(index);
+            }
+        }
+    }
+
+    /// 取出所有值和它们的句柄, 迭代器被丢弃时剩下的值也会被销毁
+    pub fn drain(&mut self) -> Drain<'_, T> {
+        Drain {
+            pool: self,
+            index: 0,
+        }
+    }
+
+    /// 迭代池中的所有值
+    pub fn iter(&self) -> impl Iterator<Item = (Handle<T>, &T)> {
+        self.entries.iter().enumerate().filter_map(|(i, entry)| {
+            entry
+                .value
+                .as_ref()
+                .map(|value| (Handle::new(i as u32, entry.generation), value))
+        })
+    }
+
+    /// 可变迭代池中的所有值
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (Handle<T>, &mut T)> {
+        self.entries
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(i, entry)| {
+                entry
+                    .value
+                    .as_mut()
+                    .map(|value| (Handle::new(i as u32, entry.generation), value))
+            })
+    }
+
+    /// 世代数用完后不再复用的槽位数
+    pub fn retired(&self) -> usize {
+        self.retired
+    }
+
+    /// 开关失效句柄追踪, 只有开启之后创建的句柄才能查到来历
+    pub fn track_stale(&mut self, enabled: bool) {
+        if !enabled {
+            self.tracker = None;
+        } else if self.tracker.is_none() {
+            self.tracker = Some(Box::default());
+        }
+    }
+
+    /// 取出追踪期间记录的失效句柄访问
+    pub fn take_stale_accesses(&mut self) -> Vec<StaleAccess> {
+        self.tracker
+            .as_mut()
+            .map(|tracker| tracker.take_stale())
+            .unwrap_or_default()
+    }
+
+    /// 取出槽位中的值并让它的句柄失效, 槽位为空时什么也不做
+    fn remove_at(&mut self, index: u32) -> Option<T> {
+        let entry = &mut self.entries[index as usize];
+        let value = entry.value.take()?;
+        match entry.generation.checked_add(1) {
+            Some(generation) => {
+                entry.generation = generation;
+                // 将索引添加到空闲列表
+                self.free_indices.push(index);
+            }
+            None => self.retired += 1,
+        }
+        self.len -= 1;
+        Some(value)
+    }
+
+    /// 记录一次失效句柄的访问, `NONE` 表示没有值, 不算误用
+    #[track_caller]
+    fn stale(&self, handle: Handle<T>) {
+        if let Some(tracker) = &self.tracker
+            && handle.is_some()
+        {
+            tracker.stale(handle.raw(), Location::caller());
+        }
+    }
+}
+
+impl<T> Index<Handle<T>> for Pool<T> {
+    type Output = T;
+
+    /// 句柄失效时 panic
+    #[track_caller]
+    fn index(&self, handle: Handle<T>) -> &T {
+        match self.borrow(handle) {
+            Some(value) => value,
+            None => panic!("invalid handle {handle:?}"),
+        }
+    }
+}
+
+impl<T> IndexMut<Handle<T>> for Pool<T> {
+    #[track_caller]
+    fn index_mut(&mut self, handle: Handle<T>) -> &mut T {
+        match self.borrow_mut(handle) {
+            Some(value) => value,
+            None => panic!("invalid handle {handle:?}"),
+        }
+    }
+}
+
+/// [`Pool::drain`] 返回的迭代器
+pub struct Drain<'a, T> {
+    pool: &'a mut Pool<T>,
+    index: u32,
+}
+
+impl<T> Iterator for Drain<'_, T> {
+    type Item = (Handle<T>, T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while (self.index as usize) < self.pool.entries.len() {
+            let index = self.index;
+            self.index += 1;
+            let generation = self.pool.entries[index as usize].generation;
+            if let Some(value) = self.pool.remove_at(index) {
+                return Some((Handle::new(index, generation), value));
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.pool.len, Some(self.pool.len))
+    }
+}
+
+impl<T> Drop for Drain<'_, T> {
+    fn drop(&mut self) {
+        self.for_each(drop);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spawned_values_can_be_borrowed() {
+        let mut pool = Pool::new();
+        let a = pool.spawn("a");
+        let b = pool.spawn("b");
+        assert_ne!(a, b);
+        assert_eq!(pool.borrow(a), Some(&"a"));
+        assert_eq!(pool.borrow(b), Some(&"b"));
+
+        *pool.borrow_mut(a).unwrap() = "c";
+        assert_eq!(pool.borrow(a), Some(&"c"));
+    }
+
+    #[test]
+    fn despawn_returns_the_value_once() {
+        let mut pool = Pool::new();
+        let a = pool.spawn(1);
+        assert_eq!(pool.despawn(a), Some(1));
+        assert_eq!(pool.despawn(a), None);
+        assert_eq!(pool.borrow(a), None);
+        assert_eq!(pool.borrow_mut(a), None);
+    }
+
+    #[test]
+    fn slots_are_reused_with_a_new_generation() {
+        let mut pool = Pool::new();
+        let old = pool.spawn(1);
+        pool.despawn(old);
+        let new = pool.spawn(2);
+        assert_eq!(new.index(), old.index());
+        assert_ne!(new.generation(), old.generation());
+
+        // 旧句柄不能访问或销毁复用槽位中的新值
+        assert_eq!(pool.borrow(old), None);
+        assert_eq!(pool.despawn(old), None);
+        assert_eq!(pool.borrow(new), Some(&2));
+    }
+
+    #[test]
+    fn none_and_out_of_range_handles_are_rejected() {
+        let mut pool = Pool::new();
+        pool.spawn(1);
+        assert_eq!(pool.borrow(Handle::NONE), None);
+        assert_eq!(pool.despawn(Handle::NONE), None);
+
+        let mut other = Pool::new();
+        for i in 0..10 {
+            other.spawn(i);
+        }
+        let far = other.spawn(10);
+        assert_eq!(pool.borrow(far), None);
+        assert_eq!(pool.borrow_mut(far), None);
+    }
+
+    #[test]
+    fn iteration_skips_despawned_values() {
+        let mut pool = Pool::new();
+        let handles: Vec<_> = (0..5).map(|i| pool.spawn(i)).collect();
+        pool.despawn(handles[1]);
+        pool.despawn(handles[3]);
+
+        let live: Vec<_> = pool.iter().collect();
+        assert_eq!(
+            live,
+            vec![(handles[0], &0), (handles[2], &2), (handles[4], &4)]
+        );
+
+        for (_, value) in pool.iter_mut() {
+            *value *= 10;
+        }
+        assert_eq!(pool.borrow(handles[4]), Some(&40));
+    }
+
+    #[test]
+    fn foreign_raw_handles_are_rejected() {
+        let mut pool = Pool::new();
+        let handle = pool.spawn(1);
+        let raw = RawHandle::from_bits(handle.raw().to_bits() + (1 << 32)).unwrap();
+        assert_eq!(pool.borrow(Handle::from_raw(raw)), None);
+        assert_eq!(pool.borrow(Handle::from_raw(handle.raw())), Some(&1));
+    }
+
+    #[test]
+    fn exhausted_slots_are_retired() {
+        let mut pool = Pool::new();
+        let first = pool.spawn(1);
+        pool.despawn(first);
+        // 模拟槽位已经复用了很多次
+        pool.entries[0].generation = Generation::MAX;
+        let last = pool.spawn(2);
+        assert_eq!(last.generation(), Generation::MAX);
+
+        assert_eq!(pool.despawn(last), Some(2));
+        assert_eq!(pool.retired(), 1);
+        // 槽位不再复用, 任何旧句柄都不会重新生效
+        let next = pool.spawn(3);
+        assert_eq!(next.index(), 1);
+        assert_eq!(pool.borrow(first), None);
+        assert_eq!(pool.borrow(last), None);
+        assert_eq!(pool.iter().count(), 1);
+    }
+
+    #[test]
+    fn tracking_reports_where_a_stale_handle_came_from() {
+        let mut pool = Pool::new();
+        pool.track_stale(true);
+        let handle = pool.spawn(1);
+        pool.despawn(handle);
+        assert_eq!(pool.borrow(handle), None);
+        assert_eq!(pool.borrow(Handle::NONE), None);
+
+        let stale = pool.take_stale_accesses();
+        assert_eq!(stale.len(), 1);
+        let access = &stale[0];
+        assert_eq!(access.handle, handle.raw());
+        let origin = access.origin.unwrap();
+        let despawned_at = origin.despawned_at.unwrap();
+        assert_eq!(origin.spawned_at.file(), file!());
+        assert!(origin.spawned_at.line() < despawned_at.line());
+        assert!(despawned_at.line() < access.accessed_at.line());
+
+        let report = access.to_string();
+        assert!(report.starts_with("stale handle 0v1 used at"));
+        assert!(report.contains("despawned at"));
+        assert!(pool.take_stale_accesses().is_empty());
+    }
+
+    #[test]
+    fn tracking_is_off_by_default() {
+        let mut pool = Pool::new();
+        let handle = pool.spawn(1);
+        pool.despawn(handle);
+        pool.despawn(handle);
+        assert_eq!(pool.borrow_mut(handle), None);
+        assert!(pool.take_stale_accesses().is_empty());
+    }
+
+    #[test]
+    fn len_and_capacity_follow_spawns() {
+        let mut pool = Pool::with_capacity(8);
+        assert!(pool.is_empty());
+        assert!(pool.capacity() >= 8);
+        let a = pool.spawn(1);
+        pool.spawn(2);
+        assert_eq!(pool.len(), 2);
+        pool.despawn(a);
+        pool.despawn(a);
+        assert_eq!(pool.len(), 1);
+        assert!(pool.contains(pool.iter().next().unwrap().0));
+        assert!(!pool.contains(a));
+    }
+
+    #[test]
+    fn clear_invalidates_every_handle() {
+        let mut pool = Pool::new();
+        let handles: Vec<_> = (0..3).map(|i| pool.spawn(i)).collect();
+        pool.clear();
+        assert!(pool.is_empty());
+        assert!(handles.iter().all(|h| !pool.contains(*h)));
+        // 槽位被复用, 但世代数不同
+        let reused = pool.spawn(9);
+        assert!(handles.iter().all(|h| *h != reused));
+        assert_eq!(pool.len(), 1);
+    }
+
+    #[test]
+    fn retain_and_drain() {
+        let mut pool = Pool::new();
+        let handles: Vec<_> = (0..6).map(|i| pool.spawn(i)).collect();
+        pool.retain(|handle, value| {
+            *value *= 10;
+            handle != handles[0] && *value % 20 == 0
+        });
+        assert_eq!(pool.len(), 2);
+        assert_eq!(pool[handles[2]], 20);
+        assert!(!pool.contains(handles[0]));
+
+        let drained: Vec<_> = pool.drain().collect();
+        assert_eq!(drained, vec![(handles[2], 20), (handles[4], 40)]);
+        assert!(pool.is_empty());
+        assert!(!pool.contains(handles[2]));
+
+        // 只取一部分时剩下的也会被销毁
+        let handles: Vec<_> = (0..3).map(|i| pool.spawn(i)).collect();
+        assert!(pool.drain().next().is_some());
+        assert!(pool.is_empty());
+        assert!(handles.iter().all(|h| !pool.contains(*h)));
+    }
+
+    #[test]
+    fn disjoint_borrows() {
+        let mut pool = Pool::new();
+        let a = pool.spawn(1);
+        let b = pool.spawn(2);
+        let [x, y] = pool.get_disjoint_mut([a, b]).unwrap();
+        std::mem::swap(x, y);
+        assert_eq!((pool[a], pool[b]), (2, 1));
+
+        assert!(pool.get_disjoint_mut([a, a]).is_none());
+        pool.despawn(b);
+        assert!(pool.get_disjoint_mut([a, b]).is_none());
+    }
+
+    #[test]
+    fn index_panics_on_stale_handles() {
+        let mut pool = Pool::new();
+        let a = pool.spawn(1);
+        pool[a] += 1;
+        assert_eq!(pool[a], 2);
+        pool.despawn(a);
+        let result = std::panic::catch_unwind(|| pool[a]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn values_can_store_their_own_handle() {
+        struct Node {
+            this: Handle<Node>,
+        }
+        let mut pool = Pool::new();
+        let old = pool.spawn(Node { this: Handle::NONE });
+        pool.despawn(old);
+        let handle = pool.spawn_with(|this| Node { this });
+        assert_eq!(pool[handle].this, handle);
+        let next = pool.spawn_with(|this| Node { this });
+        assert_eq!(pool[next].this, next);
+        assert_ne!(handle, next);
+
+        // 构造时 panic 不会占用槽位
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            pool.spawn_with(|_| panic!("failed"))
+        }));
+        assert!(result.is_err());
+        assert_eq!(pool.len(), 2);
+    }
+}
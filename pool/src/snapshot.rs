@@ -0,0 +1,258 @@
+//! 快照和序列化
+//!
+//! 快照保存每个槽位的世代数和空闲列表的顺序, 恢复后之前的句柄仍然指向同样的值,
+//! 之后创建的句柄也和快照之后第一次创建的相同, 可以用于存档和回滚
+
+use crate::{Entry, Pool};
+
+/// 池在某一时刻的完整状态, 由 [`Pool::snapshot`] 创建
+#[derive(Clone)]
+pub struct Snapshot<T> {
+    entries: Vec<Entry<T>>,
+    free_indices: Vec<u32>,
+    len: usize,
+    retired: usize,
+}
+
+impl<T: Clone> Pool<T> {
+    /// 复制池的当前状态
+    pub fn snapshot(&self) -> Snapshot<T> {
+        Snapshot {
+            entries: self.entries.clone(),
+            free_indices: self.free_indices.clone(),
+            len: self.len,
+            retired: self.retired,
+        }
+    }
+
+    /// 回到快照时的状态, 尽量复用已有的内存, 同一个快照可以反复恢复
+    ///
+    /// 失效句柄追踪保持原样
+    pub fn restore(&mut self, snapshot: &Snapshot<T>) {
+        self.entries.clone_from(&snapshot.entries);
+        self.free_indices.clone_from(&snapshot.free_indices);
+        self.len = snapshot.len;
+        self.retired = snapshot.retired;
+    }
+}
+
+impl<T> From<Snapshot<T>> for Pool<T> {
+    fn from(snapshot: Snapshot<T>) -> Self {
+        Self {
+            entries: snapshot.entries,
+            free_indices: snapshot.free_indices,
+            len: snapshot.len,
+            retired: snapshot.retired,
+            tracker: None,
+        }
+    }
+}
+
+impl<T> Snapshot<T> {
+    /// 检查反序列化得到的状态是否一致, 空槽位要么在空闲列表中, 要么已经退役
+    #[cfg(feature = "serde")]
+    fn validate(&self) -> Result<(), String> {
+        let mut free = vec![false; self.entries.len()];
+        for &index in &self.free_indices {
+            let Some(entry) = self.entries.get(index as usize) else {
+                return Err(format!("free index {index} out of range"));
+            };
+            if entry.value.is_some() {
+                return Err(format!("free index {index} points to a live slot"));
+            }
+            if std::mem::replace(&mut free[index as usize], true) {
+                return Err(format!("free index {index} listed twice"));
+            }
+        }
+        let mut retired = 0;
+        for (entry, free) in self.entries.iter().zip(free) {
+            if entry.value.is_none() && !free {
+                if entry.generation != crate::Generation::MAX {
+                    return Err("empty slot is neither free nor retired".into());
+                }
+                retired += 1;
+            }
+        }
+        if retired != self.retired {
+            return Err(format!(
+                "expected {} retired slots, found {retired}",
+                self.retired
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "serde")]
+mod serde_impls {
+    use serde::de::Error as _;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::Snapshot;
+    use crate::{Entry, Generation, Handle, Pool, RawHandle};
+
+    #[derive(Serialize)]
+    #[serde(rename = "Pool")]
+    struct PoolRef<'a, T> {
+        entries: Vec<(Generation, &'a Option<T>)>,
+        free_indices: &'a [u32],
+        retired: usize,
+    }
+
+    #[derive(Deserialize)]
+    #[serde(rename = "Pool")]
+    struct PoolRepr<T> {
+        entries: Vec<(Generation, Option<T>)>,
+        free_indices: Vec<u32>,
+        retired: usize,
+    }
+
+    fn serialize_state<T: Serialize, S: Serializer>(
+        entries: &[Entry<T>],
+        free_indices: &[u32],
+        retired: usize,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        PoolRef {
+            entries: entries
+                .iter()
+                .map(|entry| (entry.generation, &entry.value))
+                .collect(),
+            free_indices,
+            retired,
+        }
+        .serialize(serializer)
+    }
+
+    impl<T: Serialize> Serialize for Pool<T> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serialize_state(&self.entries, &self.free_indices, self.retired, serializer)
+        }
+    }
+
+    impl<T: Serialize> Serialize for Snapshot<T> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serialize_state(&self.entries, &self.free_indices, self.retired, serializer)
+        }
+    }
+
+    impl<'de, T: Deserialize<'de>> Deserialize<'de> for Snapshot<T> {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let repr = PoolRepr::<T>::deserialize(deserializer)?;
+            let entries: Vec<_> = repr
+                .entries
+                .into_iter()
+                .map(|(generation, value)| Entry { generation, value })
+                .collect();
+            let snapshot = Snapshot {
+                len: entries.iter().filter(|entry| entry.value.is_some()).count(),
+                entries,
+                free_indices: repr.free_indices,
+                retired: repr.retired,
+            };
+            snapshot.validate().map_err(D::Error::custom)?;
+            Ok(snapshot)
+        }
+    }
+
+    impl<'de, T: Deserialize<'de>> Deserialize<'de> for Pool<T> {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            Snapshot::deserialize(deserializer).map(Pool::from)
+        }
+    }
+
+    /// 句柄序列化为 `RawHandle`, 不要求 `T` 可以序列化
+    impl<T> Serialize for Handle<T> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            self.raw().serialize(serializer)
+        }
+    }
+
+    impl<'de, T> Deserialize<'de> for Handle<T> {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            RawHandle::deserialize(deserializer).map(Handle::from_raw)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "serde")]
+    use crate::Handle;
+
+    #[test]
+    fn handles_resolve_identically_after_restore() {
+        let mut pool = Pool::new();
+        let handles: Vec<_> = (0..4).map(|i| pool.spawn(i)).collect();
+        pool.despawn(handles[1]);
+        pool.despawn(handles[2]);
+        let snapshot = pool.snapshot();
+
+        // 快照之后的修改
+        pool.despawn(handles[0]);
+        *pool.borrow_mut(handles[3]).unwrap() = 30;
+        let later = pool.spawn(10);
+        let after_snapshot = [pool.spawn(11), pool.spawn(12)];
+
+        pool.restore(&snapshot);
+        assert_eq!(pool.borrow(handles[0]), Some(&0));
+        assert_eq!(pool.borrow(handles[1]), None);
+        assert_eq!(pool.borrow(handles[3]), Some(&3));
+        assert_eq!(pool.borrow(later), None);
+
+        // 空闲列表的顺序也恢复了, 重新创建得到同样的句柄
+        pool.despawn(handles[0]);
+        assert_eq!(pool.spawn(10), later);
+        assert_eq!([pool.spawn(11), pool.spawn(12)], after_snapshot);
+
+        // 同一个快照可以再次恢复
+        pool.restore(&snapshot);
+        assert_eq!(pool.iter().count(), 2);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn pools_and_handles_round_trip_through_serde() {
+        let mut pool = Pool::new();
+        let handles: Vec<_> = (0..3).map(|i| pool.spawn(format!("v{i}"))).collect();
+        pool.despawn(handles[0]);
+        let reused = pool.spawn("reused".to_string());
+
+        let json = serde_json::to_string(&(&pool, &handles, reused)).unwrap();
+        let (mut loaded, loaded_handles, loaded_reused): (Pool<String>, Vec<Handle<String>>, _) =
+            serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded_handles, handles);
+        assert_eq!(loaded.borrow(handles[0]), None);
+        assert_eq!(loaded.borrow(handles[2]).unwrap(), "v2");
+        assert_eq!(loaded.borrow(loaded_reused).unwrap(), "reused");
+        assert_eq!(
+            loaded.spawn("next".into()),
+            pool.spawn("next".into()),
+            "free list order survives"
+        );
+
+        let snapshot: Snapshot<String> =
+            serde_json::from_str(&serde_json::to_string(&pool.snapshot()).unwrap()).unwrap();
+        loaded.restore(&snapshot);
+        assert_eq!(loaded.iter().count(), pool.iter().count());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn inconsistent_state_is_rejected() {
+        let bad = [
+            r#"{"entries":[[1,5]],"free_indices":[0],"retired":0}"#,
+            r#"{"entries":[[1,null]],"free_indices":[3],"retired":0}"#,
+            r#"{"entries":[[1,null]],"free_indices":[0,0],"retired":0}"#,
+            r#"{"entries":[[1,null]],"free_indices":[],"retired":0}"#,
+            r#"{"entries":[[1,null]],"free_indices":[0],"retired":1}"#,
+        ];
+        for json in bad {
+            assert!(serde_json::from_str::<Pool<u8>>(json).is_err(), "{json}");
+        }
+        let good = r#"{"entries":[[1,null],[2,7]],"free_indices":[0],"retired":0}"#;
+        let pool: Pool<u8> = serde_json::from_str(good).unwrap();
+        assert_eq!(pool.iter().count(), 1);
+    }
+}
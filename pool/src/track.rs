@@ -0,0 +1,91 @@
+//! 失效句柄的追踪, 用于排查销毁后继续使用的问题
+//!
+//! 开启后池会记录每个句柄在哪里创建和销毁, 用失效的句柄访问时记下访问的位置,
+//! 记录只增不减, 只应在调试时开启
+
+use std::collections::HashMap;
+use std::fmt;
+use std::panic::Location;
+use std::sync::Mutex;
+
+use crate::handle::RawHandle;
+
+/// 一个句柄的来历
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Origin {
+    pub spawned_at: &'static Location<'static>,
+    /// 还没有销毁时为 `None`
+    pub despawned_at: Option<&'static Location<'static>>,
+}
+
+/// 一次用失效句柄的访问
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StaleAccess {
+    pub handle: RawHandle,
+    pub accessed_at: &'static Location<'static>,
+    /// 句柄不是在追踪期间创建的, 或者来自别的池时为 `None`
+    pub origin: Option<Origin>,
+}
+
+impl fmt::Display for StaleAccess {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "stale handle {}v{} used at {}",
+            self.handle.index(),
+            self.handle.generation(),
+            self.accessed_at
+        )?;
+        match self.origin {
+            Some(Origin {
+                spawned_at,
+                despawned_at,
+            }) => {
+                write!(f, ", spawned at {spawned_at}")?;
+                if let Some(despawned_at) = despawned_at {
+                    write!(f, ", despawned at {despawned_at}")?;
+                }
+                Ok(())
+            }
+            None => write!(f, ", origin unknown"),
+        }
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct Tracker {
+    origins: HashMap<RawHandle, Origin>,
+    /// `borrow` 只有 `&self`, 所以用锁记录
+    stale: Mutex<Vec<StaleAccess>>,
+}
+
+impl Tracker {
+    pub fn spawned(&mut self, handle: RawHandle, at: &'static Location<'static>) {
+        self.origins.insert(
+            handle,
+            Origin {
+                spawned_at: at,
+                despawned_at: None,
+            },
+        );
+    }
+
+    pub fn despawned(&mut self, handle: RawHandle, at: &'static Location<'static>) {
+        if let Some(origin) = self.origins.get_mut(&handle) {
+            origin.despawned_at = Some(at);
+        }
+    }
+
+    pub fn stale(&self, handle: RawHandle, at: &'static Location<'static>) {
+        let access = StaleAccess {
+            handle,
+            accessed_at: at,
+            origin: self.origins.get(&handle).copied(),
+        };
+        self.stale.lock().unwrap().push(access);
+    }
+
+    pub fn take_stale(&mut self) -> Vec<StaleAccess> {
+        std::mem::take(self.stale.get_mut().unwrap())
+    }
+}
@@ -0,0 +1,56 @@
+//! 用 loom 穷举并发预留的交错顺序
+//!
+//! `RUSTFLAGS="--cfg loom" cargo test -p pool --test loom --release`
+
+#![cfg(loom)]
+
+use loom::sync::Arc;
+use loom::thread;
+use pool::ConcurrentPool;
+
+#[test]
+fn concurrent_reservations_are_unique() {
+    loom::model(|| {
+        let mut pool = ConcurrentPool::new();
+        let first = pool.spawn(0);
+        pool.commit();
+        // 留下一个空闲槽位, 两个线程中会有一个复用它
+        pool.despawn(first);
+
+        let pool = Arc::new(pool);
+        let workers: Vec<_> = (1..=2)
+            .map(|i| {
+                let pool = pool.clone();
+                thread::spawn(move || pool.spawn(i))
+            })
+            .collect();
+        let handles: Vec<_> = workers.into_iter().map(|w| w.join().unwrap()).collect();
+        assert_ne!(handles[0], handles[1]);
+
+        let mut pool = Arc::try_unwrap(pool).ok().unwrap();
+        pool.commit();
+        assert_eq!(pool.len(), 2);
+        assert_eq!(pool.borrow(handles[0]), Some(&1));
+        assert_eq!(pool.borrow(handles[1]), Some(&2));
+        assert_eq!(pool.borrow(first), None);
+    });
+}
+
+#[test]
+fn reserve_and_insert_from_different_threads() {
+    loom::model(|| {
+        let pool = Arc::new(ConcurrentPool::new());
+        let handle = pool.reserve();
+        let writer = {
+            let pool = pool.clone();
+            thread::spawn(move || pool.insert(handle, "late"))
+        };
+        let other = pool.spawn("other");
+        writer.join().unwrap();
+
+        let mut pool = Arc::try_unwrap(pool).ok().unwrap();
+        pool.commit();
+        assert_eq!(pool.borrow(handle), Some(&"late"));
+        assert_eq!(pool.borrow(other), Some(&"other"));
+    });
+}